@@ -4,7 +4,10 @@ use criterion::Criterion;
 use criterion::black_box;
 use criterion::criterion_group;
 use criterion::criterion_main;
+use sandy_engine::evaluation::evaluate;
+use sandy_engine::evaluation::material::interpolate;
 use sandy_engine::opts::Opts;
+use sandy_engine::position::Position;
 use sandy_engine::search::moveordering::ordered_moves;
 use sandy_engine::util::bench_positions;
 
@@ -28,6 +31,28 @@ fn evaluation_benches(c: &mut Criterion) {
             }
         })
     });
+
+    // only the static evaluation, without setting up boards or generating moves
+    let positions = bench_positions()
+        .into_iter()
+        .map(Position::from)
+        .collect::<Vec<_>>();
+
+    group.bench_function("eval_static", |b| {
+        b.iter(|| {
+            for pos in positions.iter() {
+                black_box(evaluate(black_box(pos), false));
+            }
+        })
+    });
+
+    group.bench_function("game_phase", |b| {
+        b.iter(|| {
+            for pos in positions.iter() {
+                black_box(interpolate(black_box(&pos.chessboard)));
+            }
+        })
+    });
 }
 
 criterion_group! {
@@ -24,7 +24,7 @@ fn negamax_benches(c: &mut Criterion) {
         let table = TT::new();
         let positions = bench_positions()
             .into_iter()
-            .chain(bench_positions().into_iter())
+            .chain(bench_positions())
             .map(Position::from)
             .collect::<Vec<Position>>();
 
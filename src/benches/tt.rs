@@ -0,0 +1,89 @@
+//! Side by side benchmarks of the transposition table implementations.
+//! To compare a new table design, add it to [`tables`] in both benchmarks.
+#![allow(missing_docs)]
+use std::sync::Arc;
+use std::sync::RwLock;
+
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use criterion::Throughput;
+use criterion::black_box;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use sandy_engine::opts::Opts;
+use sandy_engine::setup::depth::Depth;
+use sandy_engine::transposition_table::TranspositionTable;
+use sandy_engine::transposition_table::empty_table::EmptyEntry;
+use sandy_engine::transposition_table::empty_table::EmptyTable;
+use sandy_engine::transposition_table::entry::TableEntry;
+use sandy_engine::transposition_table::harness::nodes_to_depth;
+use sandy_engine::transposition_table::harness::throughput;
+use sandy_engine::transposition_table::vl::VL;
+use sandy_engine::transposition_table::vl::VlShare;
+
+/// the size of the tables under test, in bytes
+const TABLE_SIZE: usize = 16 * 1024 * 1024;
+/// inserts (and probes) per thread, per iteration
+const OPS: usize = 100_000;
+
+/// a fresh [`VL`] table, shared the way the search shares it
+fn vl() -> VlShare {
+    Arc::new(RwLock::new(VL::new(TABLE_SIZE)))
+}
+
+/// insert/probe throughput, under contention from several threads
+fn throughput_benches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tt_throughput");
+    group.noise_threshold(0.08);
+
+    for threads in [1, 2, 4, 8] {
+        // one insert and (almost) one probe per op
+        group.throughput(Throughput::Elements((2 * threads * OPS) as u64));
+
+        let table = vl();
+        group.bench_with_input(BenchmarkId::new("vl", threads), &threads, |b, &t| {
+            b.iter(|| throughput(black_box(&table), t, OPS))
+        });
+
+        let table: EmptyTable<u64, TableEntry> = EmptyTable::new(TABLE_SIZE);
+        group.bench_with_input(BenchmarkId::new("empty", threads), &threads, |b, &t| {
+            b.iter(|| throughput(black_box(&table), t, OPS))
+        });
+    }
+
+    group.finish();
+}
+
+/// time to search the bench positions to a fixed depth
+fn nodes_to_depth_benches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tt_nodes_to_depth");
+    group.noise_threshold(0.08);
+    group.sample_size(10);
+
+    for depth in [3, 4, 5] {
+        let depth = Depth(depth);
+
+        let table = vl();
+        let run = nodes_to_depth(&table, depth, &Opts::bench());
+        println!("vl depth {}: {} nodes", depth.0, run.nodes);
+        group.bench_with_input(BenchmarkId::new("vl", depth.0), &depth, |b, &d| {
+            b.iter(|| nodes_to_depth(black_box(&table), d, &Opts::bench()))
+        });
+
+        let table: EmptyTable<u64, EmptyEntry> = EmptyTable::new(TABLE_SIZE);
+        let run = nodes_to_depth(&table, depth, &Opts::bench());
+        println!("empty depth {}: {} nodes", depth.0, run.nodes);
+        group.bench_with_input(BenchmarkId::new("empty", depth.0), &depth, |b, &d| {
+            b.iter(|| nodes_to_depth(black_box(&table), d, &Opts::bench()))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default();
+    targets = throughput_benches, nodes_to_depth_benches
+}
+criterion_main!(benches);
@@ -0,0 +1,156 @@
+//! material and piece-square scores, updated incrementally with every move
+use chess::Board;
+use chess::ChessMove;
+use chess::Color;
+use chess::File;
+use chess::Piece;
+use chess::Square;
+
+use crate::evaluation::Interp;
+use crate::evaluation::material::INITIAL_VALUES;
+use crate::evaluation::material::interpolate_material;
+use crate::evaluation::material::material;
+use crate::evaluation::material::total_material;
+use crate::evaluation::params::Weights;
+use crate::evaluation::params::with_weights;
+use crate::evaluation::position::piece_position_benefit_for_side;
+use crate::evaluation::position::piece_square;
+use crate::evaluation::score::Score;
+use crate::setup::values::Value;
+
+/// The material and piece-square [`Score`]s of both sides, indexed by
+/// [`Color::to_index`]. [`Position::make_move`] only adjusts them for the
+/// pieces that moved, instead of rescanning the whole board.
+///
+/// [`Position::make_move`]: crate::position::Position::make_move
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Accumulator {
+    /// the material of each side
+    pub material: [Score; 2],
+    /// the piece-square benefit of each side
+    pub psqt: [Score; 2],
+    /// the [`total_material`] on the board, which the phase of the game is
+    /// derived from. unlike the material scores, it doesn't depend on the
+    /// [`Weights`] in use.
+    ///
+    /// [`total_material`]: crate::evaluation::material::total_material
+    pub phase: i32,
+}
+
+impl Accumulator {
+    /// compute the scores from scratch, with the [`Weights`] in use
+    pub fn new(board: &Board) -> Self {
+        with_weights(|weights| Self::with_weights(weights, board))
+    }
+
+    /// compute the scores from scratch, with the given [`Weights`]
+    pub fn with_weights(weights: &Weights, board: &Board) -> Self {
+        Self {
+            material: [
+                material(weights, board, Color::White),
+                material(weights, board, Color::Black),
+            ],
+            psqt: [
+                piece_position_benefit_for_side(weights, board, Color::White),
+                piece_position_benefit_for_side(weights, board, Color::Black),
+            ],
+            phase: total_material(board).0 as i32,
+        }
+    }
+
+    /// the material and piece-square score of `side`, minus its opponent's
+    pub fn score(&self, side: Color) -> Score {
+        let (us, them) = (side.to_index(), (!side).to_index());
+        self.material[us] - self.material[them] + self.psqt[us] - self.psqt[them]
+    }
+
+    /// the material of `side`, minus its opponent's
+    pub fn material_balance(&self, side: Color) -> Score {
+        self.material[side.to_index()] - self.material[(!side).to_index()]
+    }
+
+    /// the phase of the game, from the material on the board
+    pub fn interp(&self) -> Interp {
+        interpolate_material(Value(self.phase as i16))
+    }
+
+    /// place a piece on the board
+    #[inline(always)]
+    fn add(&mut self, weights: &Weights, piece: Piece, color: Color, sq: Square) {
+        if piece != Piece::King {
+            self.material[color.to_index()] += weights.piece_scores[piece.to_index()];
+            self.phase += INITIAL_VALUES[piece.to_index()].0 as i32;
+        }
+        self.psqt[color.to_index()] += piece_square(weights, piece, color, sq);
+    }
+
+    /// take a piece off the board
+    #[inline(always)]
+    fn remove(&mut self, weights: &Weights, piece: Piece, color: Color, sq: Square) {
+        if piece != Piece::King {
+            self.material[color.to_index()] -= weights.piece_scores[piece.to_index()];
+            self.phase -= INITIAL_VALUES[piece.to_index()].0 as i32;
+        }
+        self.psqt[color.to_index()] -= piece_square(weights, piece, color, sq);
+    }
+
+    /// the scores after `mv` is played on `board`, the position *before* the
+    /// move. `mv` must be legal.
+    pub fn make_move(&self, board: &Board, mv: ChessMove) -> Self {
+        with_weights(|weights| self.make_move_with(weights, board, mv))
+    }
+
+    /// same as [`Accumulator::make_move`], with the given [`Weights`]
+    pub fn make_move_with(&self, weights: &Weights, board: &Board, mv: ChessMove) -> Self {
+        let mut next = *self;
+        let (src, dest) = (mv.get_source(), mv.get_dest());
+        let us = board.side_to_move();
+        let files_moved = src
+            .get_file()
+            .to_index()
+            .abs_diff(dest.get_file().to_index());
+        let Some(piece) = board.piece_on(src) else {
+            return next;
+        };
+
+        next.remove(weights, piece, us, src);
+        next.add(weights, mv.get_promotion().unwrap_or(piece), us, dest);
+
+        if let Some(captured) = board.piece_on(dest) {
+            next.remove(weights, captured, !us, dest);
+        } else if piece == Piece::Pawn && files_moved != 0 {
+            // en passant, the captured pawn is next to the one that moved
+            next.remove(
+                weights,
+                Piece::Pawn,
+                !us,
+                Square::make_square(src.get_rank(), dest.get_file()),
+            );
+        } else if piece == Piece::King && files_moved == 2 {
+            // castling, move the rook to the other side of the king
+            let (from, to) = if dest.get_file() == File::G {
+                (File::H, File::F)
+            } else {
+                (File::A, File::D)
+            };
+            next.remove(
+                weights,
+                Piece::Rook,
+                us,
+                Square::make_square(src.get_rank(), from),
+            );
+            next.add(
+                weights,
+                Piece::Rook,
+                us,
+                Square::make_square(src.get_rank(), to),
+            );
+        }
+
+        next
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/accumulator.rs"]
+mod tests;
@@ -0,0 +1,101 @@
+//! piece activity: the bishop pair, rooks on open files and the 7th rank, and
+//! knight outposts
+use chess::Board;
+use chess::Color;
+use chess::EMPTY;
+use chess::Piece;
+use chess::Rank;
+use chess::get_adjacent_files;
+use chess::get_file;
+use chess::get_rank;
+
+use crate::evaluation::attacks::AttackMap;
+use crate::evaluation::pawns::ranks_ahead;
+use crate::evaluation::pawns::relative_rank;
+use crate::evaluation::score::Score;
+
+/// The weights of the piece activity terms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActivityParams {
+    /// bonus for having (at least) two bishops
+    pub bishop_pair: Score,
+    /// bonus for a rook on a file without pawns
+    pub rook_open_file: Score,
+    /// bonus for a rook on a file without pawns of its side
+    pub rook_semi_open_file: Score,
+    /// bonus for a rook on the 7th rank, when the enemy king is stuck behind
+    /// it or there are enemy pawns to attack
+    pub rook_on_seventh: Score,
+    /// bonus for a knight on the 4th to 6th rank, defended by a pawn, that no
+    /// enemy pawn can ever chase away
+    pub knight_outpost: Score,
+}
+
+impl ActivityParams {
+    /// the default weights
+    pub const DEFAULT: Self = Self {
+        bishop_pair: Score::new(30, 50),
+        rook_open_file: Score::new(40, 20),
+        rook_semi_open_file: Score::new(20, 7),
+        rook_on_seventh: Score::new(10, 25),
+        knight_outpost: Score::new(30, 20),
+    };
+}
+
+impl Default for ActivityParams {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// the activity of `color`'s pieces
+pub fn piece_activity(
+    board: &Board,
+    color: Color,
+    attacks: &AttackMap,
+    params: &ActivityParams,
+) -> Score {
+    let ours = board.color_combined(color);
+    let pawns = board.pieces(Piece::Pawn);
+    let our_pawns = pawns & ours;
+    let their_pawns = pawns & board.color_combined(!color);
+
+    let mut score = Score::ZERO;
+
+    if (board.pieces(Piece::Bishop) & ours).popcnt() >= 2 {
+        score += params.bishop_pair;
+    }
+
+    let their_king_rank = relative_rank(board.king_square(!color), color);
+    let seventh = Rank::from_index(match color {
+        Color::White => 6,
+        Color::Black => 1,
+    });
+    for sq in board.pieces(Piece::Rook) & ours {
+        let file = get_file(sq.get_file());
+        if pawns & file == EMPTY {
+            score += params.rook_open_file;
+        } else if our_pawns & file == EMPTY {
+            score += params.rook_semi_open_file;
+        }
+        if sq.get_rank() == seventh
+            && (their_king_rank == 7 || their_pawns & get_rank(seventh) != EMPTY)
+        {
+            score += params.rook_on_seventh;
+        }
+    }
+
+    let defended = attacks.attacked_by(color, Piece::Pawn);
+    for sq in board.pieces(Piece::Knight) & ours & defended {
+        let chasers = their_pawns & get_adjacent_files(sq.get_file()) & ranks_ahead(sq, color);
+        if (3..=5).contains(&relative_rank(sq, color)) && chasers == EMPTY {
+            score += params.knight_outpost;
+        }
+    }
+
+    score
+}
+
+#[cfg(test)]
+#[path = "tests/activity.rs"]
+mod tests;
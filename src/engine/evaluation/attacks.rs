@@ -0,0 +1,126 @@
+//! a single pass over the pieces of a position, collecting the attacks that
+//! several evaluation terms need
+use chess::BitBoard;
+use chess::Board;
+use chess::Color;
+use chess::EMPTY;
+use chess::Piece;
+use chess::Square;
+use chess::get_bishop_moves;
+use chess::get_knight_moves;
+use chess::get_rook_moves;
+
+use crate::evaluation::bitboards::king_attacks;
+use crate::evaluation::pawns::pawn_attacks;
+use crate::evaluation::score::Score;
+
+/// the pieces whose mobility is evaluated, which are also the ones whose
+/// attacks on the enemy king zone count towards king danger
+pub const MOBILE_PIECES: [Piece; 4] = [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen];
+
+/// bonus for every square a piece can move to beyond [`MOBILITY_BASE`], for
+/// the pieces in [`MOBILE_PIECES`], by default (see [`params`])
+///
+/// [`params`]: crate::evaluation::params
+pub const MOBILITY: [Score; 4] = [
+    Score::new(4, 4),
+    Score::new(5, 5),
+    Score::new(2, 4),
+    Score::new(1, 2),
+];
+
+/// the number of squares a piece in [`MOBILE_PIECES`] is expected to reach,
+/// fewer is a penalty
+pub const MOBILITY_BASE: [i32; 4] = [4, 6, 7, 13];
+
+/// The attacks of every piece in a position, indexed by [`Color::to_index`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AttackMap {
+    /// the squares attacked by each side's pieces, by [`Piece::to_index`]
+    pub by_piece: [[BitBoard; 6]; 2],
+    /// all the squares attacked by each side
+    pub all: [BitBoard; 2],
+    /// the squares attacked at least twice by each side
+    pub double: [BitBoard; 2],
+    /// the squares around each side's king, including the king's own square
+    pub king_zone: [BitBoard; 2],
+    /// how many of each side's [`MOBILE_PIECES`] attack the enemy king zone
+    pub zone_attackers: [i32; 2],
+    /// how many enemy king zone squares each side attacks, summed over each
+    /// piece type in [`MOBILE_PIECES`]
+    pub zone_hits: [[i32; 4]; 2],
+    /// the mobility score of each side
+    pub mobility: [Score; 2],
+}
+
+/// the squares attacked by a piece of type `piece` on `sq`
+#[inline(always)]
+pub fn piece_attacks(piece: Piece, sq: Square, occupied: BitBoard) -> BitBoard {
+    match piece {
+        Piece::Pawn => EMPTY,
+        Piece::Knight => get_knight_moves(sq),
+        Piece::Bishop => get_bishop_moves(sq, occupied),
+        Piece::Rook => get_rook_moves(sq, occupied),
+        Piece::Queen => get_bishop_moves(sq, occupied) | get_rook_moves(sq, occupied),
+        Piece::King => king_attacks(sq),
+    }
+}
+
+impl AttackMap {
+    /// go over every piece of `board` once, collecting its attacks, with
+    /// `mobility` as the bonus for every square a piece reaches beyond
+    /// [`MOBILITY_BASE`] (see [`MOBILITY`])
+    pub fn new(board: &Board, mobility: &[Score; 4]) -> Self {
+        let mut map = Self::default();
+        let occupied = *board.combined();
+        let pawns = board.pieces(Piece::Pawn);
+
+        for color in [Color::White, Color::Black] {
+            let us = color.to_index();
+            let pawn_attacks = pawn_attacks(pawns & board.color_combined(color), color);
+            let king = board.king_square(color);
+            map.by_piece[us][Piece::Pawn.to_index()] = pawn_attacks;
+            map.by_piece[us][Piece::King.to_index()] = king_attacks(king);
+            map.king_zone[us] = king_attacks(king) | BitBoard::from_square(king);
+            map.double[us] = pawn_attacks & king_attacks(king);
+            map.all[us] = pawn_attacks | king_attacks(king);
+        }
+
+        for color in [Color::White, Color::Black] {
+            let (us, them) = (color.to_index(), (!color).to_index());
+            // squares a piece can go to without being taken by a pawn
+            let available =
+                !(*board.color_combined(color) | map.by_piece[them][Piece::Pawn.to_index()]);
+
+            for (idx, piece) in MOBILE_PIECES.into_iter().enumerate() {
+                for sq in board.pieces(piece) & board.color_combined(color) {
+                    let attacks = piece_attacks(piece, sq, occupied);
+                    map.by_piece[us][piece.to_index()] |= attacks;
+                    map.double[us] |= map.all[us] & attacks;
+                    map.all[us] |= attacks;
+
+                    let reach = (attacks & available).popcnt() as i32;
+                    map.mobility[us] += mobility[idx] * (reach - MOBILITY_BASE[idx]);
+
+                    let hits = (attacks & map.king_zone[them]).popcnt() as i32;
+                    if hits > 0 {
+                        map.zone_attackers[us] += 1;
+                        map.zone_hits[us][idx] += hits;
+                    }
+                }
+            }
+        }
+
+        map
+    }
+
+    /// the squares attacked by `color`'s pieces of type `piece`
+    #[inline(always)]
+    pub fn attacked_by(&self, color: Color, piece: Piece) -> BitBoard {
+        self.by_piece[color.to_index()][piece.to_index()]
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/attacks.rs"]
+mod tests;
@@ -0,0 +1,149 @@
+//! a cache of static evaluations, shared by every search thread
+//!
+//! many positions are evaluated more than once (the transposition table only
+//! keeps the static evaluation of its own entries). each entry is a single
+//! `u64`, the top 48 bits of the position's [`Board::get_hash`] and the 16
+//! bits of its value, so it can be read and written atomically, without
+//! locks. the low bits of the hash are the index of the entry.
+//!
+//! [`Board::get_hash`]: chess::Board::get_hash
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::ops::Sub;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use crate::setup::values::Value;
+use crate::transposition_table::stats::StripedCounters;
+
+/// the default size of the cache, in bytes
+pub const DEFAULT_EVAL_CACHE_SIZE: usize = 1024 * 1024;
+
+/// the smallest number of entries, so that the index covers every bit of the
+/// hash that the key doesn't
+const MIN_ENTRIES: usize = 1 << 16;
+
+/// the bits of an entry that hold the value
+const VALUE_MASK: u64 = 0xffff;
+
+/// where [`CacheStats::probes`] is counted in [`EvalCache`]
+const PROBES: usize = 0;
+/// where [`CacheStats::hits`] is counted
+const HITS: usize = 1;
+
+/// a snapshot of the counters of an [`EvalCache`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    /// how many evaluations were looked up
+    pub probes: u64,
+    /// how many of them were found
+    pub hits: u64,
+}
+
+impl CacheStats {
+    /// the fraction of probes that were hits, in per mille
+    pub fn hit_rate(&self) -> u64 {
+        (self.hits * 1000)
+            .checked_div(self.probes)
+            .unwrap_or_default()
+    }
+}
+
+/// the counters accumulated between two snapshots
+impl Sub for CacheStats {
+    type Output = CacheStats;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        CacheStats {
+            probes: self.probes.saturating_sub(rhs.probes),
+            hits: self.hits.saturating_sub(rhs.hits),
+        }
+    }
+}
+
+impl Display for CacheStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "probes {} hits {} hitrate {}",
+            self.probes,
+            self.hits,
+            self.hit_rate()
+        )
+    }
+}
+
+/// the evaluation cache. an empty entry is 0, so a position whose key and
+/// value are both 0 is never found, which is harmless.
+#[derive(Debug)]
+pub struct EvalCache {
+    /// the packed entries, a power of two of them
+    entries: Box<[AtomicU64]>,
+    /// the probes and the hits, see [`CacheStats`]
+    counters: StripedCounters<2>,
+}
+
+impl EvalCache {
+    /// a cache of at most `bytes` bytes, but at least 512kB
+    pub fn new(bytes: usize) -> Self {
+        let count = (bytes / size_of::<AtomicU64>() + 1)
+            .next_power_of_two()
+            .checked_shr(1)
+            .unwrap_or_default()
+            .max(MIN_ENTRIES);
+        Self {
+            entries: (0..count).map(|_| AtomicU64::new(0)).collect(),
+            counters: StripedCounters::default(),
+        }
+    }
+
+    /// the size of the cache, in bytes
+    pub fn size(&self) -> usize {
+        self.entries.len() * size_of::<AtomicU64>()
+    }
+
+    /// the entry of `hash`
+    #[inline(always)]
+    fn entry(&self, hash: u64) -> &AtomicU64 {
+        &self.entries[hash as usize & (self.entries.len() - 1)]
+    }
+
+    /// the cached evaluation of the position with `hash`, if there is one
+    #[inline(always)]
+    pub fn probe(&self, hash: u64) -> Option<Value> {
+        self.counters.add(PROBES);
+        let entry = self.entry(hash).load(Ordering::Relaxed);
+        if entry != 0 && entry & !VALUE_MASK == hash & !VALUE_MASK {
+            self.counters.add(HITS);
+            Some(Value((entry & VALUE_MASK) as u16 as i16))
+        } else {
+            None
+        }
+    }
+
+    /// cache the evaluation of the position with `hash`, replacing whatever
+    /// was in its entry
+    #[inline(always)]
+    pub fn store(&self, hash: u64, value: Value) {
+        let entry = hash & !VALUE_MASK | value.0 as u16 as u64;
+        self.entry(hash).store(entry, Ordering::Relaxed);
+    }
+
+    /// forget every evaluation, and reset the counters
+    pub fn clear(&self) {
+        for entry in &self.entries {
+            entry.store(0, Ordering::Relaxed);
+        }
+        self.counters.reset();
+    }
+
+    /// read the counters
+    pub fn stats(&self) -> CacheStats {
+        let [probes, hits] = self.counters.load();
+        CacheStats { probes, hits }
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/cache.rs"]
+mod tests;
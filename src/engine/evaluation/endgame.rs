@@ -0,0 +1,230 @@
+//! endgame knowledge: material configurations that are known draws, scale
+//! factors for the ones that are hard to win, and mop-up of the ones that are
+//! won
+use chess::BitBoard;
+use chess::Board;
+use chess::Color;
+use chess::EMPTY;
+use chess::File;
+use chess::Piece;
+use chess::Rank;
+use chess::Square;
+use chess::get_file;
+
+use crate::evaluation::material::MIDGAME_VALUES;
+use crate::evaluation::pawns::distance;
+use crate::evaluation::score::Score;
+use crate::setup::values::Value;
+
+/// the scale factor that leaves the endgame score as it is
+pub const SCALE_NORMAL: i32 = 64;
+/// The weights of the endgame scale factors and of mop-up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EndgameParams {
+    /// the scale factor of an ending with only opposite-coloured bishops and
+    /// pawns
+    pub opposite_bishops: i32,
+    /// the scale factor when the stronger side has no pawns, and is ahead by
+    /// no more than a minor piece
+    pub no_pawns: i32,
+    /// mop-up bonus for every step the losing king is away from the centre
+    pub mop_up_edge: i32,
+    /// mop-up bonus for every step the losing king is closer to a corner that
+    /// the bishop covers, with bishop and knight against a bare king
+    pub mop_up_corner: i32,
+    /// mop-up bonus for every step the winning king is closer to the losing
+    /// one
+    pub mop_up_kings: i32,
+}
+
+impl EndgameParams {
+    /// the default weights
+    pub const DEFAULT: Self = Self {
+        opposite_bishops: 22,
+        no_pawns: 8,
+        mop_up_edge: 20,
+        mop_up_corner: 40,
+        mop_up_kings: 10,
+    };
+}
+
+impl Default for EndgameParams {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// the light squares of the board
+const LIGHT_SQUARES: BitBoard = BitBoard(0x55aa_55aa_55aa_55aa);
+
+/// the non-pawn material of `color`, in middlegame values
+fn non_pawn_material(board: &Board, color: Color) -> i32 {
+    let ours = board.color_combined(color);
+    [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen]
+        .into_iter()
+        .map(|piece| {
+            (board.pieces(piece) & ours).popcnt() as i32 * MIDGAME_VALUES[piece.to_index()].0 as i32
+        })
+        .sum()
+}
+
+/// whether all the given squares are of the same colour
+#[inline(always)]
+fn same_coloured(squares: BitBoard) -> bool {
+    squares & LIGHT_SQUARES == EMPTY || squares & !LIGHT_SQUARES == EMPTY
+}
+
+/// whether neither side can ever checkmate: bare kings, a single minor piece,
+/// or only bishops that all stand on squares of the same colour
+pub fn insufficient_material(board: &Board) -> bool {
+    let heavy = board.pieces(Piece::Pawn) | board.pieces(Piece::Rook) | board.pieces(Piece::Queen);
+    if heavy != EMPTY {
+        return false;
+    }
+    let knights = *board.pieces(Piece::Knight);
+    let bishops = *board.pieces(Piece::Bishop);
+    match (knights.popcnt(), bishops.popcnt()) {
+        (0, 0) | (1, 0) | (0, 1) => true,
+        (0, _) => same_coloured(bishops),
+        _ => false,
+    }
+}
+
+/// whether the position is a draw with correct defence, even though mate may
+/// still be possible: [`insufficient_material`], two knights against a bare
+/// king, and a bishop with rook pawns that can't be promoted
+pub fn is_known_draw(board: &Board) -> bool {
+    if board.pieces(Piece::Rook) | board.pieces(Piece::Queen) != EMPTY {
+        return false;
+    }
+    if insufficient_material(board) {
+        return true;
+    }
+    [Color::White, Color::Black]
+        .into_iter()
+        .any(|color| two_knights(board, color) || wrong_bishop(board, color))
+}
+
+/// `color` has two knights and nothing else, against a bare king
+fn two_knights(board: &Board, color: Color) -> bool {
+    let ours = board.color_combined(color);
+    board.pieces(Piece::Pawn) == &EMPTY
+        && board.color_combined(!color).popcnt() == 1
+        && (board.pieces(Piece::Knight) & ours).popcnt() == 2
+        && ours.popcnt() == 3
+}
+
+/// `color` has a bishop and pawns on a single rook file against a bare king,
+/// the bishop doesn't cover the promotion square, and the defending king
+/// stands in front of the pawns
+fn wrong_bishop(board: &Board, color: Color) -> bool {
+    let ours = *board.color_combined(color);
+    let pawns = board.pieces(Piece::Pawn) & ours;
+    let bishops = board.pieces(Piece::Bishop) & ours;
+    if board.color_combined(!color).popcnt() != 1
+        || pawns == EMPTY
+        || bishops.popcnt() != 1
+        || (pawns | bishops).popcnt() + 1 != ours.popcnt()
+    {
+        return false;
+    }
+    let file = if pawns & get_file(File::A) == pawns {
+        File::A
+    } else if pawns & get_file(File::H) == pawns {
+        File::H
+    } else {
+        return false;
+    };
+    let rank = match color {
+        Color::White => Rank::Eighth,
+        Color::Black => Rank::First,
+    };
+    let corner = Square::make_square(rank, file);
+    !same_coloured(bishops | BitBoard::from_square(corner))
+        && distance(board.king_square(!color), corner) <= 1
+}
+
+/// the score of a won ending against a bare king, from the point of view of
+/// `stm`, if there is one. offset by [`Value::KNOWN_WIN`], plus bonuses for
+/// driving the losing king to the edge (or the right corner, with bishop and
+/// knight) and bringing the winning king closer, so that the search makes
+/// progress towards mate.
+pub fn mop_up(board: &Board, stm: Color, params: &EndgameParams) -> Option<Value> {
+    let strong = [Color::White, Color::Black]
+        .into_iter()
+        .find(|color| board.color_combined(!*color).popcnt() == 1)?;
+    let ours = *board.color_combined(strong);
+    let count = |piece| (board.pieces(piece) & ours).popcnt();
+    let bishops = board.pieces(Piece::Bishop) & ours;
+    let bishop_and_knight = count(Piece::Bishop) >= 1 && count(Piece::Knight) >= 1;
+    let heavy = count(Piece::Queen) + count(Piece::Rook) > 0;
+    if !heavy && !bishop_and_knight && (count(Piece::Bishop) < 2 || same_coloured(bishops)) {
+        return None;
+    }
+
+    let winner = board.king_square(strong);
+    let loser = board.king_square(!strong);
+    let mut value = Value::KNOWN_WIN.0 as i32 + non_pawn_material(board, strong);
+    value += params.mop_up_kings * (7 - distance(winner, loser));
+
+    if !heavy && count(Piece::Bishop) == 1 {
+        // only the corners of the bishop's colour can be mated in
+        let corners = if bishops & LIGHT_SQUARES == EMPTY {
+            [Square::A1, Square::H8]
+        } else {
+            [Square::H1, Square::A8]
+        };
+        let to_corner = corners.map(|corner| distance(loser, corner));
+        value += params.mop_up_corner * (7 - to_corner[0].min(to_corner[1]));
+    } else {
+        let centre = |idx: usize| (3 - idx as i32).max(idx as i32 - 4);
+        let from_centre = centre(loser.get_file().to_index()) + centre(loser.get_rank().to_index());
+        value += params.mop_up_edge * from_centre;
+    }
+
+    let value = Value(value as i16);
+    Some(if strong == stm { value } else { -value })
+}
+
+/// how much of the endgame score the side that is ahead in `score` (from the
+/// point of view of `stm`) keeps, out of [`SCALE_NORMAL`]
+pub fn scale_factor(board: &Board, stm: Color, score: Score, params: &EndgameParams) -> i32 {
+    let strong = if score.eg() >= 0 { stm } else { !stm };
+    let pawns = board.pieces(Piece::Pawn);
+    let strong_npm = non_pawn_material(board, strong);
+    let weak_npm = non_pawn_material(board, !strong);
+
+    if pawns & board.color_combined(strong) == EMPTY
+        && strong_npm - weak_npm <= MIDGAME_VALUES[Piece::Bishop.to_index()].0 as i32
+    {
+        return params.no_pawns;
+    }
+
+    let bishops = *board.pieces(Piece::Bishop);
+    let bishop_value = MIDGAME_VALUES[Piece::Bishop.to_index()].0 as i32;
+    if strong_npm == bishop_value
+        && weak_npm == bishop_value
+        && bishops.popcnt() == 2
+        && !same_coloured(bishops)
+    {
+        return params.opposite_bishops;
+    }
+
+    SCALE_NORMAL
+}
+
+/// `score` with its endgame lane scaled by `factor` out of [`SCALE_NORMAL`]
+pub fn scale(score: Score, factor: i32) -> Score {
+    if factor == SCALE_NORMAL {
+        return score;
+    }
+    Score::phased(
+        score.early(),
+        score.mg(),
+        score.eg() * factor / SCALE_NORMAL,
+    )
+}
+
+#[cfg(test)]
+#[path = "tests/endgame.rs"]
+mod tests;
@@ -0,0 +1,120 @@
+//! the evaluation as the search sees it, so that the search can be run with
+//! other evaluations without being edited.
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use chess::Board;
+use chess::ChessMove;
+use chess::EMPTY;
+
+use crate::evaluation::cache::CacheStats;
+use crate::evaluation::cache::EvalCache;
+use crate::evaluation::endgame;
+use crate::evaluation::material::material_count_for_side;
+use crate::evaluation::search_eval;
+use crate::opts::Opts;
+use crate::position::Position;
+use crate::setup::values::Value;
+
+/// an evaluation the search ([`negamax`], and the [`Engine`]) can use
+///
+/// [`negamax`]: crate::search::negamax::negamax
+/// [`Engine`]: crate::Engine
+pub trait Evaluator: Debug + Clone + Send + Sync + 'static {
+    /// the static evaluation of `pos`, for the side to move. `out_of_moves`
+    /// is set when the side to move has no legal moves, ie in checkmate or
+    /// stalemate.
+    fn evaluate(&self, pos: &Position, out_of_moves: bool, opts: &Opts) -> Value;
+
+    /// the position after `mv` is played on `pos`. this is where incremental
+    /// state is updated; by default, the [`Accumulator`]s of the position
+    /// (see [`Position::make_move`]).
+    ///
+    /// [`Accumulator`]: crate::evaluation::accumulator::Accumulator
+    #[inline(always)]
+    fn make_move(&self, pos: &Position, mv: ChessMove) -> Position {
+        pos.make_move(mv)
+    }
+
+    /// whether neither side can win `board`, so that it doesn't need to be
+    /// searched. by default, see [`endgame::is_known_draw`].
+    #[inline(always)]
+    fn is_known_draw(&self, board: &Board) -> bool {
+        endgame::is_known_draw(board)
+    }
+
+    /// forget any evaluations that were cached, after the evaluation changed
+    /// (eg its parameters)
+    fn clear(&self) {}
+
+    /// the counters of the evaluation cache, if there is one
+    fn cache_stats(&self) -> Option<CacheStats> {
+        None
+    }
+}
+
+/// the evaluation of the engine: the network or the handcrafted evaluation
+/// (see [`search_eval`]), optionally cached in an [`EvalCache`]
+#[derive(Debug, Clone, Default)]
+pub struct DefaultEvaluator {
+    /// where evaluations are cached, if anywhere
+    pub cache: Option<Arc<EvalCache>>,
+}
+
+impl DefaultEvaluator {
+    /// the evaluation, cached in an [`EvalCache`] of `bytes` bytes, or not
+    /// cached if `bytes` is 0
+    pub fn with_cache(bytes: usize) -> Self {
+        Self {
+            cache: (bytes > 0).then(|| Arc::new(EvalCache::new(bytes))),
+        }
+    }
+}
+
+impl Evaluator for DefaultEvaluator {
+    #[inline(always)]
+    fn evaluate(&self, pos: &Position, out_of_moves: bool, opts: &Opts) -> Value {
+        let Some(cache) = self.cache.as_ref().filter(|_| !out_of_moves) else {
+            return search_eval(pos, out_of_moves, opts);
+        };
+        let hash = pos.chessboard.get_hash();
+        cache.probe(hash).unwrap_or_else(|| {
+            let value = search_eval(pos, out_of_moves, opts);
+            cache.store(hash, value);
+            value
+        })
+    }
+
+    fn clear(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+    }
+
+    fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(|cache| cache.stats())
+    }
+}
+
+/// counts the material, and nothing else. a predictable evaluation for tests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MaterialEvaluator;
+
+impl Evaluator for MaterialEvaluator {
+    fn evaluate(&self, pos: &Position, out_of_moves: bool, _: &Opts) -> Value {
+        let board = &pos.chessboard;
+        if out_of_moves {
+            return if *board.checkers() == EMPTY {
+                Value::DRAW
+            } else {
+                -Value::MATE
+            };
+        }
+        let stm = board.side_to_move();
+        material_count_for_side(board, stm) - material_count_for_side(board, !stm)
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/evaluator.rs"]
+mod tests;
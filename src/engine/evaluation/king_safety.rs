@@ -0,0 +1,123 @@
+//! king safety: pawn shield and storm, open files, and attacks on the king
+use chess::BitBoard;
+use chess::Board;
+use chess::Color;
+use chess::EMPTY;
+use chess::File;
+use chess::Piece;
+use chess::Square;
+use chess::get_file;
+
+use crate::evaluation::attacks::AttackMap;
+use crate::evaluation::pawns::relative_rank;
+use crate::evaluation::score::Score;
+
+/// The weights of the king safety terms. every term is a middlegame value,
+/// king safety is tapered to zero in the endgame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KingSafetyParams {
+    /// bonus for the closest pawn of the king's side in front of it, on the
+    /// king's and adjacent files, if it is 1 or 2 ranks ahead. the last value
+    /// is for a file without such a pawn
+    pub shield: [i32; 3],
+    /// penalty for the closest enemy pawn in front of the king, on the king's
+    /// and adjacent files, by how many ranks ahead of the king it is (1 to 4)
+    pub storm: [i32; 4],
+    /// penalty for a file next to the king without pawns of the king's side
+    pub semi_open_file: i32,
+    /// penalty for a file next to the king without any pawns
+    pub open_file: i32,
+    /// the danger from every king zone square attacked by a knight, bishop,
+    /// rook and queen (see [`MOBILE_PIECES`])
+    ///
+    /// [`MOBILE_PIECES`]: crate::evaluation::attacks::MOBILE_PIECES
+    pub attack_weight: [i32; 4],
+    /// the squared danger is divided by this
+    pub danger_scale: i32,
+    /// the largest penalty for attacks on the king zone
+    pub max_danger: i32,
+}
+
+impl KingSafetyParams {
+    /// the default weights
+    pub const DEFAULT: Self = Self {
+        shield: [30, 15, -20],
+        storm: [10, 40, 25, 10],
+        semi_open_file: 12,
+        open_file: 25,
+        attack_weight: [8, 8, 12, 20],
+        danger_scale: 64,
+        max_danger: 500,
+    };
+}
+
+impl Default for KingSafetyParams {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// how many ranks in front of `king` is the closest of `pawns`, if any
+fn closest_ahead(pawns: BitBoard, king: Square, color: Color) -> Option<i32> {
+    let king_rank = relative_rank(king, color) as i32;
+    pawns
+        .map(|sq| relative_rank(sq, color) as i32 - king_rank)
+        .filter(|d| *d > 0)
+        .min()
+}
+
+/// the safety of `color`'s king, higher is safer
+pub fn king_safety(
+    board: &Board,
+    color: Color,
+    attacks: &AttackMap,
+    params: &KingSafetyParams,
+) -> Score {
+    let king = board.king_square(color);
+    let pawns = board.pieces(Piece::Pawn);
+    let ours = pawns & board.color_combined(color);
+    let theirs = pawns & board.color_combined(!color);
+
+    let mut mg = 0;
+
+    // pawn shield, storm and open files, on the king's and adjacent files
+    let king_file = king.get_file().to_index();
+    for file in king_file.saturating_sub(1)..=(king_file + 1).min(7) {
+        let file = get_file(File::from_index(file));
+        let shield = closest_ahead(ours & file, king, color);
+        mg += match shield {
+            Some(d @ 1..=2) => params.shield[d as usize - 1],
+            _ => params.shield[2],
+        };
+        if let Some(d @ 1..=4) = closest_ahead(theirs & file, king, color) {
+            mg -= params.storm[d as usize - 1];
+        }
+        if ours & file == EMPTY {
+            mg -= if theirs & file == EMPTY {
+                params.open_file
+            } else {
+                params.semi_open_file
+            };
+        }
+    }
+
+    // attacks on the king zone, collected by the [`AttackMap`]
+    let them = (!color).to_index();
+    let attackers = attacks.zone_attackers[them];
+    let danger = params
+        .attack_weight
+        .iter()
+        .zip(attacks.zone_hits[them])
+        .map(|(weight, hits)| weight * hits)
+        .sum::<i32>();
+    // a lone attacker is rarely dangerous
+    if attackers >= 2 {
+        mg -= (danger * danger / params.danger_scale.max(1)).min(params.max_danger);
+    }
+
+    Score::new(mg, 0)
+}
+
+#[cfg(test)]
+#[path = "tests/king_safety.rs"]
+mod tests;
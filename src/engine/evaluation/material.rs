@@ -6,6 +6,9 @@ use chess::Color;
 use chess::Piece;
 
 use crate::evaluation::Interp;
+use crate::evaluation::PHASE_SCALE;
+use crate::evaluation::params::Weights;
+use crate::evaluation::score::Score;
 use crate::setup::values::Value;
 
 /// The score threshold at which the game is considered to be in the middlegame.
@@ -51,20 +54,22 @@ pub const ENDGAME_VALUES: [Value; 5] = [
     Value(1050), // Queen
 ];
 
-/// Calculate the material value for a given side, interpolated for the
-/// different phases of the game
-pub fn material(board: &Board, side: Color, interp: Interp) -> Value {
-    let mut value = Value::ZERO;
+/// [`INITIAL_VALUES`], [`MIDGAME_VALUES`] and [`ENDGAME_VALUES`] packed
+/// together, one [`Score`] per piece type. the evaluation reads them through
+/// [`Weights`], which may have been loaded from a file instead.
+pub const PIECE_SCORES: [Score; 5] = Weights::DEFAULT.piece_scores;
+
+/// Calculate the material value for a given side, for every phase of the game
+pub fn material(weights: &Weights, board: &Board, side: Color) -> Score {
+    let mut score = Score::ZERO;
 
     let side_board = board.color_combined(side);
     for (idx, piece) in MAT_PIECE_TYPES.iter().enumerate() {
         let count = board.pieces(*piece).bitand(side_board).popcnt();
-        value += (INITIAL_VALUES[idx] * Value::from(count)) * interp.0;
-        value += (MIDGAME_VALUES[idx] * Value::from(count)) * interp.1;
-        value += (ENDGAME_VALUES[idx] * Value::from(count)) * interp.2;
+        score += weights.piece_scores[idx] * count as i32;
     }
 
-    value
+    score
 }
 
 /// Calculate the material value for a given side.
@@ -93,35 +98,33 @@ pub fn total_material(pos: &Board) -> Value {
     value
 }
 
-/// Interpolate between [`GAME_PHASE`] values based on the number of pieces on
-/// the board.
+/// Interpolate between the phases of the game based on the pieces on the
+/// board.
 ///
-/// Returns a multiplier for each (EARLY_VALUES, MG_VALUES, EG_VALUES).
+/// Returns the weights of the early game, middlegame and endgame, out of
+/// [`PHASE_SCALE`]. the early game fades out between [`MIDDLEGAME_SCORE`] and
+/// the midpoint of the two thresholds, where the endgame starts fading in,
+/// until it is complete at [`ENDGAME_SCORE`].
 pub fn interpolate(pos: &Board) -> Interp {
-    let total_value = total_material(pos).0 as f64;
-    // Calculate the midpoint between middlegame and endgame thresholds
-    let midpoint = (MIDDLEGAME_SCORE + ENDGAME_SCORE).0 as f64 / 2.0;
-
-    // Early Game Coefficient
-    let early_game_coeff = if total_value >= MIDDLEGAME_SCORE.0 as f64 {
-        1.0
-    } else if total_value <= midpoint {
-        0.0
-    } else {
-        (total_value - midpoint) / (MIDDLEGAME_SCORE.0 as f64 - midpoint)
-    };
-
-    // Endgame Coefficient
-    let endgame_coeff = if total_value <= ENDGAME_SCORE.0 as f64 {
-        1.0
-    } else if total_value >= midpoint {
-        0.0
-    } else {
-        (midpoint - total_value) / (midpoint - ENDGAME_SCORE.0 as f64)
-    };
-
-    // Middle Game Coefficient
-    let middle_game_coeff = 1.0 - early_game_coeff - endgame_coeff;
-
-    (early_game_coeff, middle_game_coeff, endgame_coeff)
+    interpolate_material(total_material(pos))
+}
+
+/// same as [`interpolate`], for a known [`total_material`]
+pub fn interpolate_material(total_material: Value) -> Interp {
+    // everything is doubled, so that the midpoint is an integer
+    let total = 2 * total_material.0 as i32;
+    let midpoint = (MIDDLEGAME_SCORE + ENDGAME_SCORE).0 as i32;
+    // the thresholds are equally far from the midpoint
+    let width = (MIDDLEGAME_SCORE - ENDGAME_SCORE).0 as i32;
+
+    // positive in the early game, negative in the endgame
+    let weight = ((total - midpoint) * PHASE_SCALE / width).clamp(-PHASE_SCALE, PHASE_SCALE);
+    let early = weight.max(0);
+    let end = (-weight).max(0);
+
+    Interp {
+        early,
+        middle: PHASE_SCALE - early - end,
+        end,
+    }
 }
@@ -1,17 +1,40 @@
 //! The Evaluation module of this chess engine
 
+pub mod accumulator;
+pub mod activity;
+pub mod attacks;
 pub mod bitboards;
+pub mod cache;
+pub mod endgame;
+pub mod evaluator;
+pub mod king_safety;
 pub mod material;
+#[cfg(feature = "nnue")]
+pub mod nnue;
+pub mod params;
+pub mod pawns;
+pub mod score;
+pub mod symmetry;
+pub mod threats;
+pub mod trace;
 
 use std::ops::Not;
 
 use anyhow::Result;
 use chess::Board;
+use chess::Color;
 use chess::EMPTY;
 
-use crate::evaluation::material::interpolate;
-use crate::evaluation::material::material;
-use crate::evaluation::position::piece_position_benefit_for_side;
+use crate::evaluation::activity::piece_activity;
+use crate::evaluation::attacks::AttackMap;
+use crate::evaluation::endgame::is_known_draw;
+use crate::evaluation::endgame::mop_up;
+use crate::evaluation::endgame::scale;
+use crate::evaluation::endgame::scale_factor;
+use crate::evaluation::king_safety::king_safety;
+use crate::evaluation::params::with_weights;
+use crate::evaluation::pawns::evaluate_pawns;
+use crate::evaluation::threats::threats;
 use crate::optlog;
 use crate::opts::Opts;
 use crate::opts::setopts;
@@ -19,11 +42,24 @@ use crate::position::Position;
 use crate::search::moveordering::MoveOrdering;
 use crate::setup::values::Value;
 
-/// a bonus given to the side-to-move for having a tempo advantage
+/// a bonus given to the side-to-move for having a tempo advantage, by default
+/// (see [`params`])
 pub const TEMPO: Value = Value(25);
 
-/// an interpolation between beginning, middle, and endgame
-pub type Interp = (f64, f64, f64);
+/// the sum of the weights of an [`Interp`]
+pub const PHASE_SCALE: i32 = 256;
+
+/// an interpolation between beginning, middle, and endgame, as integer weights
+/// that add up to [`PHASE_SCALE`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interp {
+    /// the weight of the early game
+    pub early: i32,
+    /// the weight of the middlegame
+    pub middle: i32,
+    /// the weight of the endgame
+    pub end: i32,
+}
 
 /// same as [`evaluate`] but first sets the global [`Opts`]
 pub fn eval(board: &Board, moves: &MoveOrdering, opts: Opts) -> Result<Value> {
@@ -34,6 +70,24 @@ pub fn eval(board: &Board, moves: &MoveOrdering, opts: Opts) -> Result<Value> {
     Ok(evaluate(&position, moves.is_empty()))
 }
 
+/// the evaluation the search uses: the network when it is enabled with
+/// [`Opts::use_nnue`] and one is loaded (see [`nnue`]), the handcrafted
+/// [`evaluate`] otherwise. positions without moves are always left to
+/// [`evaluate`].
+#[inline(always)]
+pub fn search_eval(pos: &Position, out_of_moves: bool, opts: &Opts) -> Value {
+    #[cfg(feature = "nnue")]
+    if opts.use_nnue
+        && !out_of_moves
+        && let Some(value) = nnue::evaluate(pos)
+    {
+        return value;
+    }
+    #[cfg(not(feature = "nnue"))]
+    let _ = opts;
+    evaluate(pos, out_of_moves)
+}
+
 /// the main evaluation function. returns a value representing the score of the
 /// position from the point of view of the player whos turn it is to move
 // pub fn evaluate(pos: &Position, moves: &MoveOrdering) -> Value {
@@ -46,13 +100,8 @@ pub fn evaluate(pos: &Position, out_of_moves: bool) -> Value {
     if out_of_moves {
         return if pos.chessboard.checkers().eq(&EMPTY) {
             optlog!(eval;debug;"eval stalemate");
-            // in stalemate, give a slightly negative score to the side that's winning to
-            // encourage it to keep playing instead
-            value -= material(&pos.chessboard, stm, (0.0, 0.0, 1.0));
-            value += material(&pos.chessboard, stm.not(), (0.0, 0.0, 1.0));
-            // value is small as to not significantly impact the search tree
-            value.0 = value.0.checked_shr(3).unwrap_or_default();
-            value
+            // the search scores stalemates itself, with the engine's contempt
+            Value::DRAW
         } else {
             // Side to move is checkmated
             optlog!(eval;debug;"eval checkmate");
@@ -60,21 +109,55 @@ pub fn evaluate(pos: &Position, out_of_moves: bool) -> Value {
         };
     }
 
-    let interp = interpolate(&pos.chessboard);
+    // nobody can win, whatever the rest of the evaluation says
+    if is_known_draw(&pos.chessboard) {
+        optlog!(eval;debug;"eval known draw");
+        return Value::DRAW;
+    }
+
+    // the weights that can be loaded at runtime, see [`params`]
+    with_weights(|w| {
+        // against a bare king, drive it to the edge and mate it
+        if let Some(value) = mop_up(&pos.chessboard, stm, &w.endgame) {
+            optlog!(eval;debug;"eval mop-up");
+            return value;
+        }
+
+        let interp = pos.accumulator.interp();
+
+        // material and positional benefits from the side to move's perspective,
+        // kept up to date by [`Position::make_move`]
+        let mut score = pos.accumulator.score(stm);
+
+        // pawn structure, cached in the pawn hash table
+        let pawns = evaluate_pawns(&pos.chessboard, &w.pawns);
+        score += if stm == Color::White { pawns } else { -pawns };
+
+        // every other term shares the attacks of a single pass over the pieces
+        let attacks = AttackMap::new(&pos.chessboard, &w.mobility);
+        score += attacks.mobility[stm.to_index()] - attacks.mobility[stm.not().to_index()];
+        score += piece_activity(&pos.chessboard, stm, &attacks, &w.activity);
+        score -= piece_activity(&pos.chessboard, stm.not(), &attacks, &w.activity);
+
+        // pieces under attack, and attacks and checks that are about to happen
+        score += threats(&pos.chessboard, stm, &attacks, &w.threats);
+        score -= threats(&pos.chessboard, stm.not(), &attacks, &w.threats);
+
+        // king safety, which fades out in the endgame
+        score += king_safety(&pos.chessboard, stm, &attacks, &w.king_safety);
+        score -= king_safety(&pos.chessboard, stm.not(), &attacks, &w.king_safety);
 
-    // Calculate material and positional benefits from the side to move's
-    // perspective
-    value += material(&pos.chessboard, stm, interp);
-    value -= material(&pos.chessboard, stm.not(), interp);
+        // drawish endings keep only part of the endgame score
+        let score = scale(score, scale_factor(&pos.chessboard, stm, score, &w.endgame));
 
-    value += piece_position_benefit_for_side(&pos.chessboard, stm, interp);
-    value -= piece_position_benefit_for_side(&pos.chessboard, stm.not(), interp);
+        value += score.taper(interp);
 
-    // Add tempo bonus
-    value += TEMPO; // Always positive for the side to move
+        // Add tempo bonus
+        value += w.tempo; // Always positive for the side to move
 
-    // Return the evaluation score
-    value
+        // Return the evaluation score
+        value
+    })
 }
 
 mod position;
@@ -0,0 +1,188 @@
+//! the first layer of the network, updated incrementally with every move
+use chess::Board;
+use chess::ChessMove;
+use chess::Color;
+use chess::File;
+use chess::Piece;
+use chess::Square;
+
+use crate::evaluation::nnue::HIDDEN;
+use crate::evaluation::nnue::KingKey;
+use crate::evaluation::nnue::Network;
+use crate::evaluation::nnue::features;
+use crate::evaluation::nnue::with_network;
+
+/// the sum of the feature weights of the active features of each side's
+/// perspective, indexed by [`Color::to_index`]. like the handcrafted
+/// [`Accumulator`], [`Position::make_move`] only adds and subtracts the
+/// features of the pieces that moved.
+///
+/// [`Accumulator`]: crate::evaluation::accumulator::Accumulator
+/// [`Position::make_move`]: crate::position::Position::make_move
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[repr(C, align(64))]
+pub struct NnueAccumulator {
+    /// the accumulator of each perspective
+    pub values: [[i16; HIDDEN]; 2],
+    /// the generation of the network these values are for (see
+    /// [`with_network`]), 0 if none was loaded
+    pub generation: usize,
+}
+
+impl Default for NnueAccumulator {
+    fn default() -> Self {
+        Self {
+            values: [[0; HIDDEN]; 2],
+            generation: 0,
+        }
+    }
+}
+
+/// a change to the board: a piece of a colour appearing on, or disappearing
+/// from, a square
+type Change = (Piece, Color, Square);
+
+/// the (at most two) changes of each kind a move makes
+type Changes = [Option<Change>; 2];
+
+impl NnueAccumulator {
+    /// compute the accumulator from scratch, with the network in use
+    pub fn new(board: &Board) -> Self {
+        with_network(|net| match net {
+            Some((net, generation)) => Self::with_network(net, generation, board),
+            None => Self::default(),
+        })
+    }
+
+    /// compute the accumulator from scratch, with the given network
+    pub fn with_network(net: &Network, generation: usize, board: &Board) -> Self {
+        let mut acc = Self {
+            values: [net.feature_bias; 2],
+            generation,
+        };
+        for perspective in [Color::White, Color::Black] {
+            acc.refresh(net, board, perspective);
+        }
+        acc
+    }
+
+    /// recompute the accumulator of one perspective
+    fn refresh(&mut self, net: &Network, board: &Board, perspective: Color) {
+        let values = &mut self.values[perspective.to_index()];
+        *values = net.feature_bias;
+        for feature in features(board, perspective) {
+            add(values, &net.feature_weights[feature]);
+        }
+    }
+
+    /// the accumulator after `mv` is played on `before`, which leads to
+    /// `after`. `mv` must be legal.
+    pub fn make_move(&self, before: &Board, after: &Board, mv: ChessMove) -> Self {
+        with_network(|net| match net {
+            None => Self::default(),
+            Some((net, generation)) if generation != self.generation => {
+                Self::with_network(net, generation, after)
+            }
+            Some((net, _)) => self.make_move_with(net, before, after, mv),
+        })
+    }
+
+    /// same as [`NnueAccumulator::make_move`], with the given network, which
+    /// must be the one this accumulator was computed with
+    pub fn make_move_with(
+        &self,
+        net: &Network,
+        before: &Board,
+        after: &Board,
+        mv: ChessMove,
+    ) -> Self {
+        let mut next = self.clone();
+        let Some((removed, added)) = changes(before, mv) else {
+            return next;
+        };
+
+        for perspective in [Color::White, Color::Black] {
+            let (old, new) = (
+                KingKey::of(before, perspective),
+                KingKey::of(after, perspective),
+            );
+            if old != new {
+                // the king changed bucket or side, every feature moves
+                next.refresh(net, after, perspective);
+                continue;
+            }
+            let values = &mut next.values[perspective.to_index()];
+            for (piece, color, sq) in removed.iter().flatten() {
+                sub(
+                    values,
+                    &net.feature_weights[new.feature(perspective, *piece, *color, *sq)],
+                );
+            }
+            for (piece, color, sq) in added.iter().flatten() {
+                add(
+                    values,
+                    &net.feature_weights[new.feature(perspective, *piece, *color, *sq)],
+                );
+            }
+        }
+
+        next
+    }
+}
+
+/// the pieces removed from, and added to, `board` by `mv`
+#[inline(always)]
+fn changes(board: &Board, mv: ChessMove) -> Option<(Changes, Changes)> {
+    let (src, dest) = (mv.get_source(), mv.get_dest());
+    let us = board.side_to_move();
+    let piece = board.piece_on(src)?;
+    let files_moved = src
+        .get_file()
+        .to_index()
+        .abs_diff(dest.get_file().to_index());
+
+    let mut removed = [Some((piece, us, src)), None];
+    let mut added = [Some((mv.get_promotion().unwrap_or(piece), us, dest)), None];
+
+    if let Some(captured) = board.piece_on(dest) {
+        removed[1] = Some((captured, !us, dest));
+    } else if piece == Piece::Pawn && files_moved != 0 {
+        // en passant, the captured pawn is next to the one that moved
+        removed[1] = Some((
+            Piece::Pawn,
+            !us,
+            Square::make_square(src.get_rank(), dest.get_file()),
+        ));
+    } else if piece == Piece::King && files_moved == 2 {
+        // castling, move the rook to the other side of the king
+        let (from, to) = if dest.get_file() == File::G {
+            (File::H, File::F)
+        } else {
+            (File::A, File::D)
+        };
+        removed[1] = Some((Piece::Rook, us, Square::make_square(src.get_rank(), from)));
+        added[1] = Some((Piece::Rook, us, Square::make_square(src.get_rank(), to)));
+    }
+
+    Some((removed, added))
+}
+
+/// add the weights of a feature to an accumulator
+#[inline(always)]
+fn add(values: &mut [i16; HIDDEN], weights: &[i16; HIDDEN]) {
+    for (v, w) in values.iter_mut().zip(weights) {
+        *v = v.wrapping_add(*w);
+    }
+}
+
+/// subtract the weights of a feature from an accumulator
+#[inline(always)]
+fn sub(values: &mut [i16; HIDDEN], weights: &[i16; HIDDEN]) {
+    for (v, w) in values.iter_mut().zip(weights) {
+        *v = v.wrapping_sub(*w);
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/accumulator.rs"]
+mod tests;
@@ -0,0 +1,356 @@
+//! an efficiently updatable neural network, as an alternative to the
+//! handcrafted [`evaluate`].
+//!
+//! the network is a small perspective network:
+//! `HalfKA(INPUTS) -> HIDDEN x 2 -> 1`.
+//!
+//! every piece on the board (kings included) is an input feature, seen from
+//! each side's perspective. the board is flipped for black, and mirrored so
+//! that the side's king is always on the queenside, and the feature is
+//! shifted by a bucket for the position of that king ([`KING_BUCKETS`]).
+//! the first layer is summed in an [`NnueAccumulator`], which
+//! [`Position::make_move`] updates with the few features that changed. the
+//! side to move's half goes first into the output layer.
+//!
+//! all the weights are quantised `i16`s: the accumulator is scaled by
+//! [`QA`], and the output weights by [`QB`]. the activation is a clipped
+//! ReLU.
+//!
+//! the network is loaded from a file at runtime, with the `EvalFile` UCI
+//! option, and used instead of the handcrafted evaluation when `Use NNUE` is
+//! on. see [`Network::from_bytes`] for the format.
+//!
+//! [`evaluate`]: crate::evaluation::evaluate
+//! [`Position::make_move`]: crate::position::Position::make_move
+pub mod accumulator;
+
+use std::cell::RefCell;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use chess::ALL_PIECES;
+use chess::Board;
+use chess::Color;
+use chess::Piece;
+use chess::Square;
+
+use crate::evaluation::nnue::accumulator::NnueAccumulator;
+use crate::position::Position;
+use crate::setup::values::Value;
+
+/// the number of king buckets per perspective
+pub const KING_BUCKETS: usize = 4;
+
+/// the input features of a single king bucket: a piece type of either colour
+/// on any square
+pub const FEATURES_PER_BUCKET: usize = 2 * 6 * 64;
+
+/// the number of input features of each perspective
+pub const INPUTS: usize = KING_BUCKETS * FEATURES_PER_BUCKET;
+
+/// the size of the accumulator of each perspective
+pub const HIDDEN: usize = 128;
+
+/// the quantisation of the first layer
+pub const QA: i32 = 255;
+
+/// the quantisation of the output weights. the output bias is scaled by
+/// `QA * QB`, like the sum it is added to.
+pub const QB: i32 = 64;
+
+/// the output of the network is scaled to centipawns by this
+pub const SCALE: i32 = 400;
+
+/// the bucket of each square the king can be on, from its own side's
+/// perspective, once mirrored to the queenside (so files e-h are unused)
+#[rustfmt::skip]
+const BUCKET_LAYOUT: [usize; 64] = [
+    0, 0, 1, 1, 1, 1, 0, 0,
+    2, 2, 2, 2, 2, 2, 2, 2,
+    3, 3, 3, 3, 3, 3, 3, 3,
+    3, 3, 3, 3, 3, 3, 3, 3,
+    3, 3, 3, 3, 3, 3, 3, 3,
+    3, 3, 3, 3, 3, 3, 3, 3,
+    3, 3, 3, 3, 3, 3, 3, 3,
+    3, 3, 3, 3, 3, 3, 3, 3,
+];
+
+/// the first bytes of a network file
+pub const MAGIC: &[u8; 8] = b"SANDYNN1";
+
+/// how the features of a perspective are laid out, which only depends on
+/// where its king is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KingKey {
+    /// flip the board vertically (for black)
+    flip: usize,
+    /// mirror the board horizontally (when the king is on the kingside)
+    mirror: usize,
+    /// the first feature of the bucket of the king
+    offset: usize,
+}
+
+impl KingKey {
+    /// the layout for `perspective`, whose king is on `king`
+    #[inline(always)]
+    pub fn new(perspective: Color, king: Square) -> Self {
+        let flip = if perspective == Color::White { 0 } else { 56 };
+        let mirror = if king.get_file().to_index() >= 4 {
+            7
+        } else {
+            0
+        };
+        let bucket = BUCKET_LAYOUT[king.to_index() ^ flip ^ mirror];
+        Self {
+            flip,
+            mirror,
+            offset: bucket * FEATURES_PER_BUCKET,
+        }
+    }
+
+    /// the layout for `perspective` on `board`
+    #[inline(always)]
+    pub fn of(board: &Board, perspective: Color) -> Self {
+        Self::new(perspective, board.king_square(perspective))
+    }
+
+    /// the input feature of a `piece` of `color` on `sq`
+    #[inline(always)]
+    pub fn feature(&self, perspective: Color, piece: Piece, color: Color, sq: Square) -> usize {
+        let side = (color != perspective) as usize;
+        self.offset + (side * 6 + piece.to_index()) * 64 + (sq.to_index() ^ self.flip ^ self.mirror)
+    }
+}
+
+/// the active input features of `board` from the `perspective` of one side.
+/// this is shared with the trainer, so that both agree on the inputs.
+pub fn features(board: &Board, perspective: Color) -> impl Iterator<Item = usize> + '_ {
+    let key = KingKey::of(board, perspective);
+    ALL_PIECES.into_iter().flat_map(move |piece| {
+        [Color::White, Color::Black]
+            .into_iter()
+            .flat_map(move |color| {
+                (*board.pieces(piece) & *board.color_combined(color))
+                    .map(move |sq| key.feature(perspective, piece, color, sq))
+            })
+    })
+}
+
+/// the weights of a network
+#[derive(Clone, PartialEq, Eq)]
+pub struct Network {
+    /// the weights of every input feature into the accumulator
+    pub feature_weights: Box<[[i16; HIDDEN]]>,
+    /// the initial value of the accumulator
+    pub feature_bias: [i16; HIDDEN],
+    /// the output weights of the side to move's accumulator, then the
+    /// opponent's
+    pub output_weights: [[i16; HIDDEN]; 2],
+    /// the bias of the output
+    pub output_bias: i16,
+}
+
+impl std::fmt::Debug for Network {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Network(HalfKA({INPUTS}) -> {HIDDEN}x2 -> 1, output bias {})",
+            self.output_bias
+        )
+    }
+}
+
+/// the clipped ReLU of a quantised accumulator value
+#[inline(always)]
+fn crelu(x: i16) -> i32 {
+    (x as i32).clamp(0, QA)
+}
+
+impl Network {
+    /// a network with every weight set to zero
+    pub fn zeroed() -> Self {
+        Self {
+            feature_weights: vec![[0; HIDDEN]; INPUTS].into_boxed_slice(),
+            feature_bias: [0; HIDDEN],
+            output_weights: [[0; HIDDEN]; 2],
+            output_bias: 0,
+        }
+    }
+
+    /// the evaluation of an accumulator, in centipawns, from the point of view
+    /// of `stm`
+    #[inline(always)]
+    pub fn forward(&self, acc: &NnueAccumulator, stm: Color) -> i32 {
+        let halves = [&acc.values[stm.to_index()], &acc.values[(!stm).to_index()]];
+        let mut sum = 0;
+        for (half, weights) in halves.into_iter().zip(&self.output_weights) {
+            sum += half
+                .iter()
+                .zip(weights)
+                .map(|(x, w)| crelu(*x) * *w as i32)
+                .sum::<i32>();
+        }
+        ((sum + self.output_bias as i32) as i64 * SCALE as i64 / (QA * QB) as i64) as i32
+    }
+
+    /// the evaluation of `pos` from the point of view of the side to move,
+    /// recomputing its accumulator if it isn't for this network
+    pub fn evaluate(&self, pos: &Position, generation: usize) -> Value {
+        let stm = pos.chessboard.side_to_move();
+        let raw = if pos.nnue.generation == generation {
+            self.forward(&pos.nnue, stm)
+        } else {
+            self.forward(
+                &NnueAccumulator::with_network(self, generation, &pos.chessboard),
+                stm,
+            )
+        };
+        // a network can't see mates
+        let bound = Value::KNOWN_WIN.0 as i32 - 1;
+        Value(raw.clamp(-bound, bound) as i16)
+    }
+
+    /// parse a network file:
+    /// * [`MAGIC`]
+    /// * the number of king buckets and the size of the accumulator, as
+    ///   little-endian `u32`s, which must match [`KING_BUCKETS`] and [`HIDDEN`]
+    /// * then little-endian `i16`s: the feature weights (`INPUTS x HIDDEN`,
+    ///   feature by feature), the feature bias (`HIDDEN`), the output weights
+    ///   (`2 x HIDDEN`, side to move first) and the output bias
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let Some(rest) = bytes.strip_prefix(MAGIC) else {
+            bail!("not a network file");
+        };
+        if rest.len() < 8 {
+            bail!("truncated network header");
+        }
+        let header = |i: usize| u32::from_le_bytes(rest[4 * i..4 * i + 4].try_into().unwrap());
+        let (buckets, hidden) = (header(0) as usize, header(1) as usize);
+        if (buckets, hidden) != (KING_BUCKETS, HIDDEN) {
+            bail!(
+                "the network has {buckets} king buckets and {hidden} neurons, the engine expects {KING_BUCKETS} and {HIDDEN}"
+            );
+        }
+
+        let values = &rest[8..];
+        let expected = 2 * ((INPUTS + 1 + 2) * HIDDEN + 1);
+        if values.len() != expected {
+            bail!(
+                "the network has {} bytes of weights, expected {expected}",
+                values.len()
+            );
+        }
+        let mut values = values
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]));
+        let mut next = || values.next().unwrap_or_default();
+
+        let mut net = Self::zeroed();
+        for row in net.feature_weights.iter_mut() {
+            row.iter_mut().for_each(|w| *w = next());
+        }
+        net.feature_bias.iter_mut().for_each(|w| *w = next());
+        for row in net.output_weights.iter_mut() {
+            row.iter_mut().for_each(|w| *w = next());
+        }
+        net.output_bias = next();
+        Ok(net)
+    }
+
+    /// the inverse of [`Network::from_bytes`]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend((KING_BUCKETS as u32).to_le_bytes());
+        bytes.extend((HIDDEN as u32).to_le_bytes());
+        let weights = self
+            .feature_weights
+            .iter()
+            .flatten()
+            .chain(&self.feature_bias)
+            .chain(self.output_weights.iter().flatten())
+            .chain(std::iter::once(&self.output_bias));
+        bytes.extend(weights.flat_map(|w| w.to_le_bytes()));
+        bytes
+    }
+
+    /// read a network from a file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).with_context(|| format!("could not read {}", path.display()))?;
+        Self::from_bytes(&bytes).with_context(|| format!("invalid network {}", path.display()))
+    }
+
+    /// write the network to a file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, self.to_bytes())
+            .with_context(|| format!("could not write {}", path.display()))
+    }
+}
+
+/// the network in use, if one has been loaded
+static CURRENT: RwLock<Option<Arc<Network>>> = RwLock::new(None);
+
+/// incremented every time the network is replaced. accumulators remember the
+/// generation they were computed for, so that stale ones are never used.
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// every thread keeps a handle to the network, refreshed when the
+    /// [`GENERATION`] changes, so that reading it needs no locks
+    static LOCAL: RefCell<(usize, Option<Arc<Network>>)> = const { RefCell::new((0, None)) };
+}
+
+/// the network in use, if any
+pub fn network() -> Option<Arc<Network>> {
+    CURRENT
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// replace the network, for every thread. `None` unloads it.
+///
+/// the accumulators of existing [`Position`]s are recomputed when they are
+/// next evaluated or moved from, but it's cheaper to recreate them (see
+/// [`Engine::set_network`]).
+///
+/// [`Engine::set_network`]: crate::Engine::set_network
+pub fn set_network(net: Option<Network>) {
+    *CURRENT.write().unwrap_or_else(PoisonError::into_inner) = net.map(Arc::new);
+    GENERATION.fetch_add(1, Ordering::Release);
+}
+
+/// run `f` with the network in use and its generation, if one is loaded
+#[inline(always)]
+pub fn with_network<R>(f: impl FnOnce(Option<(&Network, usize)>) -> R) -> R {
+    let generation = GENERATION.load(Ordering::Acquire);
+    if generation == 0 {
+        return f(None);
+    }
+    LOCAL.with(|local| {
+        if local.borrow().0 != generation {
+            *local.borrow_mut() = (generation, network());
+        }
+        f(local.borrow().1.as_deref().map(|net| (net, generation)))
+    })
+}
+
+/// the evaluation of `pos` by the network, from the point of view of the
+/// side to move, or `None` if no network is loaded
+#[inline(always)]
+pub fn evaluate(pos: &Position) -> Option<Value> {
+    with_network(|net| net.map(|(net, generation)| net.evaluate(pos, generation)))
+}
+
+#[cfg(test)]
+#[path = "tests/mod.rs"]
+mod tests;
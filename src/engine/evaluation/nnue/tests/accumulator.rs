@@ -0,0 +1,76 @@
+use std::str::FromStr;
+
+use chess::Board;
+use chess::ChessMove;
+use chess::Color;
+use chess::MoveGen;
+
+use crate::evaluation::nnue::accumulator::NnueAccumulator;
+use crate::evaluation::nnue::tests::random_network;
+
+/// play `moves` from `fen`, checking the incremental accumulator against a
+/// fresh one after every move
+fn check_line(fen: &str, moves: &[&str]) {
+    let net = random_network(1);
+    let mut board = Board::from_str(fen).unwrap();
+    let mut acc = NnueAccumulator::with_network(&net, 1, &board);
+    for mv in moves {
+        let mv = ChessMove::from_str(mv).unwrap();
+        assert!(board.legal(mv), "{mv} on {board}");
+        let next = board.make_move_new(mv);
+        acc = acc.make_move_with(&net, &board, &next, mv);
+        board = next;
+        assert_eq!(acc, NnueAccumulator::with_network(&net, 1, &board), "{mv}");
+    }
+}
+
+#[test]
+fn special_moves() {
+    // castling on both sides, which also changes the king's bucket
+    check_line(
+        "r3k2r/pppq1ppp/2n2n2/3pp3/3PP3/2N2N2/PPPQ1PPP/R3K2R w KQkq - 0 1",
+        &["e1g1", "e8c8", "g1h1", "c8b8"],
+    );
+    // en passant
+    check_line(
+        "4k3/8/8/8/3p4/8/4P3/4K3 w - - 0 1",
+        &["e2e4", "d4e3", "e1e2", "e8d7"],
+    );
+    // promotions, with and without captures
+    check_line(
+        "1n2k3/P7/8/8/8/8/6p1/4K2R w K - 0 1",
+        &["a7b8n", "g2h1q", "e1d2", "h1h8"],
+    );
+    // the king crossing to the other wing
+    check_line(
+        "4k3/8/8/8/8/8/8/3K4 w - - 0 1",
+        &["d1e1", "e8d8", "e1e2", "d8c7"],
+    );
+}
+
+#[test]
+fn every_move() {
+    let net = random_network(2);
+    for fen in [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+    ] {
+        let board = Board::from_str(fen).unwrap();
+        let acc = NnueAccumulator::with_network(&net, 5, &board);
+        for mv in MoveGen::new_legal(&board) {
+            let next = board.make_move_new(mv);
+            assert_eq!(
+                acc.make_move_with(&net, &board, &next, mv),
+                NnueAccumulator::with_network(&net, 5, &next),
+                "{mv} on {fen}"
+            );
+        }
+        // the perspectives are independent of the side to move
+        let acc = NnueAccumulator::with_network(&net, 5, &board);
+        assert_ne!(
+            acc.values[Color::White.to_index()],
+            acc.values[Color::Black.to_index()]
+        );
+    }
+}
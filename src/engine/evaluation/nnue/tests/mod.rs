@@ -0,0 +1,155 @@
+use std::str::FromStr;
+
+use chess::Board;
+use chess::Color;
+use chess::Piece;
+use chess::Square;
+
+use crate::evaluation::nnue::HIDDEN;
+use crate::evaluation::nnue::INPUTS;
+use crate::evaluation::nnue::KingKey;
+use crate::evaluation::nnue::MAGIC;
+use crate::evaluation::nnue::Network;
+use crate::evaluation::nnue::QA;
+use crate::evaluation::nnue::QB;
+use crate::evaluation::nnue::SCALE;
+use crate::evaluation::nnue::accumulator::NnueAccumulator;
+use crate::evaluation::nnue::features;
+use crate::util::Rng;
+
+/// a network with small pseudo-random weights
+pub fn random_network(seed: u64) -> Network {
+    let mut rng = Rng::new(seed);
+    let mut next = move || (rng.next_u64() >> 58) as i16 - 32;
+    let mut net = Network::zeroed();
+    for w in net.feature_weights.iter_mut().flatten() {
+        *w = next();
+    }
+    for w in net.feature_bias.iter_mut() {
+        *w = next() + 64;
+    }
+    for w in net.output_weights.iter_mut().flatten() {
+        *w = next();
+    }
+    net.output_bias = next();
+    net
+}
+
+/// the features of a perspective, in order
+fn sorted(board: &Board, perspective: Color) -> Vec<usize> {
+    let mut features: Vec<usize> = features(board, perspective).collect();
+    features.sort();
+    features
+}
+
+#[test]
+fn one_feature_per_piece() {
+    let board =
+        Board::from_str("r3k2r/ppp2ppp/2n5/3qp3/8/2N2N2/PPP2PPP/R2QK2R w KQkq - 0 1").unwrap();
+    for perspective in [Color::White, Color::Black] {
+        let features = sorted(&board, perspective);
+        assert_eq!(features.len(), board.combined().popcnt() as usize);
+        assert!(features.iter().all(|f| *f < INPUTS));
+        features.windows(2).for_each(|w| assert_ne!(w[0], w[1]));
+    }
+}
+
+#[test]
+fn perspectives_are_symmetric() {
+    // the same position with the colours swapped
+    let board =
+        Board::from_str("r3k2r/ppp2ppp/2n5/3qp3/8/2N2N2/PPP2PPP/R2QK2R w KQkq - 0 1").unwrap();
+    let swapped =
+        Board::from_str("r2qk2r/ppp2ppp/2n2n2/8/3QP3/2N5/PPP2PPP/R3K2R b KQkq - 0 1").unwrap();
+    assert_eq!(sorted(&board, Color::White), sorted(&swapped, Color::Black));
+    assert_eq!(sorted(&board, Color::Black), sorted(&swapped, Color::White));
+    assert_eq!(
+        sorted(&Board::default(), Color::White),
+        sorted(&Board::default(), Color::Black)
+    );
+}
+
+#[test]
+fn kingside_is_mirrored() {
+    let queenside = Board::from_str("4k3/8/8/8/8/8/PP6/1KR5 w - - 0 1").unwrap();
+    let kingside = Board::from_str("3k4/8/8/8/8/8/6PP/5RK1 w - - 0 1").unwrap();
+    assert_eq!(
+        sorted(&queenside, Color::White),
+        sorted(&kingside, Color::White)
+    );
+    assert_eq!(
+        sorted(&queenside, Color::Black),
+        sorted(&kingside, Color::Black)
+    );
+
+    // only its own king decides how a perspective is mirrored
+    let kingside = Board::from_str("4k3/8/8/8/8/8/6PP/5RK1 w - - 0 1").unwrap();
+    assert_ne!(
+        sorted(&queenside, Color::Black),
+        sorted(&kingside, Color::Black)
+    );
+}
+
+#[test]
+fn king_buckets() {
+    let key = |sq| KingKey::new(Color::White, sq);
+    let bucket = |sq| key(sq).offset;
+    // the same buckets, mirrored
+    assert_eq!(bucket(Square::G1), bucket(Square::B1));
+    assert_eq!(bucket(Square::E1), bucket(Square::D1));
+    assert_ne!(key(Square::E1), key(Square::D1));
+    assert_eq!(key(Square::G1), key(Square::H1));
+    // and different ones
+    assert_ne!(bucket(Square::G1), bucket(Square::E1));
+    assert_ne!(bucket(Square::G1), bucket(Square::G2));
+    assert_eq!(bucket(Square::G3), bucket(Square::B8));
+    // black's back rank is its first
+    assert_eq!(
+        KingKey::new(Color::Black, Square::G8).offset,
+        bucket(Square::G1)
+    );
+
+    let (near, far) = (key(Square::G1), key(Square::G3));
+    let feature = |key: KingKey| key.feature(Color::White, Piece::Pawn, Color::White, Square::E4);
+    assert_ne!(feature(near), feature(far));
+}
+
+#[test]
+fn output_scaling() {
+    let mut net = Network::zeroed();
+    let acc = NnueAccumulator::with_network(&net, 1, &Board::default());
+    assert_eq!(net.forward(&acc, Color::White), 0);
+
+    net.output_bias = (QA * QB) as i16;
+    assert_eq!(net.forward(&acc, Color::White), SCALE);
+
+    // a single neuron, fully active for the side to move
+    net.output_bias = 0;
+    net.feature_bias[0] = QA as i16;
+    net.output_weights[0][0] = QB as i16;
+    let acc = NnueAccumulator::with_network(&net, 1, &Board::default());
+    assert_eq!(net.forward(&acc, Color::White), SCALE);
+    assert_eq!(net.forward(&acc, Color::Black), SCALE);
+    net.output_weights[1][0] = -QB as i16;
+    assert_eq!(net.forward(&acc, Color::White), 0);
+    // clipped at QA
+    net.feature_bias[0] = 2 * QA as i16;
+    net.output_weights[1][0] = 0;
+    let acc = NnueAccumulator::with_network(&net, 1, &Board::default());
+    assert_eq!(net.forward(&acc, Color::White), SCALE);
+}
+
+#[test]
+fn file_round_trip() {
+    let net = random_network(3);
+    let bytes = net.to_bytes();
+    assert_eq!(bytes.len(), 8 + 8 + 2 * ((INPUTS + 3) * HIDDEN + 1));
+    assert_eq!(Network::from_bytes(&bytes).unwrap(), net);
+
+    assert!(Network::from_bytes(&bytes[..bytes.len() - 2]).is_err());
+    assert!(Network::from_bytes(&bytes[1..]).is_err());
+    assert!(Network::from_bytes(MAGIC).is_err());
+    let mut wrong = bytes.clone();
+    wrong[12] += 1;
+    assert!(Network::from_bytes(&wrong).is_err());
+}
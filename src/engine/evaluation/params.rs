@@ -0,0 +1,561 @@
+//! the weights of the evaluation. the compile-time constants are the defaults,
+//! and can be replaced at runtime by a parameter file, so that tuned sets can
+//! be tried without recompiling.
+//!
+//! the file format is a subset of TOML: `key = value` lines, where a value is
+//! an integer or an array of integers (which may span several lines), grouped
+//! into `[sections]`, with `#` comments. a [`Score`] is written as its
+//! middlegame and endgame values, and an array of them as those pairs one
+//! after the other. keys that are left out keep their default values. see
+//! [`EvalParams`]'s [`Display`] implementation for a complete file.
+use std::cell::RefCell;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+
+use crate::evaluation::TEMPO;
+use crate::evaluation::activity::ActivityParams;
+use crate::evaluation::attacks::MOBILITY;
+use crate::evaluation::bitboards::EG_PESTO_TABLE;
+use crate::evaluation::bitboards::MG_PESTO_TABLE;
+use crate::evaluation::bitboards::PestoTable;
+use crate::evaluation::endgame::EndgameParams;
+use crate::evaluation::king_safety::KingSafetyParams;
+use crate::evaluation::material::ENDGAME_VALUES;
+use crate::evaluation::material::INITIAL_VALUES;
+use crate::evaluation::material::MIDGAME_VALUES;
+use crate::evaluation::pawns::PawnParams;
+use crate::evaluation::score::Score;
+use crate::evaluation::threats::ThreatParams;
+use crate::setup::values::Value;
+
+/// the names of the piece types, as used for the piece-square table keys
+const PIECE_NAMES: [&str; 6] = ["pawn", "knight", "bishop", "rook", "queen", "king"];
+
+/// The tunable weights of the evaluation. the piece values and piece-square
+/// tables are plain integers, piece values indexed by [`Piece::to_index`]
+/// (without the king), and the tables laid out like [`PestoTable`]s, from
+/// white's point of view with the 8th rank first.
+///
+/// [`Piece::to_index`]: chess::Piece::to_index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EvalParams {
+    /// the value of each piece type in the early game
+    pub initial_values: [i32; 5],
+    /// the value of each piece type in the middlegame
+    pub midgame_values: [i32; 5],
+    /// the value of each piece type in the endgame
+    pub endgame_values: [i32; 5],
+    /// the bonus for the side to move
+    pub tempo: i32,
+    /// the middlegame (and early game) piece-square tables
+    pub mg_psqt: [PestoTable; 6],
+    /// the endgame piece-square tables
+    pub eg_psqt: [PestoTable; 6],
+    /// the weights of the king safety term
+    pub king_safety: KingSafetyParams,
+    /// the weights of the pawn structure terms
+    pub pawns: PawnParams,
+    /// the bonus for every square a piece can move to, for the pieces in
+    /// [`MOBILE_PIECES`] (see [`AttackMap::mobility`])
+    ///
+    /// [`MOBILE_PIECES`]: crate::evaluation::attacks::MOBILE_PIECES
+    /// [`AttackMap::mobility`]: crate::evaluation::attacks::AttackMap::mobility
+    pub mobility: [Score; 4],
+    /// the weights of the piece activity terms
+    pub activity: ActivityParams,
+    /// the weights of the threat terms
+    pub threats: ThreatParams,
+    /// the endgame scale factors and mop-up weights
+    pub endgame: EndgameParams,
+}
+
+/// The [`EvalParams`] packed into [`Score`]s, the way the evaluation uses them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Weights {
+    /// the material of each piece type, by [`Piece::to_index`]
+    ///
+    /// [`Piece::to_index`]: chess::Piece::to_index
+    pub piece_scores: [Score; 5],
+    /// the piece-square tables, indexed by piece type and then [`sq_pi`]
+    ///
+    /// [`sq_pi`]: crate::evaluation::position::sq_pi
+    pub psqt: [[[Score; 8]; 8]; 6],
+    /// the bonus for the side to move
+    pub tempo: Value,
+    /// the weights of the king safety term
+    pub king_safety: KingSafetyParams,
+    /// the weights of the pawn structure terms
+    pub pawns: PawnParams,
+    /// the bonus for every square a piece can move to
+    pub mobility: [Score; 4],
+    /// the weights of the piece activity terms
+    pub activity: ActivityParams,
+    /// the weights of the threat terms
+    pub threats: ThreatParams,
+    /// the endgame scale factors and mop-up weights
+    pub endgame: EndgameParams,
+}
+
+/// convert an array of [`Value`]s to integers
+const fn values(values: [Value; 5]) -> [i32; 5] {
+    let mut out = [0; 5];
+    let mut idx = 0;
+    while idx < 5 {
+        out[idx] = values[idx].0 as i32;
+        idx += 1;
+    }
+    out
+}
+
+impl EvalParams {
+    /// the weights that are compiled into the engine
+    pub const DEFAULT: Self = Self {
+        initial_values: values(INITIAL_VALUES),
+        midgame_values: values(MIDGAME_VALUES),
+        endgame_values: values(ENDGAME_VALUES),
+        tempo: TEMPO.0 as i32,
+        mg_psqt: MG_PESTO_TABLE,
+        eg_psqt: EG_PESTO_TABLE,
+        king_safety: KingSafetyParams::DEFAULT,
+        pawns: PawnParams::DEFAULT,
+        mobility: MOBILITY,
+        activity: ActivityParams::DEFAULT,
+        threats: ThreatParams::DEFAULT,
+        endgame: EndgameParams::DEFAULT,
+    };
+
+    /// pack the parameters for the evaluation
+    pub const fn weights(&self) -> Weights {
+        let mut piece_scores = [Score::ZERO; 5];
+        let mut idx = 0;
+        while idx < piece_scores.len() {
+            piece_scores[idx] = Score::phased(
+                self.initial_values[idx],
+                self.midgame_values[idx],
+                self.endgame_values[idx],
+            );
+            idx += 1;
+        }
+
+        let mut psqt = [[[Score::ZERO; 8]; 8]; 6];
+        let mut idx = 0;
+        while idx < psqt.len() {
+            let mut row = 0;
+            while row < 8 {
+                let mut col = 0;
+                while col < 8 {
+                    psqt[idx][row][col] = Score::new(
+                        self.mg_psqt[idx][row][col] as i32,
+                        self.eg_psqt[idx][row][col] as i32,
+                    );
+                    col += 1;
+                }
+                row += 1;
+            }
+            idx += 1;
+        }
+
+        Weights {
+            piece_scores,
+            psqt,
+            tempo: Value(self.tempo as i16),
+            king_safety: self.king_safety,
+            pawns: self.pawns,
+            mobility: self.mobility,
+            activity: self.activity,
+            threats: self.threats,
+            endgame: self.endgame,
+        }
+    }
+
+    /// read the parameters from a file. see the [module docs](self) for the
+    /// format.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("could not read parameters from {}", path.display()))?;
+        text.parse()
+            .with_context(|| format!("invalid parameter file {}", path.display()))
+    }
+
+    /// set the parameter `key` (including its section, eg
+    /// `king_safety.shield`) to `values`
+    fn set(&mut self, key: &str, values: &[i64]) -> Result<()> {
+        if let Some(table) = key.strip_prefix("mg_psqt.") {
+            return set_table(&mut self.mg_psqt, table, key, values);
+        }
+        if let Some(table) = key.strip_prefix("eg_psqt.") {
+            return set_table(&mut self.eg_psqt, table, key, values);
+        }
+        let ks = &mut self.king_safety;
+        let (pawns, activity) = (&mut self.pawns, &mut self.activity);
+        let (threats, endgame) = (&mut self.threats, &mut self.endgame);
+        match key {
+            "initial_values" => set_array(&mut self.initial_values, key, values),
+            "midgame_values" => set_array(&mut self.midgame_values, key, values),
+            "endgame_values" => set_array(&mut self.endgame_values, key, values),
+            "tempo" => set_array(std::slice::from_mut(&mut self.tempo), key, values),
+            "king_safety.shield" => set_array(&mut ks.shield, key, values),
+            "king_safety.storm" => set_array(&mut ks.storm, key, values),
+            "king_safety.semi_open_file" => {
+                set_array(std::slice::from_mut(&mut ks.semi_open_file), key, values)
+            }
+            "king_safety.open_file" => {
+                set_array(std::slice::from_mut(&mut ks.open_file), key, values)
+            }
+            "king_safety.attack_weight" => set_array(&mut ks.attack_weight, key, values),
+            "king_safety.danger_scale" => {
+                set_array(std::slice::from_mut(&mut ks.danger_scale), key, values)
+            }
+            "king_safety.max_danger" => {
+                set_array(std::slice::from_mut(&mut ks.max_danger), key, values)
+            }
+            "pawns.doubled" => set_scores(std::slice::from_mut(&mut pawns.doubled), key, values),
+            "pawns.isolated" => set_scores(std::slice::from_mut(&mut pawns.isolated), key, values),
+            "pawns.backward" => set_scores(std::slice::from_mut(&mut pawns.backward), key, values),
+            "pawns.connected" => set_scores(&mut pawns.connected, key, values),
+            "pawns.passed" => set_scores(&mut pawns.passed, key, values),
+            "pawns.passed_enemy_king" => set_array(
+                std::slice::from_mut(&mut pawns.passed_enemy_king),
+                key,
+                values,
+            ),
+            "pawns.passed_own_king" => set_array(
+                std::slice::from_mut(&mut pawns.passed_own_king),
+                key,
+                values,
+            ),
+            "mobility" => set_scores(&mut self.mobility, key, values),
+            "activity.bishop_pair" => {
+                set_scores(std::slice::from_mut(&mut activity.bishop_pair), key, values)
+            }
+            "activity.rook_open_file" => set_scores(
+                std::slice::from_mut(&mut activity.rook_open_file),
+                key,
+                values,
+            ),
+            "activity.rook_semi_open_file" => set_scores(
+                std::slice::from_mut(&mut activity.rook_semi_open_file),
+                key,
+                values,
+            ),
+            "activity.rook_on_seventh" => set_scores(
+                std::slice::from_mut(&mut activity.rook_on_seventh),
+                key,
+                values,
+            ),
+            "activity.knight_outpost" => set_scores(
+                std::slice::from_mut(&mut activity.knight_outpost),
+                key,
+                values,
+            ),
+            "threats.by_safe_pawn" => {
+                set_scores(std::slice::from_mut(&mut threats.by_safe_pawn), key, values)
+            }
+            "threats.by_minor" => set_scores(&mut threats.by_minor, key, values),
+            "threats.by_rook" => set_scores(&mut threats.by_rook, key, values),
+            "threats.hanging" => {
+                set_scores(std::slice::from_mut(&mut threats.hanging), key, values)
+            }
+            "threats.pawn_push" => {
+                set_scores(std::slice::from_mut(&mut threats.pawn_push), key, values)
+            }
+            "threats.safe_check" => set_scores(&mut threats.safe_check, key, values),
+            "endgame.opposite_bishops" => set_array(
+                std::slice::from_mut(&mut endgame.opposite_bishops),
+                key,
+                values,
+            ),
+            "endgame.no_pawns" => {
+                set_array(std::slice::from_mut(&mut endgame.no_pawns), key, values)
+            }
+            "endgame.mop_up_edge" => {
+                set_array(std::slice::from_mut(&mut endgame.mop_up_edge), key, values)
+            }
+            "endgame.mop_up_corner" => set_array(
+                std::slice::from_mut(&mut endgame.mop_up_corner),
+                key,
+                values,
+            ),
+            "endgame.mop_up_kings" => {
+                set_array(std::slice::from_mut(&mut endgame.mop_up_kings), key, values)
+            }
+            unknown => bail!("unknown parameter {unknown:?}"),
+        }
+    }
+}
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// copy `values` into `target`, if there are as many of them
+fn set_array(target: &mut [i32], key: &str, values: &[i64]) -> Result<()> {
+    if values.len() != target.len() {
+        bail!(
+            "{key} needs {} values, found {}",
+            target.len(),
+            values.len()
+        );
+    }
+    for (t, v) in target.iter_mut().zip(values) {
+        *t = i32::try_from(*v).with_context(|| format!("{v} is out of range for {key}"))?;
+    }
+    Ok(())
+}
+
+/// copy `values`, as middlegame and endgame pairs, into the scores of `target`
+fn set_scores(target: &mut [Score], key: &str, values: &[i64]) -> Result<()> {
+    let mut pairs = vec![0; 2 * target.len()];
+    set_array(&mut pairs, key, values)?;
+    for (t, pair) in target.iter_mut().zip(pairs.chunks(2)) {
+        *t = Score::new(pair[0], pair[1]);
+    }
+    Ok(())
+}
+
+/// copy the 64 `values` into the piece-square table of the piece `name`
+fn set_table(tables: &mut [PestoTable; 6], name: &str, key: &str, values: &[i64]) -> Result<()> {
+    let Some(idx) = PIECE_NAMES.iter().position(|p| *p == name) else {
+        bail!("unknown piece in {key:?}");
+    };
+    if values.len() != 64 {
+        bail!("{key} needs 64 values, found {}", values.len());
+    }
+    for (sq, v) in tables[idx].as_flattened_mut().iter_mut().zip(values) {
+        *sq = i16::try_from(*v).with_context(|| format!("{v} is out of range for {key}"))?;
+    }
+    Ok(())
+}
+
+impl FromStr for EvalParams {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let mut params = Self::DEFAULT;
+        let mut section = String::new();
+        // a key whose array continues on the next lines, with what was read so far
+        let mut open: Option<(String, String)> = None;
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if let Some((key, mut value)) = open.take() {
+                value += " ";
+                value += line;
+                if line.contains(']') {
+                    params.set(&key, &parse_value(&value, number)?)?;
+                } else {
+                    open = Some((key, value));
+                }
+                continue;
+            }
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = format!("{}.", name.trim());
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                bail!("line {}: expected `key = value`", number + 1);
+            };
+            let key = format!("{section}{}", key.trim());
+            let value = value.trim().to_string();
+            if value.starts_with('[') && !value.contains(']') {
+                open = Some((key, value));
+            } else {
+                params.set(&key, &parse_value(&value, number)?)?;
+            }
+        }
+
+        if let Some((key, _)) = open {
+            bail!("unterminated array for {key}");
+        }
+        Ok(params)
+    }
+}
+
+/// parse an integer, or an array of integers
+fn parse_value(value: &str, number: usize) -> Result<Vec<i64>> {
+    value
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| {
+            v.parse()
+                .with_context(|| format!("line {}: {v:?} is not an integer", number + 1))
+        })
+        .collect()
+}
+
+/// format integers as a TOML array
+fn array(values: impl IntoIterator<Item = impl Display>) -> String {
+    let values: Vec<String> = values.into_iter().map(|v| v.to_string()).collect();
+    format!("[{}]", values.join(", "))
+}
+
+/// format scores as a TOML array of their middlegame and endgame values
+fn scores<'a>(scores: impl IntoIterator<Item = &'a Score>) -> String {
+    array(scores.into_iter().flat_map(|s| [s.mg(), s.eg()]))
+}
+
+impl Display for EvalParams {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "# piece values: pawn, knight, bishop, rook, queen")?;
+        writeln!(f, "initial_values = {}", array(self.initial_values))?;
+        writeln!(f, "midgame_values = {}", array(self.midgame_values))?;
+        writeln!(f, "endgame_values = {}", array(self.endgame_values))?;
+        writeln!(f, "tempo = {}", self.tempo)?;
+        writeln!(f, "# [mg, eg] pairs: knight, bishop, rook, queen")?;
+        writeln!(f, "mobility = {}", scores(&self.mobility))?;
+
+        for (section, tables) in [("mg_psqt", &self.mg_psqt), ("eg_psqt", &self.eg_psqt)] {
+            writeln!(
+                f,
+                "\n# from white's point of view, 8th rank first\n[{section}]"
+            )?;
+            for (name, table) in PIECE_NAMES.iter().zip(tables) {
+                writeln!(f, "{name} = [")?;
+                for row in table {
+                    writeln!(f, "    {},", array(row).trim_matches(['[', ']']))?;
+                }
+                writeln!(f, "]")?;
+            }
+        }
+
+        let ks = &self.king_safety;
+        writeln!(f, "\n[king_safety]")?;
+        writeln!(f, "shield = {}", array(ks.shield))?;
+        writeln!(f, "storm = {}", array(ks.storm))?;
+        writeln!(f, "semi_open_file = {}", ks.semi_open_file)?;
+        writeln!(f, "open_file = {}", ks.open_file)?;
+        writeln!(f, "attack_weight = {}", array(ks.attack_weight))?;
+        writeln!(f, "danger_scale = {}", ks.danger_scale)?;
+        writeln!(f, "max_danger = {}", ks.max_danger)?;
+
+        let pawns = &self.pawns;
+        writeln!(
+            f,
+            "\n# scores are [mg, eg] pairs, by relative rank\n[pawns]"
+        )?;
+        writeln!(f, "doubled = {}", scores([&pawns.doubled]))?;
+        writeln!(f, "isolated = {}", scores([&pawns.isolated]))?;
+        writeln!(f, "backward = {}", scores([&pawns.backward]))?;
+        writeln!(f, "connected = {}", scores(&pawns.connected))?;
+        writeln!(f, "passed = {}", scores(&pawns.passed))?;
+        writeln!(f, "passed_enemy_king = {}", pawns.passed_enemy_king)?;
+        writeln!(f, "passed_own_king = {}", pawns.passed_own_king)?;
+
+        let activity = &self.activity;
+        writeln!(f, "\n[activity]")?;
+        writeln!(f, "bishop_pair = {}", scores([&activity.bishop_pair]))?;
+        writeln!(f, "rook_open_file = {}", scores([&activity.rook_open_file]))?;
+        writeln!(
+            f,
+            "rook_semi_open_file = {}",
+            scores([&activity.rook_semi_open_file])
+        )?;
+        writeln!(
+            f,
+            "rook_on_seventh = {}",
+            scores([&activity.rook_on_seventh])
+        )?;
+        writeln!(f, "knight_outpost = {}", scores([&activity.knight_outpost]))?;
+
+        let threats = &self.threats;
+        writeln!(
+            f,
+            "\n# by the attacked piece: pawn, knight, bishop, rook, queen, king\n[threats]"
+        )?;
+        writeln!(f, "by_safe_pawn = {}", scores([&threats.by_safe_pawn]))?;
+        writeln!(f, "by_minor = {}", scores(&threats.by_minor))?;
+        writeln!(f, "by_rook = {}", scores(&threats.by_rook))?;
+        writeln!(f, "hanging = {}", scores([&threats.hanging]))?;
+        writeln!(f, "pawn_push = {}", scores([&threats.pawn_push]))?;
+        writeln!(f, "safe_check = {}", scores(&threats.safe_check))?;
+
+        let endgame = &self.endgame;
+        writeln!(f, "\n[endgame]")?;
+        writeln!(f, "opposite_bishops = {}", endgame.opposite_bishops)?;
+        writeln!(f, "no_pawns = {}", endgame.no_pawns)?;
+        writeln!(f, "mop_up_edge = {}", endgame.mop_up_edge)?;
+        writeln!(f, "mop_up_corner = {}", endgame.mop_up_corner)?;
+        writeln!(f, "mop_up_kings = {}", endgame.mop_up_kings)
+    }
+}
+
+impl Weights {
+    /// the weights that are compiled into the engine
+    pub const DEFAULT: Self = EvalParams::DEFAULT.weights();
+}
+
+/// the compiled-in weights, in a static so that they are never copied
+static DEFAULT_WEIGHTS: Weights = Weights::DEFAULT;
+
+/// the parameters in use, and their packed weights
+static CURRENT: RwLock<(EvalParams, Weights)> =
+    RwLock::new((EvalParams::DEFAULT, Weights::DEFAULT));
+
+/// incremented every time the parameters are replaced. while it is 0, the
+/// compiled-in weights are used directly.
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// every thread keeps a copy of the weights, refreshed when the
+    /// [`GENERATION`] changes, so that reading them needs no locks
+    static LOCAL: RefCell<(usize, Weights)> = const { RefCell::new((0, Weights::DEFAULT)) };
+}
+
+/// the parameters the evaluation is currently using
+pub fn params() -> EvalParams {
+    CURRENT.read().unwrap_or_else(PoisonError::into_inner).0
+}
+
+/// replace the parameters of the evaluation, for every thread.
+///
+/// the [`Accumulator`]s of existing [`Position`]s are not updated, they need
+/// to be recreated (see [`Engine::set_params`]).
+///
+/// [`Accumulator`]: crate::evaluation::accumulator::Accumulator
+/// [`Position`]: crate::position::Position
+/// [`Engine::set_params`]: crate::Engine::set_params
+pub fn set_params(params: EvalParams) {
+    *CURRENT.write().unwrap_or_else(PoisonError::into_inner) = (params, params.weights());
+    GENERATION.fetch_add(1, Ordering::Release);
+}
+
+/// run `f` with the weights currently in use
+#[inline(always)]
+pub fn with_weights<R>(f: impl FnOnce(&Weights) -> R) -> R {
+    let generation = GENERATION.load(Ordering::Acquire);
+    if generation == 0 {
+        return f(&DEFAULT_WEIGHTS);
+    }
+    LOCAL.with(|local| {
+        if local.borrow().0 != generation {
+            let weights = CURRENT.read().unwrap_or_else(PoisonError::into_inner).1;
+            *local.borrow_mut() = (generation, weights);
+        }
+        f(&local.borrow().1)
+    })
+}
+
+#[cfg(test)]
+#[path = "tests/params.rs"]
+mod tests;
@@ -0,0 +1,291 @@
+//! pawn structure evaluation, cached in a pawn hash table
+use std::cell::RefCell;
+
+use chess::BitBoard;
+use chess::Board;
+use chess::Color;
+use chess::EMPTY;
+use chess::File;
+use chess::Piece;
+use chess::Square;
+use chess::get_adjacent_files;
+use chess::get_file;
+use chess::get_rank;
+
+use crate::evaluation::score::Score;
+
+/// the number of entries in each thread's [`PawnTable`]
+pub const PAWN_TABLE_SIZE: usize = 1 << 13;
+
+/// The weights of the pawn structure terms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PawnParams {
+    /// penalty for a pawn with another pawn of its side in front of it
+    pub doubled: Score,
+    /// penalty for a pawn without pawns of its side on the adjacent files
+    pub isolated: Score,
+    /// penalty for a pawn behind all the pawns of its side on the adjacent
+    /// files, that can't advance without being captured by an enemy pawn
+    pub backward: Score,
+    /// bonus for a pawn defended by, or side by side with, a pawn of its side,
+    /// by relative rank
+    pub connected: [Score; 8],
+    /// bonus for a pawn without enemy pawns in front of it, on its own or the
+    /// adjacent files, by relative rank
+    pub passed: [Score; 8],
+    /// endgame bonus for every square between the enemy king and the square in
+    /// front of a passed pawn, multiplied by how advanced the pawn is
+    pub passed_enemy_king: i32,
+    /// endgame penalty for every square between the own king and the square in
+    /// front of a passed pawn, multiplied by how advanced the pawn is
+    pub passed_own_king: i32,
+}
+
+impl PawnParams {
+    /// the default weights
+    pub const DEFAULT: Self = Self {
+        doubled: Score::new(-11, -56),
+        isolated: Score::new(-5, -15),
+        backward: Score::new(-9, -24),
+        connected: [
+            Score::ZERO,
+            Score::new(7, 0),
+            Score::new(8, 2),
+            Score::new(12, 6),
+            Score::new(29, 15),
+            Score::new(48, 30),
+            Score::new(86, 60),
+            Score::ZERO,
+        ],
+        passed: [
+            Score::ZERO,
+            Score::new(10, 28),
+            Score::new(17, 33),
+            Score::new(15, 41),
+            Score::new(62, 72),
+            Score::new(168, 177),
+            Score::new(276, 260),
+            Score::ZERO,
+        ],
+        passed_enemy_king: 5,
+        passed_own_king: 2,
+    };
+}
+
+impl Default for PawnParams {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// The pawn structure of a board, as stored in the [`PawnTable`]. every array
+/// is indexed by [`Color::to_index`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PawnEntry {
+    /// the pawns of each side that this entry was computed for
+    pub pawns: [BitBoard; 2],
+    /// the structure score of each side, without the king proximity of passed
+    /// pawns (which depends on more than the pawns)
+    pub score: [Score; 2],
+    /// the passed pawns of each side
+    pub passed: [BitBoard; 2],
+}
+
+impl PawnEntry {
+    /// evaluate the structure of the given pawns from scratch
+    pub fn new(pawns: [BitBoard; 2], params: &PawnParams) -> Self {
+        let (white, white_passed) = side_structure(pawns, Color::White, params);
+        let (black, black_passed) = side_structure(pawns, Color::Black, params);
+        Self {
+            pawns,
+            score: [white, black],
+            passed: [white_passed, black_passed],
+        }
+    }
+}
+
+/// A cache of [`PawnEntry`]s, indexed by [`pawn_hash`]. entries are verified
+/// against the full pawn bitboards, so there are no false hits, and the table
+/// is emptied when it is probed with different weights.
+#[derive(Debug)]
+pub struct PawnTable {
+    /// the entries, a power of 2 of them
+    entries: Vec<PawnEntry>,
+    /// the weights the entries were evaluated with
+    params: PawnParams,
+    /// how many times the table was read from
+    pub probes: u64,
+    /// how many reads found the pawn structure they were looking for
+    pub hits: u64,
+}
+
+impl PawnTable {
+    /// create a table of `size` entries, rounded up to a power of 2
+    pub fn new(size: usize) -> Self {
+        Self {
+            entries: vec![PawnEntry::default(); size.max(1).next_power_of_two()],
+            params: PawnParams::DEFAULT,
+            probes: 0,
+            hits: 0,
+        }
+    }
+
+    /// get the pawn structure of `board`, evaluating and storing it if it isn't
+    /// in the table yet
+    pub fn probe(&mut self, board: &Board, params: &PawnParams) -> PawnEntry {
+        if self.params != *params {
+            self.entries.fill(PawnEntry::default());
+            self.params = *params;
+        }
+        let pawns = board.pieces(Piece::Pawn);
+        let pawns = [
+            pawns & board.color_combined(Color::White),
+            pawns & board.color_combined(Color::Black),
+        ];
+        let idx = (pawn_hash(pawns) as usize) & (self.entries.len() - 1);
+        self.probes += 1;
+        if self.entries[idx].pawns == pawns {
+            self.hits += 1;
+        } else {
+            self.entries[idx] = PawnEntry::new(pawns, params);
+        }
+        self.entries[idx]
+    }
+}
+
+thread_local! {
+    /// every search thread keeps its own pawn table, so that it needs no locks
+    static PAWN_TABLE: RefCell<PawnTable> = RefCell::new(PawnTable::new(PAWN_TABLE_SIZE));
+}
+
+/// a hash of the pawns of both sides, and nothing else
+pub fn pawn_hash(pawns: [BitBoard; 2]) -> u64 {
+    // the splitmix64 finalizer, over both bitboards
+    let mut h = pawns[0].0
+        ^ pawns[1]
+            .0
+            .rotate_left(29)
+            .wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    h = (h ^ (h >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    h ^ (h >> 31)
+}
+
+/// the pawn structure score of `board`, white's minus black's
+pub fn evaluate_pawns(board: &Board, params: &PawnParams) -> Score {
+    let entry = PAWN_TABLE.with_borrow_mut(|table| table.probe(board, params));
+    entry.score[0] - entry.score[1]
+        + passed_king_proximity(board, Color::White, entry.passed[0], params)
+        - passed_king_proximity(board, Color::Black, entry.passed[1], params)
+}
+
+/// the rank of `sq`, counted from `color`'s side of the board
+#[inline(always)]
+pub fn relative_rank(sq: Square, color: Color) -> usize {
+    match color {
+        Color::White => sq.get_rank().to_index(),
+        Color::Black => 7 - sq.get_rank().to_index(),
+    }
+}
+
+/// all the squares on the ranks in front of `sq`, from `color`'s point of view
+#[inline(always)]
+pub fn ranks_ahead(sq: Square, color: Color) -> BitBoard {
+    let rank = sq.get_rank().to_index() as u32;
+    BitBoard(match color {
+        Color::White => u64::MAX.checked_shl(8 * (rank + 1)).unwrap_or(0),
+        Color::Black => (1u64 << (8 * rank)) - 1,
+    })
+}
+
+/// the squares attacked by the given pawns of `color`
+#[inline(always)]
+pub fn pawn_attacks(pawns: BitBoard, color: Color) -> BitBoard {
+    let not_a = pawns.0 & !get_file(File::A).0;
+    let not_h = pawns.0 & !get_file(File::H).0;
+    BitBoard(match color {
+        Color::White => (not_a << 7) | (not_h << 9),
+        Color::Black => (not_a >> 9) | (not_h >> 7),
+    })
+}
+
+/// the number of king moves between two squares
+#[inline(always)]
+pub fn distance(a: Square, b: Square) -> i32 {
+    let files = a.get_file().to_index().abs_diff(b.get_file().to_index());
+    let ranks = a.get_rank().to_index().abs_diff(b.get_rank().to_index());
+    files.max(ranks) as i32
+}
+
+/// the structure score of `color`'s pawns, and which of them are passed
+fn side_structure(pawns: [BitBoard; 2], color: Color, params: &PawnParams) -> (Score, BitBoard) {
+    let us = pawns[color.to_index()];
+    let them = pawns[(!color).to_index()];
+    let their_attacks = pawn_attacks(them, !color);
+
+    let mut score = Score::ZERO;
+    let mut passed = EMPTY;
+    for sq in us {
+        let rank = relative_rank(sq, color);
+        let file = get_file(sq.get_file());
+        let adjacent = get_adjacent_files(sq.get_file());
+        let ahead = ranks_ahead(sq, color);
+        let neighbours = us & adjacent;
+
+        let doubled = us & file & ahead != EMPTY;
+        if doubled {
+            score += params.doubled;
+        }
+
+        if neighbours == EMPTY {
+            score += params.isolated;
+        } else if neighbours & !ahead == EMPTY
+            && sq
+                .forward(color)
+                .is_some_and(|stop| their_attacks & BitBoard::from_square(stop) != EMPTY)
+        {
+            score += params.backward;
+        }
+
+        let supported = pawn_attacks(BitBoard::from_square(sq), !color) & us != EMPTY;
+        let phalanx = neighbours & get_rank(sq.get_rank()) != EMPTY;
+        if supported || phalanx {
+            score += params.connected[rank];
+        }
+
+        // only the frontmost of doubled pawns counts as passed
+        if !doubled && them & (file | adjacent) & ahead == EMPTY {
+            passed |= BitBoard::from_square(sq);
+            score += params.passed[rank];
+        }
+    }
+
+    (score, passed)
+}
+
+/// the endgame bonus of `color`'s passed pawns for the distance of the kings
+/// to the square in front of them
+pub fn passed_king_proximity(
+    board: &Board,
+    color: Color,
+    passed: BitBoard,
+    params: &PawnParams,
+) -> Score {
+    let ours = board.king_square(color);
+    let theirs = board.king_square(!color);
+    let mut eg = 0;
+    for sq in passed {
+        // pawns that haven't advanced past their third rank get nothing
+        let weight = relative_rank(sq, color).saturating_sub(2) as i32;
+        if let Some(stop) = sq.forward(color) {
+            eg += weight
+                * (distance(theirs, stop) * params.passed_enemy_king
+                    - distance(ours, stop) * params.passed_own_king);
+        }
+    }
+    Score::new(0, eg)
+}
+
+#[cfg(test)]
+#[path = "tests/pawns.rs"]
+mod tests;
@@ -3,33 +3,34 @@ use std::ops::BitAnd;
 
 use chess::Board;
 use chess::Color;
+use chess::Piece;
 use chess::Square;
 
-use crate::evaluation::Interp;
-use crate::evaluation::bitboards::EG_PESTO_TABLE;
-use crate::evaluation::bitboards::MG_PESTO_TABLE;
 use crate::evaluation::bitboards::POS_PIECE_TYPES;
-use crate::setup::values::Value;
+use crate::evaluation::params::Weights;
+use crate::evaluation::score::Score;
 
 /// returns the benefit this side has from its pieces' positions
-pub fn piece_position_benefit_for_side(pos: &Board, color: Color, interp: Interp) -> Value {
-    let mut value = Value::ZERO;
+pub fn piece_position_benefit_for_side(weights: &Weights, pos: &Board, color: Color) -> Score {
+    let mut score = Score::ZERO;
     let side = pos.color_combined(color);
 
     for (idx, pt) in POS_PIECE_TYPES.into_iter().enumerate() {
         let bb = pos.pieces(pt).bitand(side);
-        let mg = MG_PESTO_TABLE[idx];
-        let eg = EG_PESTO_TABLE[idx];
-
         for sq in bb {
             let (row, col) = sq_pi(sq, color);
-            value += Value::from(
-                ((mg[row][col] as f64) * (interp.0 + interp.1)) + (eg[row][col] as f64 * interp.2),
-            );
+            score += weights.psqt[idx][row][col];
         }
     }
 
-    value
+    score
+}
+
+/// the piece-square [`Score`] of a single piece
+#[inline(always)]
+pub fn piece_square(weights: &Weights, piece: Piece, color: Color, sq: Square) -> Score {
+    let (row, col) = sq_pi(sq, color);
+    weights.psqt[piece.to_index()][row][col]
 }
 
 /// Converts a square to a pesto index.
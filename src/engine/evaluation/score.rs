@@ -0,0 +1,111 @@
+//! packed evaluation scores, tapered between the phases of the game
+use std::ops::Add;
+use std::ops::AddAssign;
+use std::ops::Mul;
+use std::ops::Neg;
+use std::ops::Sub;
+use std::ops::SubAssign;
+
+use crate::evaluation::Interp;
+use crate::evaluation::PHASE_SCALE;
+use crate::setup::values::Value;
+
+/// the number of bits of each lane of a [`Score`]
+const LANE: u32 = 21;
+
+/// A score for each phase of the game (early, middle, and endgame), packed in
+/// a single integer, so that adding up evaluation terms is one addition.
+///
+/// ## Memory Layout
+/// * endgame score: upper 22 bits
+/// * middlegame score: 21 bits
+/// * early game score: lower 21 bits
+///
+/// the lanes are signed, and addition, subtraction, negation and
+/// multiplication by an integer work on all of them at once, as long as no
+/// lane overflows (±2^20, far above any evaluation).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Score(i64);
+
+impl Score {
+    /// a score of zero in every phase
+    pub const ZERO: Score = Score(0);
+
+    /// a score with the same value for the early and middle game, which is
+    /// what most evaluation terms need
+    pub const fn new(mg: i32, eg: i32) -> Self {
+        Self::phased(mg, mg, eg)
+    }
+
+    /// a score with a separate value for every phase of the game
+    pub const fn phased(early: i32, mg: i32, eg: i32) -> Self {
+        Score(((eg as i64) << (2 * LANE)) + ((mg as i64) << LANE) + early as i64)
+    }
+
+    /// the early game value
+    pub const fn early(self) -> i32 {
+        ((self.0 << (64 - LANE)) >> (64 - LANE)) as i32
+    }
+
+    /// the middlegame value
+    pub const fn mg(self) -> i32 {
+        // rounding makes up for the borrow of a negative early game value
+        (((self.0 + (1 << (LANE - 1))) << (64 - 2 * LANE)) >> (64 - LANE)) as i32
+    }
+
+    /// the endgame value
+    pub const fn eg(self) -> i32 {
+        ((self.0 + (1 << (2 * LANE - 1)) + (1 << (LANE - 1))) >> (2 * LANE)) as i32
+    }
+
+    /// blend the phases of this score according to the weights in `interp`
+    pub const fn taper(self, interp: Interp) -> Value {
+        let blended =
+            self.early() * interp.early + self.mg() * interp.middle + self.eg() * interp.end;
+        Value((blended / PHASE_SCALE) as i16)
+    }
+}
+
+impl Add for Score {
+    type Output = Score;
+    fn add(self, rhs: Score) -> Score {
+        Score(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Score {
+    type Output = Score;
+    fn sub(self, rhs: Score) -> Score {
+        Score(self.0 - rhs.0)
+    }
+}
+
+impl Neg for Score {
+    type Output = Score;
+    fn neg(self) -> Score {
+        Score(-self.0)
+    }
+}
+
+impl Mul<i32> for Score {
+    type Output = Score;
+    fn mul(self, rhs: i32) -> Score {
+        Score(self.0 * rhs as i64)
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, rhs: Score) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Score {
+    fn sub_assign(&mut self, rhs: Score) {
+        *self = *self - rhs;
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/score.rs"]
+mod tests;
@@ -0,0 +1,256 @@
+//! a harness to check that the evaluation doesn't depend on which colour is
+//! which, or which side of the board is which.
+//!
+//! a position and its colour-flipped version (see [`flip_colours`]) are the
+//! same position for the side to move, so they must have the same
+//! evaluation. the same goes for the left-right mirror (see [`mirror`]) of a
+//! position where nobody can castle. passing the move (see [`null_move`])
+//! swaps the sides, so the evaluation changes sign, give or take the tempo
+//! bonus of each side.
+//!
+//! the default piece-square tables (PeSTO's) aren't left-right symmetric, so
+//! mirrored positions are only checked while the tables in use are (see
+//! [`mirrored_params`]).
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+use chess::Board;
+use chess::BoardStatus;
+
+use crate::evaluation::TEMPO;
+use crate::evaluation::evaluate;
+use crate::evaluation::params::EvalParams;
+use crate::evaluation::params::params;
+use crate::position::Position;
+use crate::setup::values::Value;
+
+/// more positions to check than [`bench_positions`], from self-play games and
+/// a few hand-picked en passant, castling, check and endgame positions. one
+/// position per line, as the first four fields of a FEN.
+///
+/// [`bench_positions`]: crate::util::bench_positions
+const EPD_POSITIONS: &str = include_str!("symmetry.epd");
+
+/// the positions of [`EPD_POSITIONS`]
+pub fn epd_positions() -> Vec<Board> {
+    EPD_POSITIONS
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().take(4).collect();
+            let fen = format!("{} 0 1", fields.join(" "));
+            Board::from_str(&fen).unwrap_or_else(|e| panic!("{fen} is not a valid position: {e}"))
+        })
+        .collect()
+}
+
+/// how a position and its transformation disagree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Asymmetry {
+    /// the colour-flipped position has a different evaluation
+    Colours {
+        /// the position
+        board: Board,
+        /// its evaluation
+        value: Value,
+        /// the evaluation of [`flip_colours`]
+        flipped: Value,
+    },
+    /// the mirrored position has a different evaluation
+    Mirror {
+        /// the position
+        board: Board,
+        /// its evaluation
+        value: Value,
+        /// the evaluation of [`mirror`]
+        mirrored: Value,
+    },
+    /// passing the move changes the evaluation by more than the tempo bonuses
+    NullMove {
+        /// the position
+        board: Board,
+        /// its evaluation
+        value: Value,
+        /// the evaluation of [`null_move`]
+        passed: Value,
+    },
+}
+
+impl Display for Asymmetry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Asymmetry::Colours {
+                board,
+                value,
+                flipped,
+            } => write!(f, "{board}: {value} but {flipped} with the colours flipped"),
+            Asymmetry::Mirror {
+                board,
+                value,
+                mirrored,
+            } => write!(f, "{board}: {value} but {mirrored} mirrored"),
+            Asymmetry::NullMove {
+                board,
+                value,
+                passed,
+            } => write!(f, "{board}: {value} but {passed} after a null move"),
+        }
+    }
+}
+
+/// `board` with the colours swapped: the pieces of each side belong to the
+/// other, the ranks are flipped, and the other side is to move
+pub fn flip_colours(board: &Board) -> Board {
+    let fen = board.to_string();
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    let placement = fields[0]
+        .split('/')
+        .rev()
+        .map(|rank| rank.chars().map(swap_case).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("/");
+    let stm = if fields[1] == "w" { "b" } else { "w" };
+    let castling = match fields[2] {
+        "-" => "-".to_string(),
+        rights => {
+            let swapped: String = rights.chars().map(swap_case).collect();
+            // white's rights come first
+            "KQkq".chars().filter(|c| swapped.contains(*c)).collect()
+        }
+    };
+    let en_passant = match fields[3].as_bytes() {
+        [file, rank] => format!("{}{}", *file as char, (b'1' + b'8' - rank) as char),
+        _ => "-".to_string(),
+    };
+    from_fields(&placement, stm, &castling, &en_passant)
+}
+
+/// `board` reflected left to right (the a-file becomes the h-file), if
+/// nobody can castle, since castling isn't symmetric
+pub fn mirror(board: &Board) -> Option<Board> {
+    let fen = board.to_string();
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    if fields[2] != "-" {
+        return None;
+    }
+    // the empty squares are single digits, so reversing the characters of
+    // each rank reverses its squares
+    let placement = fields[0]
+        .split('/')
+        .map(|rank| rank.chars().rev().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("/");
+    let en_passant = match fields[3].as_bytes() {
+        [file, rank] => format!("{}{}", (b'a' + b'h' - file) as char, *rank as char),
+        _ => "-".to_string(),
+    };
+    Some(from_fields(&placement, fields[1], "-", &en_passant))
+}
+
+/// `board` with the other side to move, unless the side to move is in check
+pub fn null_move(board: &Board) -> Option<Board> {
+    board.null_move()
+}
+
+/// `params` with every piece-square table averaged with its mirror image, so
+/// that the evaluation should be left-right symmetric
+pub fn mirrored_params(params: &EvalParams) -> EvalParams {
+    let mut mirrored = *params;
+    for table in mirrored
+        .mg_psqt
+        .iter_mut()
+        .chain(mirrored.eg_psqt.iter_mut())
+    {
+        for row in table.iter_mut() {
+            let original = *row;
+            for (col, value) in row.iter_mut().enumerate() {
+                *value = ((original[col] as i32 + original[7 - col] as i32) / 2) as _;
+            }
+        }
+    }
+    mirrored
+}
+
+/// whether the piece-square tables of `params` are left-right symmetric
+pub fn is_mirrored(params: &EvalParams) -> bool {
+    params
+        .mg_psqt
+        .iter()
+        .chain(params.eg_psqt.iter())
+        .flatten()
+        .all(|row| (0..4).all(|col| row[col] == row[7 - col]))
+}
+
+/// the piece of the other colour
+fn swap_case(c: char) -> char {
+    if c.is_ascii_uppercase() {
+        c.to_ascii_lowercase()
+    } else {
+        c.to_ascii_uppercase()
+    }
+}
+
+/// the board with the given FEN fields, which describe a legal position if
+/// the fields they were transformed from did
+fn from_fields(placement: &str, stm: &str, castling: &str, en_passant: &str) -> Board {
+    let fen = format!("{placement} {stm} {castling} {en_passant} 0 1");
+    Board::from_str(&fen).unwrap_or_else(|e| panic!("{fen} is not a valid position: {e}"))
+}
+
+/// the evaluation of `board`, with a fresh [`Position`]
+fn evaluation(board: &Board) -> Value {
+    evaluate(&Position::from(*board), false)
+}
+
+/// every way the evaluation of `board` isn't symmetric. positions where the
+/// game is over aren't checked, since they aren't evaluated, and neither are
+/// mirrored positions unless [`is_mirrored`] holds for the [`params`] in use.
+pub fn check(board: &Board) -> Vec<Asymmetry> {
+    let mut found = vec![];
+    if board.status() != BoardStatus::Ongoing {
+        return found;
+    }
+    let value = evaluation(board);
+
+    let flipped = evaluation(&flip_colours(board));
+    if flipped != value {
+        found.push(Asymmetry::Colours {
+            board: *board,
+            value,
+            flipped,
+        });
+    }
+    if let Some(mirrored) = mirror(board)
+        .filter(|_| is_mirrored(&params()))
+        .as_ref()
+        .map(evaluation)
+        && mirrored != value
+    {
+        found.push(Asymmetry::Mirror {
+            board: *board,
+            value,
+            mirrored,
+        });
+    }
+    if let Some(passed) = null_move(board).filter(|b| b.status() == BoardStatus::Ongoing)
+        && let passed = evaluation(&passed)
+        && (value + passed).abs() > TEMPO + TEMPO
+    {
+        found.push(Asymmetry::NullMove {
+            board: *board,
+            value,
+            passed,
+        });
+    }
+    found
+}
+
+/// every asymmetry of every position in `boards`
+pub fn check_all<'a>(boards: impl IntoIterator<Item = &'a Board>) -> Vec<Asymmetry> {
+    boards.into_iter().flat_map(check).collect()
+}
+
+#[cfg(test)]
+#[path = "tests/symmetry.rs"]
+mod tests;
@@ -0,0 +1,96 @@
+use std::str::FromStr;
+
+use chess::Board;
+use chess::ChessMove;
+use chess::Color;
+use chess::MoveGen;
+use chess::Piece;
+use chess::Square;
+
+use crate::evaluation::accumulator::Accumulator;
+use crate::evaluation::material::interpolate;
+use crate::position::Position;
+use crate::util::bench_positions;
+
+/// play every legal move from `board`, `depth` plies deep, checking the
+/// incremental update against a full recompute at every node
+fn check_moves(pos: &Position, depth: usize) {
+    if depth == 0 {
+        return;
+    }
+    for mv in MoveGen::new_legal(&pos.chessboard) {
+        let acc = pos.accumulator.make_move(&pos.chessboard, mv);
+        let next = pos.chessboard.make_move_new(mv);
+        assert_eq!(acc, Accumulator::new(&next), "{} {mv}", pos.chessboard);
+        check_moves(&pos.make_move(mv), depth - 1);
+    }
+}
+
+/// play a single move, given by its squares
+fn play(fen: &str, src: &str, dest: &str, promotion: Option<Piece>) -> (Position, Position) {
+    let pos = Position::from(Board::from_str(fen).unwrap());
+    let mv = ChessMove::new(
+        Square::from_str(src).unwrap(),
+        Square::from_str(dest).unwrap(),
+        promotion,
+    );
+    assert!(pos.chessboard.legal(mv), "{mv} in {fen}");
+    let next = pos.make_move(mv);
+    (pos, next)
+}
+
+#[test]
+fn startpos_is_symmetric() {
+    let pos = Position::default();
+    assert_eq!(pos.accumulator, Accumulator::new(&Board::default()));
+    assert_eq!(pos.accumulator.material[0], pos.accumulator.material[1]);
+    assert_eq!(
+        pos.accumulator.score(Color::White),
+        -pos.accumulator.score(Color::Black)
+    );
+    assert_eq!(pos.accumulator.interp(), interpolate(&pos.chessboard));
+}
+
+#[test]
+fn special_moves() {
+    let cases = [
+        // castling, both sides
+        ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1", "g1", None),
+        ("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "e8", "c8", None),
+        // en passant
+        ("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2", "e5", "d6", None),
+        ("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 2", "d4", "e3", None),
+        // promotion, with and without capture
+        (
+            "1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1",
+            "a7",
+            "a8",
+            Some(Piece::Queen),
+        ),
+        (
+            "1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1",
+            "a7",
+            "b8",
+            Some(Piece::Knight),
+        ),
+        // a plain capture, and a king capture
+        ("4k3/8/8/3p4/4N3/8/8/4K3 w - - 0 1", "e4", "d6", None),
+        ("4k3/8/8/8/8/8/3q4/4K3 w - - 0 1", "e1", "d2", None),
+    ];
+    for (fen, src, dest, promotion) in cases {
+        let (pos, next) = play(fen, src, dest, promotion);
+        assert_eq!(
+            next.accumulator,
+            Accumulator::new(&next.chessboard),
+            "{fen} {src}{dest}"
+        );
+        assert_ne!(next.accumulator, pos.accumulator, "{fen} {src}{dest}");
+    }
+}
+
+#[test]
+fn every_move_matches_recompute() {
+    for board in bench_positions().into_iter().take(12) {
+        check_moves(&Position::from(board), 2);
+    }
+}
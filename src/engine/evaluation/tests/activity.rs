@@ -0,0 +1,79 @@
+use std::str::FromStr;
+
+use chess::Board;
+use chess::Color;
+
+use crate::evaluation::activity::ActivityParams;
+use crate::evaluation::activity::piece_activity;
+use crate::evaluation::attacks::AttackMap;
+use crate::evaluation::attacks::MOBILITY;
+use crate::evaluation::score::Score;
+
+/// the piece activity of `color` in the position given as a FEN
+fn activity(fen: &str, color: Color) -> Score {
+    let board = Board::from_str(fen).unwrap();
+    piece_activity(
+        &board,
+        color,
+        &AttackMap::new(&board, &MOBILITY),
+        &ActivityParams::DEFAULT,
+    )
+}
+
+#[test]
+fn startpos_has_the_bishop_pair() {
+    let p = ActivityParams::DEFAULT;
+    let board = Board::default();
+    assert_eq!(activity(&board.to_string(), Color::White), p.bishop_pair);
+    assert_eq!(activity(&board.to_string(), Color::Black), p.bishop_pair);
+}
+
+#[test]
+fn rooks_on_files() {
+    let p = ActivityParams::DEFAULT;
+    let fen = "4k3/p1p5/8/8/8/8/1PP5/R1R1K3 w - - 0 1";
+    // a-file: semi-open, b-file: closed, c-file: closed
+    assert_eq!(activity(fen, Color::White), p.rook_semi_open_file);
+    let fen = "4k3/2p5/8/8/8/8/2P5/R1R1K3 w - - 0 1";
+    assert_eq!(activity(fen, Color::White), p.rook_open_file);
+}
+
+#[test]
+fn rook_on_seventh() {
+    let p = ActivityParams::DEFAULT;
+    // the black king is stuck on the 8th rank
+    let fen = "4k3/1R6/8/8/8/8/8/4K3 w - - 0 1";
+    assert_eq!(
+        activity(fen, Color::White),
+        p.rook_open_file + p.rook_on_seventh
+    );
+    // or there are pawns to take
+    let fen = "8/1R3p2/4k3/8/8/8/8/4K3 w - - 0 1";
+    assert_eq!(
+        activity(fen, Color::White),
+        p.rook_open_file + p.rook_on_seventh
+    );
+    // but otherwise it's just a rook
+    let fen = "8/1R6/4k3/8/8/8/8/4K3 w - - 0 1";
+    assert_eq!(activity(fen, Color::White), p.rook_open_file);
+    // and the same for black
+    let fen = "4k3/8/8/8/8/8/1r6/4K3 b - - 0 1";
+    assert_eq!(
+        activity(fen, Color::Black),
+        p.rook_open_file + p.rook_on_seventh
+    );
+}
+
+#[test]
+fn knight_outposts() {
+    let p = ActivityParams::DEFAULT;
+    // defended by e4, and no black pawn on the d or f files can chase it
+    let fen = "4k3/pp6/8/3N4/4P3/8/8/4K3 w - - 0 1";
+    assert_eq!(activity(fen, Color::White), p.knight_outpost);
+    // c7 can kick it
+    let fen = "4k3/2p5/8/3N4/4P3/8/8/4K3 w - - 0 1";
+    assert_eq!(activity(fen, Color::White), Score::ZERO);
+    // not defended
+    let fen = "4k3/pp6/8/3N4/8/8/8/4K3 w - - 0 1";
+    assert_eq!(activity(fen, Color::White), Score::ZERO);
+}
@@ -0,0 +1,60 @@
+use std::str::FromStr;
+
+use chess::BitBoard;
+use chess::Board;
+use chess::Color;
+use chess::Piece;
+use chess::Rank;
+use chess::Square;
+use chess::get_rank;
+
+use crate::evaluation::attacks::AttackMap;
+use crate::evaluation::attacks::MOBILITY;
+use crate::evaluation::attacks::MOBILITY_BASE;
+use crate::evaluation::score::Score;
+
+/// the attack map of a position given as a FEN
+fn map(fen: &str) -> AttackMap {
+    AttackMap::new(&Board::from_str(fen).unwrap(), &MOBILITY)
+}
+
+#[test]
+fn startpos_is_symmetric() {
+    let map = AttackMap::new(&Board::default(), &MOBILITY);
+    assert_eq!(map.mobility[0], map.mobility[1]);
+    assert_eq!(map.all[0] & get_rank(Rank::Third), get_rank(Rank::Third));
+    assert_eq!(map.all[0].reverse_colors(), map.all[1]);
+    assert_eq!(map.double[0].reverse_colors(), map.double[1]);
+    assert_eq!(map.zone_attackers, [0, 0]);
+}
+
+#[test]
+fn mobility_counts_safe_squares() {
+    // a knight in the centre reaches 8 squares
+    let free = map("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1");
+    assert_eq!(free.mobility[0], MOBILITY[0] * (8 - MOBILITY_BASE[0]));
+    assert_eq!(free.mobility[1], Score::ZERO);
+    // c6 and e6 are taken by a pawn, and f5 by the knight's own pawn
+    let guarded = map("4k3/3p4/8/5P2/3N4/8/8/4K3 w - - 0 1");
+    assert_eq!(guarded.mobility[0], MOBILITY[0] * (5 - MOBILITY_BASE[0]));
+}
+
+#[test]
+fn collects_attacks() {
+    let map = map("4k3/8/8/8/8/8/8/R2QK3 w - - 0 1");
+    let d4 = BitBoard::from_square(Square::D4);
+    let b1 = BitBoard::from_square(Square::B1);
+    assert_eq!(map.attacked_by(Color::White, Piece::Queen) & d4, d4);
+    assert_eq!(map.attacked_by(Color::White, Piece::Rook) & d4, BitBoard(0));
+    // b1 and c1 are attacked by both the rook and the queen
+    assert_eq!(map.double[0] & b1, b1);
+    assert_eq!(map.all[1] & d4, BitBoard(0));
+}
+
+#[test]
+fn counts_king_zone_attacks() {
+    let map = map("4k3/8/8/8/8/5n2/4q3/6K1 w - - 0 1");
+    assert_eq!(map.zone_attackers, [0, 2]);
+    // the knight hits g1 and h2, the queen f1, f2, g2 and h2
+    assert_eq!(map.zone_hits[1], [2, 0, 0, 4]);
+}
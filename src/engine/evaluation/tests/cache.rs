@@ -0,0 +1,60 @@
+use super::*;
+
+#[test]
+fn store_and_probe() {
+    let cache = EvalCache::new(DEFAULT_EVAL_CACHE_SIZE);
+    let hash = 0x1234_5678_9abc_def0;
+    assert_eq!(cache.probe(hash), None);
+    cache.store(hash, Value(-37));
+    assert_eq!(cache.probe(hash), Some(Value(-37)));
+    cache.store(hash, Value(512));
+    assert_eq!(cache.probe(hash), Some(Value(512)));
+}
+
+#[test]
+fn other_keys_miss() {
+    let cache = EvalCache::new(DEFAULT_EVAL_CACHE_SIZE);
+    let hash = 0x1234_5678_9abc_def0;
+    cache.store(hash, Value(20));
+    // same entry, different key
+    assert_eq!(cache.probe(hash ^ (1 << 60)), None);
+    // and the other position replaces it
+    cache.store(hash ^ (1 << 60), Value(30));
+    assert_eq!(cache.probe(hash), None);
+    assert_eq!(cache.probe(hash ^ (1 << 60)), Some(Value(30)));
+}
+
+#[test]
+fn clear_forgets() {
+    let cache = EvalCache::new(DEFAULT_EVAL_CACHE_SIZE);
+    cache.store(42 << 20, Value(1));
+    assert_eq!(cache.probe(42 << 20), Some(Value(1)));
+    cache.clear();
+    assert_eq!(cache.probe(42 << 20), None);
+    assert_eq!(cache.stats(), CacheStats { probes: 1, hits: 0 });
+}
+
+#[test]
+fn stats() {
+    let cache = EvalCache::new(DEFAULT_EVAL_CACHE_SIZE);
+    let before = cache.stats();
+    cache.store(7 << 20, Value(3));
+    for hash in [7 << 20, 7 << 20, 8 << 20, 7 << 20] {
+        cache.probe(hash);
+    }
+    let stats = cache.stats() - before;
+    assert_eq!(stats, CacheStats { probes: 4, hits: 3 });
+    assert_eq!(stats.hit_rate(), 750);
+    assert_eq!(stats.to_string(), "probes 4 hits 3 hitrate 750");
+    assert_eq!(CacheStats::default().hit_rate(), 0);
+}
+
+#[test]
+fn sizes() {
+    assert_eq!(
+        EvalCache::new(DEFAULT_EVAL_CACHE_SIZE).size(),
+        DEFAULT_EVAL_CACHE_SIZE
+    );
+    assert_eq!(EvalCache::new(3 << 20).size(), 2 << 20);
+    assert_eq!(EvalCache::new(1).size(), MIN_ENTRIES * 8);
+}
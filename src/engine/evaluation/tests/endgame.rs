@@ -0,0 +1,144 @@
+use std::str::FromStr;
+
+use chess::Board;
+use chess::Color;
+
+use crate::evaluation::endgame::EndgameParams;
+use crate::evaluation::endgame::SCALE_NORMAL;
+use crate::evaluation::endgame::insufficient_material;
+use crate::evaluation::endgame::is_known_draw;
+use crate::evaluation::endgame::mop_up;
+use crate::evaluation::endgame::scale;
+use crate::evaluation::endgame::scale_factor;
+use crate::evaluation::evaluate;
+use crate::evaluation::score::Score;
+use crate::position::Position;
+use crate::setup::values::Value;
+
+/// the board of a FEN
+fn board(fen: &str) -> Board {
+    Board::from_str(fen).unwrap()
+}
+
+#[test]
+fn insufficient() {
+    for fen in [
+        "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/4KN2 w - - 0 1",
+        "4k3/8/8/8/8/8/8/4KB2 b - - 0 1",
+        // both bishops on dark squares
+        "4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1",
+    ] {
+        assert!(insufficient_material(&board(fen)), "{fen}");
+        assert!(is_known_draw(&board(fen)), "{fen}");
+    }
+    for fen in [
+        "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1",
+        "4k3/8/8/8/8/8/8/3NKB2 w - - 0 1",
+        Board::default().to_string().as_str(),
+    ] {
+        assert!(!insufficient_material(&board(fen)), "{fen}");
+    }
+}
+
+#[test]
+fn known_draws() {
+    // two knights can't force mate
+    assert!(is_known_draw(&board("4k3/8/8/8/8/8/8/1N2K1N1 w - - 0 1")));
+    assert!(!is_known_draw(&board("4k3/8/8/8/8/8/7p/1N2K1N1 w - - 0 1")));
+    // h8 is dark, the bishop is light squared and the king holds the corner
+    assert!(is_known_draw(&board("6k1/8/8/7P/8/8/8/4KB2 w - - 0 1")));
+    // same for black, promoting on a1
+    assert!(is_known_draw(&board("2k1b3/8/8/8/p7/8/8/1K6 b - - 0 1")));
+    // the right bishop wins
+    assert!(!is_known_draw(&board("6k1/8/8/7P/8/8/8/2B1K3 w - - 0 1")));
+    // as does the wrong one, if the king is too far away
+    assert!(!is_known_draw(&board("8/8/8/3k3P/8/8/8/4KB2 w - - 0 1")));
+}
+
+#[test]
+fn evaluates_draws() {
+    for fen in [
+        "4k3/8/8/8/8/8/8/4KB2 w - - 0 1",
+        "4k3/8/8/8/8/8/8/1N2K1N1 b - - 0 1",
+        "6k1/8/8/7P/8/8/8/4KB2 w - - 0 1",
+    ] {
+        let pos = Position::from(board(fen));
+        assert_eq!(evaluate(&pos, false), Value::DRAW, "{fen}");
+    }
+}
+
+#[test]
+fn scale_factors() {
+    let p = EndgameParams::DEFAULT;
+    let ahead = Score::new(100, 100);
+    let fen = "4kb2/5ppp/8/8/8/8/PPPP4/4KB2 w - - 0 1";
+    assert_eq!(
+        scale_factor(&board(fen), Color::White, ahead, &p),
+        p.opposite_bishops
+    );
+    // same coloured bishops
+    let fen = "4k1b1/5ppp/8/8/8/8/PPPP4/4KB2 w - - 0 1";
+    assert_eq!(
+        scale_factor(&board(fen), Color::White, ahead, &p),
+        SCALE_NORMAL
+    );
+    // a rook against a bishop
+    let fen = "4kb2/8/8/8/8/8/8/R3K3 b - - 0 1";
+    assert_eq!(
+        scale_factor(&board(fen), Color::Black, -ahead, &p),
+        p.no_pawns
+    );
+    assert_eq!(
+        scale_factor(
+            &board(&Board::default().to_string()),
+            Color::White,
+            ahead,
+            &p
+        ),
+        SCALE_NORMAL
+    );
+}
+
+#[test]
+fn scaling_only_touches_the_endgame() {
+    let score = Score::phased(10, 20, 128);
+    let scaled = scale(score, SCALE_NORMAL / 4);
+    assert_eq!((scaled.early(), scaled.mg(), scaled.eg()), (10, 20, 32));
+    assert_eq!(scale(score, SCALE_NORMAL), score);
+}
+
+/// the mop-up score of a FEN
+fn mop(fen: &str) -> Option<Value> {
+    let board = board(fen);
+    mop_up(&board, board.side_to_move(), &EndgameParams::DEFAULT)
+}
+
+#[test]
+fn mop_up_only_against_a_bare_king() {
+    assert!(mop("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap() > Value::KNOWN_WIN);
+    assert!(mop("4k3/8/8/8/8/8/8/3QK3 b - - 0 1").unwrap() < -Value::KNOWN_WIN);
+    assert!(mop("4k3/8/8/8/8/8/8/2BNK3 w - - 0 1").is_some());
+    assert!(mop("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1").is_some());
+    // a pawn is enough to defend, and a lone bishop can't win
+    assert_eq!(mop("4k3/7p/8/8/8/8/8/3QK3 w - - 0 1"), None);
+    assert_eq!(mop("4k3/8/8/8/8/8/8/3NKN2 w - - 0 1"), None);
+    assert_eq!(mop(&Board::default().to_string()), None);
+}
+
+#[test]
+fn mop_up_drives_the_king() {
+    // to the edge
+    let centre = mop("8/8/8/4k3/8/8/8/R3K3 w - - 0 1").unwrap();
+    let edge = mop("8/8/8/7k/8/8/8/R3K3 w - - 0 1").unwrap();
+    assert!(edge > centre);
+    // with the winning king close by
+    let far = mop("7k/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    let near = mop("7k/8/5K2/8/8/8/8/R7 w - - 0 1").unwrap();
+    assert!(near > far);
+    // and to the bishop's corner, h8 for a dark squared one
+    let right = mop("6k1/8/8/8/8/8/8/2BNK3 w - - 0 1").unwrap();
+    let wrong = mop("1k6/8/8/8/8/8/8/2BNK3 w - - 0 1").unwrap();
+    assert!(right > wrong);
+}
@@ -0,0 +1,110 @@
+use std::str::FromStr;
+
+use chess::Board;
+use chess::ChessMove;
+use chess::Square;
+
+use crate::Engine;
+use crate::evaluation::cache::DEFAULT_EVAL_CACHE_SIZE;
+use crate::evaluation::evaluate;
+use crate::evaluation::evaluator::DefaultEvaluator;
+use crate::evaluation::evaluator::Evaluator;
+use crate::evaluation::evaluator::MaterialEvaluator;
+use crate::opts::Opts;
+use crate::position::Position;
+use crate::setup::depth::Depth;
+use crate::setup::values::Value;
+
+fn material(fen: &str, out_of_moves: bool) -> Value {
+    let pos = Position::from(Board::from_str(fen).unwrap());
+    MaterialEvaluator.evaluate(&pos, out_of_moves, &Opts::new())
+}
+
+#[test]
+fn material_only() {
+    assert_eq!(
+        material(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            false
+        ),
+        Value::ZERO
+    );
+    // a queen up, for either side to move
+    assert_eq!(
+        material("4k3/8/8/8/8/8/8/3QK3 w - - 0 1", false),
+        Value(900)
+    );
+    assert_eq!(
+        material("4k3/8/8/8/8/8/8/3QK3 b - - 0 1", false),
+        Value(-900)
+    );
+    // checkmate and stalemate
+    assert_eq!(
+        material("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1", true),
+        -Value::MATE
+    );
+    assert_eq!(
+        material("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", true),
+        Value::DRAW
+    );
+}
+
+#[test]
+fn default_is_the_usual_evaluation() {
+    let pos = Position::from(
+        Board::from_str("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3")
+            .unwrap(),
+    );
+    assert_eq!(
+        DefaultEvaluator::default().evaluate(&pos, false, &Opts::new()),
+        evaluate(&pos, false)
+    );
+    let mv = ChessMove::new(Square::F1, Square::B5, None);
+    assert_eq!(
+        DefaultEvaluator::default().make_move(&pos, mv),
+        pos.make_move(mv)
+    );
+}
+
+#[test]
+fn cached_evaluation() {
+    let pos = Position::from(
+        Board::from_str("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3")
+            .unwrap(),
+    );
+    let cached = DefaultEvaluator::with_cache(DEFAULT_EVAL_CACHE_SIZE);
+    let opts = Opts::new();
+    let value = evaluate(&pos, false);
+    assert_eq!(cached.evaluate(&pos, false, &opts), value);
+    assert_eq!(cached.evaluate(&pos, false, &opts), value);
+    let stats = cached.cache_stats().unwrap();
+    assert_eq!((stats.probes, stats.hits), (2, 1));
+
+    cached.clear();
+    assert_eq!(cached.cache_stats().unwrap().probes, 0);
+    assert!(DefaultEvaluator::with_cache(0).cache.is_none());
+}
+
+#[test]
+fn search_with_material() {
+    // the queen can take the pawn, but then the king takes the queen
+    let mut engine = Engine::with_evaluator(MaterialEvaluator).unwrap();
+    engine.board = Board::from_str("4k3/3p4/8/8/8/8/8/3QK3 w - - 0 1")
+        .unwrap()
+        .into();
+    let (mv, _) = engine.search_fixed(Depth(3), None).unwrap().unwrap();
+    assert_ne!(mv.0, ChessMove::new(Square::D1, Square::D7, None));
+    assert!(mv.1 >= Value(800), "{}", mv.1);
+
+    // a hanging queen is taken
+    engine.board = Board::from_str("3q2k1/8/8/8/8/8/8/3R2K1 w - - 0 1")
+        .unwrap()
+        .into();
+    let (mv, _) = engine.search_fixed(Depth(3), None).unwrap().unwrap();
+    assert_eq!(mv.0, ChessMove::new(Square::D1, Square::D8, None));
+    assert!(
+        mv.1 >= Value::MATE_IN_MAX_PLY || mv.1 == Value(500),
+        "{}",
+        mv.1
+    );
+}
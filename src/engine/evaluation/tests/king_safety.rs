@@ -0,0 +1,77 @@
+use std::str::FromStr;
+
+use chess::Board;
+use chess::Color;
+
+use crate::evaluation::attacks::AttackMap;
+use crate::evaluation::attacks::MOBILITY;
+use crate::evaluation::king_safety::KingSafetyParams;
+use crate::evaluation::king_safety::king_safety;
+use crate::evaluation::score::Score;
+
+/// the king safety of `color` in the position given as a FEN
+fn safety(fen: &str, color: Color) -> Score {
+    let board = Board::from_str(fen).unwrap();
+    king_safety(
+        &board,
+        color,
+        &AttackMap::new(&board, &MOBILITY),
+        &KingSafetyParams::DEFAULT,
+    )
+}
+
+#[test]
+fn intact_shield_is_safest() {
+    let p = KingSafetyParams::DEFAULT;
+    let intact = safety("4k3/8/8/8/8/8/5PPP/6K1 w - - 0 1", Color::White);
+    assert_eq!(intact, Score::new(3 * p.shield[0], 0));
+
+    let advanced = safety("4k3/8/8/8/8/6P1/5P1P/6K1 w - - 0 1", Color::White);
+    assert_eq!(advanced, Score::new(2 * p.shield[0] + p.shield[1], 0));
+
+    // g-file without white pawns, but with a storming black pawn
+    let open = safety("4k3/8/8/8/8/6p1/5P1P/6K1 w - - 0 1", Color::White);
+    assert_eq!(
+        open,
+        Score::new(
+            2 * p.shield[0] + p.shield[2] - p.storm[1] - p.semi_open_file,
+            0
+        )
+    );
+    assert!(intact.mg() > advanced.mg() && advanced.mg() > open.mg());
+}
+
+#[test]
+fn colours_are_symmetric() {
+    let white = safety("r5k1/5ppp/8/8/8/6P1/3q1P1P/6K1 w - - 0 1", Color::White);
+    let black = safety("6k1/3Q1p1p/6p1/8/8/8/5PPP/R5K1 b - - 0 1", Color::Black);
+    assert_eq!(white, black);
+}
+
+#[test]
+fn attackers_add_danger() {
+    let p = KingSafetyParams::DEFAULT;
+    let base = "4k3/8/8/8/8/8/5PPP/6K1 w - - 0 1";
+    // a lone queen on the king zone is not counted
+    let queen = "4k3/8/8/8/8/8/4qPPP/6K1 w - - 0 1";
+    // but a queen and a knight are
+    let both = "4k3/8/8/8/8/5n2/4qPPP/6K1 w - - 0 1";
+    let pawns_only = safety(base, Color::White).mg();
+    assert_eq!(safety(queen, Color::White).mg(), pawns_only);
+    let attacked = safety(both, Color::White).mg();
+    assert!(attacked < pawns_only, "{attacked} {pawns_only}");
+    assert!(attacked >= pawns_only - p.max_danger);
+    // a side's own pieces don't put its king in danger
+    assert_eq!(safety(both, Color::Black), safety(base, Color::Black));
+}
+
+#[test]
+fn nothing_in_the_endgame() {
+    for fen in [
+        "4k3/8/8/8/8/8/5PPP/6K1 w - - 0 1",
+        "4k3/8/8/8/8/5n2/4qPPP/6K1 w - - 0 1",
+        "8/8/8/3K4/8/8/8/3k4 w - - 0 1",
+    ] {
+        assert_eq!(safety(fen, Color::White).eg(), 0, "{fen}");
+    }
+}
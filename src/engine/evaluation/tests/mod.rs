@@ -3,9 +3,15 @@ use std::str::FromStr;
 use chess::Board;
 
 use crate::evaluation;
+use crate::evaluation::PHASE_SCALE;
+use crate::evaluation::TEMPO;
+use crate::evaluation::accumulator::Accumulator;
+use crate::evaluation::material::interpolate;
 use crate::opts::Opts;
 use crate::search::moveordering::ordered_moves;
 use crate::setup::values::Value;
+use crate::util::bench_positions;
+use crate::util::short_benches;
 
 // #[test]
 // fn startpos_is_tempo() {
@@ -56,3 +62,132 @@ fn black_completely_winning() {
     let moves = ordered_moves(&pos);
     assert!(evaluation::eval(&pos, &moves, Opts::new()).unwrap() > Value::ZERO);
 }
+
+/// the evaluation as it was before integer tapering: every term multiplied by
+/// `f64` phase coefficients, and truncated on its own.
+mod reference {
+    use std::ops::BitAnd;
+
+    use chess::Board;
+    use chess::Color;
+
+    use crate::evaluation::TEMPO;
+    use crate::evaluation::bitboards::EG_PESTO_TABLE;
+    use crate::evaluation::bitboards::MG_PESTO_TABLE;
+    use crate::evaluation::bitboards::POS_PIECE_TYPES;
+    use crate::evaluation::material::ENDGAME_SCORE;
+    use crate::evaluation::material::ENDGAME_VALUES;
+    use crate::evaluation::material::INITIAL_VALUES;
+    use crate::evaluation::material::MAT_PIECE_TYPES;
+    use crate::evaluation::material::MIDDLEGAME_SCORE;
+    use crate::evaluation::material::MIDGAME_VALUES;
+    use crate::evaluation::material::total_material;
+    use crate::evaluation::position::sq_pi;
+    use crate::setup::values::Value;
+
+    pub fn interpolate(pos: &Board) -> (f64, f64, f64) {
+        let total = total_material(pos).0 as f64;
+        let midpoint = (MIDDLEGAME_SCORE + ENDGAME_SCORE).0 as f64 / 2.0;
+        let early = if total >= MIDDLEGAME_SCORE.0 as f64 {
+            1.0
+        } else if total <= midpoint {
+            0.0
+        } else {
+            (total - midpoint) / (MIDDLEGAME_SCORE.0 as f64 - midpoint)
+        };
+        let end = if total <= ENDGAME_SCORE.0 as f64 {
+            1.0
+        } else if total >= midpoint {
+            0.0
+        } else {
+            (midpoint - total) / (midpoint - ENDGAME_SCORE.0 as f64)
+        };
+        (early, 1.0 - early - end, end)
+    }
+
+    fn side(board: &Board, color: Color, interp: (f64, f64, f64)) -> Value {
+        let mut value = Value::ZERO;
+        let side = board.color_combined(color);
+        for (idx, piece) in MAT_PIECE_TYPES.iter().enumerate() {
+            let count = board.pieces(*piece).bitand(side).popcnt();
+            value += (INITIAL_VALUES[idx] * Value::from(count)) * interp.0;
+            value += (MIDGAME_VALUES[idx] * Value::from(count)) * interp.1;
+            value += (ENDGAME_VALUES[idx] * Value::from(count)) * interp.2;
+        }
+        for (idx, pt) in POS_PIECE_TYPES.into_iter().enumerate() {
+            for sq in board.pieces(pt).bitand(side) {
+                let (row, col) = sq_pi(sq, color);
+                value += Value::from(
+                    (MG_PESTO_TABLE[idx][row][col] as f64) * (interp.0 + interp.1)
+                        + EG_PESTO_TABLE[idx][row][col] as f64 * interp.2,
+                );
+            }
+        }
+        value
+    }
+
+    pub fn evaluate(board: &Board) -> Value {
+        let interp = interpolate(board);
+        let stm = board.side_to_move();
+        side(board, stm, interp) - side(board, !stm, interp) + TEMPO
+    }
+}
+
+/// the reference evaluation truncates each of (at most) 15 material terms and
+/// 16 piece-square terms per side, so it can be off by that much.
+const TAPER_TOLERANCE: i16 = 31;
+
+/// positions from the openings to bare endgames, to cover every phase
+fn taper_positions() -> Vec<Board> {
+    let mut positions = bench_positions();
+    positions.extend(short_benches());
+    positions.extend(
+        [
+            "8/P7/8/2k2K2/8/8/8/8 w - - 0 1",
+            "8/ppp1pppp/3p4/8/2k2K2/8/8/8 w - - 0 1",
+            "r3k3/pp3ppp/8/8/8/8/PP3PPP/3RK3 b - - 0 1",
+            "r1bqk2r/pp3ppp/8/8/8/8/PP3PPP/R1BQK2R w KQkq - 0 1",
+            "r1bqkb1r/pppp1ppp/2n2n2/8/8/2N2N2/PPPP1PPP/R1BQKB1R w KQkq - 0 1",
+        ]
+        .iter()
+        .map(|fen| Board::from_str(fen).unwrap()),
+    );
+    positions
+}
+
+#[test]
+fn phase_weights_match_reference() {
+    for board in taper_positions() {
+        let interp = interpolate(&board);
+        assert_eq!(interp.early + interp.middle + interp.end, PHASE_SCALE);
+        let (early, middle, end) = reference::interpolate(&board);
+        for (weight, coeff) in [
+            (interp.early, early),
+            (interp.middle, middle),
+            (interp.end, end),
+        ] {
+            let expected = coeff * PHASE_SCALE as f64;
+            assert!(
+                (weight as f64 - expected).abs() < 2.0,
+                "{board}: {interp:?} vs {:?}",
+                (early, middle, end)
+            );
+        }
+    }
+}
+
+#[test]
+fn taper_matches_reference() {
+    for board in taper_positions() {
+        // only the terms that the reference has
+        let tapered = Accumulator::new(&board)
+            .score(board.side_to_move())
+            .taper(interpolate(&board))
+            + TEMPO;
+        let reference = reference::evaluate(&board);
+        assert!(
+            (tapered - reference).abs() <= Value(TAPER_TOLERANCE),
+            "{board}: {tapered:?} vs {reference:?}"
+        );
+    }
+}
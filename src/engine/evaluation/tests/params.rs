@@ -0,0 +1,77 @@
+use crate::evaluation::TEMPO;
+use crate::evaluation::material::PIECE_SCORES;
+use crate::evaluation::params::EvalParams;
+use crate::evaluation::params::Weights;
+use crate::evaluation::score::Score;
+
+#[test]
+fn default_file_round_trips() {
+    let text = EvalParams::DEFAULT.to_string();
+    let params: EvalParams = text.parse().unwrap();
+    assert_eq!(params, EvalParams::DEFAULT);
+    assert_eq!(Weights::DEFAULT.piece_scores, PIECE_SCORES);
+    assert_eq!(Weights::DEFAULT.tempo, TEMPO);
+}
+
+#[test]
+fn missing_keys_keep_their_defaults() {
+    let text = "
+        # only a few changes
+        tempo = 40 # more tempo
+
+        [eg_psqt]
+        knight = [
+            1, 2, 3, 4, 5, 6, 7, 8,
+            0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, -8,
+        ]
+
+        [king_safety]
+        shield = [1, 2, 3]
+
+        [pawns]
+        doubled = [-20, -40]
+
+        [threats]
+        safe_check = [1, 2, 3, 4, 5, 6, 7, 8]
+    ";
+    let params: EvalParams = text.parse().unwrap();
+    assert_eq!(params.tempo, 40);
+    assert_eq!(params.eg_psqt[1][0], [1, 2, 3, 4, 5, 6, 7, 8]);
+    assert_eq!(params.eg_psqt[1][7][7], -8);
+    assert_eq!(params.eg_psqt[0], EvalParams::DEFAULT.eg_psqt[0]);
+    assert_eq!(params.king_safety.shield, [1, 2, 3]);
+    assert_eq!(
+        params.king_safety.storm,
+        EvalParams::DEFAULT.king_safety.storm
+    );
+    assert_eq!(params.pawns.doubled, Score::new(-20, -40));
+    assert_eq!(params.pawns.passed, EvalParams::DEFAULT.pawns.passed);
+    assert_eq!(params.threats.safe_check[3], Score::new(7, 8));
+    assert_eq!(params.threats.hanging, EvalParams::DEFAULT.threats.hanging);
+    assert_eq!(params.midgame_values, EvalParams::DEFAULT.midgame_values);
+    assert_eq!(params.weights().tempo.0, 40);
+}
+
+#[test]
+fn rejects_invalid_files() {
+    for text in [
+        "tempo = 1\nnot a parameter = 2",
+        "tempo",
+        "tempo = fast",
+        "midgame_values = [1, 2, 3]",
+        "[mg_psqt]\nking = [1, 2",
+        "[mg_psqt]\nunicorn = [0]",
+        "[eg_psqt]\npawn = [100000]",
+        "[king_safety]\ntempo = 3",
+        "[pawns]\ndoubled = [-20]",
+        "mobility = [1, 2, 3, 4]",
+    ] {
+        assert!(text.parse::<EvalParams>().is_err(), "{text:?}");
+    }
+}
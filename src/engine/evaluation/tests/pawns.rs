@@ -0,0 +1,146 @@
+use std::str::FromStr;
+
+use chess::BitBoard;
+use chess::Board;
+use chess::Color;
+use chess::EMPTY;
+use chess::Piece;
+use chess::Square;
+
+use crate::evaluation::pawns::PawnEntry;
+use crate::evaluation::pawns::PawnParams;
+use crate::evaluation::pawns::PawnTable;
+use crate::evaluation::pawns::evaluate_pawns;
+use crate::evaluation::pawns::pawn_attacks;
+use crate::evaluation::score::Score;
+use crate::util::bench_positions;
+
+/// the pawn structure of a position given as a FEN
+fn structure(fen: &str) -> PawnEntry {
+    let board = Board::from_str(fen).unwrap();
+    let pawns = board.pieces(Piece::Pawn);
+    PawnEntry::new(
+        [
+            pawns & board.color_combined(Color::White),
+            pawns & board.color_combined(Color::Black),
+        ],
+        &PawnParams::DEFAULT,
+    )
+}
+
+/// a bitboard of the given squares
+fn squares(names: &[&str]) -> BitBoard {
+    names.iter().fold(EMPTY, |bb, name| {
+        bb | BitBoard::from_square(Square::from_str(name).unwrap())
+    })
+}
+
+#[test]
+fn doubled_isolated_passed() {
+    let p = PawnParams::DEFAULT;
+    let entry = structure("4k3/8/8/8/8/P7/P7/4K3 w - - 0 1");
+    // a2 is doubled and isolated, a3 is isolated and passed
+    assert_eq!(entry.score[0], p.doubled + p.isolated * 2 + p.passed[2]);
+    assert_eq!(entry.passed[0], squares(&["a3"]));
+    assert_eq!(entry.score[1], Score::ZERO);
+    assert_eq!(entry.passed[1], EMPTY);
+}
+
+#[test]
+fn backward_connected() {
+    let p = PawnParams::DEFAULT;
+    let entry = structure("4k3/8/3p4/2p5/2P1P3/3P4/8/4K3 w - - 0 1");
+    // d3 can't advance past c5, c4 and e4 are defended by it
+    assert_eq!(entry.score[0], p.backward + p.connected[3] * 2);
+    // d6 can't advance past c4 and e4, c5 is defended by it
+    assert_eq!(entry.score[1], p.backward + p.connected[3]);
+    assert_eq!(entry.passed, [EMPTY, EMPTY]);
+}
+
+#[test]
+fn phalanx_is_connected() {
+    let p = PawnParams::DEFAULT;
+    let entry = structure("4k3/8/8/3PP3/8/8/8/4K3 w - - 0 1");
+    assert_eq!(entry.score[0], (p.connected[4] + p.passed[4]) * 2);
+}
+
+#[test]
+fn pawn_attacks_stay_on_the_board() {
+    assert_eq!(
+        pawn_attacks(squares(&["a2", "h2"]), Color::White),
+        squares(&["b3", "g3"])
+    );
+    assert_eq!(
+        pawn_attacks(squares(&["a7", "h7"]), Color::Black),
+        squares(&["b6", "g6"])
+    );
+}
+
+#[test]
+fn structure_is_symmetric() {
+    for board in bench_positions() {
+        let pawns = board.pieces(Piece::Pawn);
+        let white = pawns & board.color_combined(Color::White);
+        let black = pawns & board.color_combined(Color::Black);
+        let entry = PawnEntry::new([white, black], &PawnParams::DEFAULT);
+        // flip the board vertically and swap the colours
+        let mirrored = PawnEntry::new(
+            [black.reverse_colors(), white.reverse_colors()],
+            &PawnParams::DEFAULT,
+        );
+        assert_eq!(
+            entry.score,
+            [mirrored.score[1], mirrored.score[0]],
+            "{board}"
+        );
+        assert_eq!(
+            entry.passed[0],
+            mirrored.passed[1].reverse_colors(),
+            "{board}"
+        );
+    }
+}
+
+#[test]
+fn table_caches_structures() {
+    let p = PawnParams::DEFAULT;
+    let mut table = PawnTable::new(64);
+    let a = Board::from_str("4k3/8/3p4/2p5/2P1P3/3P4/8/4K3 w - - 0 1").unwrap();
+    // same pawns, different pieces
+    let b = Board::from_str("3qk3/8/3p4/2p5/2P1P3/3P4/8/R3K3 b - - 0 1").unwrap();
+    let first = table.probe(&a, &p);
+    assert_eq!((table.probes, table.hits), (1, 0));
+    assert_eq!(table.probe(&b, &p), first);
+    assert_eq!((table.probes, table.hits), (2, 1));
+    for board in bench_positions() {
+        let entry = table.probe(&board, &p);
+        assert_eq!(entry, PawnEntry::new(entry.pawns, &p));
+    }
+}
+
+#[test]
+fn table_forgets_other_weights() {
+    let mut table = PawnTable::new(64);
+    let board = Board::from_str("4k3/8/3p4/2p5/2P1P3/3P4/8/4K3 w - - 0 1").unwrap();
+    let first = table.probe(&board, &PawnParams::DEFAULT);
+    let p = PawnParams {
+        backward: Score::new(-100, -100),
+        ..PawnParams::DEFAULT
+    };
+    let second = table.probe(&board, &p);
+    assert_eq!(table.hits, 0);
+    assert_eq!(second, PawnEntry::new(first.pawns, &p));
+    assert_ne!(second.score, first.score);
+}
+
+#[test]
+fn kings_escort_passed_pawns() {
+    let eval = |fen| evaluate_pawns(&Board::from_str(fen).unwrap(), &PawnParams::DEFAULT);
+    let near = eval("8/8/8/3P4/2K5/8/8/7k w - - 0 1");
+    let far = eval("k7/8/8/3P4/8/8/8/K7 w - - 0 1");
+    let blocked = eval("8/8/3k4/3P4/8/8/8/K7 w - - 0 1");
+    assert!(near.eg() > far.eg(), "{near:?} {far:?}");
+    assert!(far.eg() > blocked.eg(), "{far:?} {blocked:?}");
+    // only the endgame cares
+    assert_eq!(near.mg(), blocked.mg());
+}
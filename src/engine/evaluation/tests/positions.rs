@@ -6,6 +6,7 @@ use chess::Square;
 
 use crate::evaluation::bitboards::EG_PAWN_TABLE;
 use crate::evaluation::material::interpolate;
+use crate::evaluation::params::Weights;
 use crate::evaluation::position::piece_position_benefit_for_side;
 use crate::evaluation::position::sq_pi;
 use crate::setup::values::Value;
@@ -40,7 +41,7 @@ fn test_correct_indices() {
 fn test_single_white_pawn() {
     let pos = Board::from_str("8/P7/8/2k2K2/8/8/8/8 w - - 0 1").unwrap();
     let interp = interpolate(&pos);
-    let eval = piece_position_benefit_for_side(&pos, Color::White, interp);
+    let eval = piece_position_benefit_for_side(&Weights::DEFAULT, &pos, Color::White).taper(interp);
     assert_eq!(eval, Value(178), "{}", eval);
 }
 
@@ -48,7 +49,7 @@ fn test_single_white_pawn() {
 fn test_single_black_pawn() {
     let pos = Board::from_str("8/8/8/2k2K2/8/8/p7/8 b - - 0 1").unwrap();
     let interp = interpolate(&pos);
-    let eval = piece_position_benefit_for_side(&pos, Color::Black, interp);
+    let eval = piece_position_benefit_for_side(&Weights::DEFAULT, &pos, Color::Black).taper(interp);
     assert_eq!(eval, Value(178), "{}", eval);
 }
 
@@ -65,7 +66,7 @@ fn check_mirror_positions() {
     let interp_b = interpolate(&mirrored);
     assert_eq!(interp_a, interp_b, "{:?} {:?}", interp_a, interp_b);
 
-    let eval = piece_position_benefit_for_side(&position, Color::White, interp_a);
-    let eval_mirrored = piece_position_benefit_for_side(&mirrored, Color::Black, interp_b);
-    assert_eq!(eval, eval_mirrored, "{}", eval);
+    let eval = piece_position_benefit_for_side(&Weights::DEFAULT, &position, Color::White);
+    let eval_mirrored = piece_position_benefit_for_side(&Weights::DEFAULT, &mirrored, Color::Black);
+    assert_eq!(eval, eval_mirrored, "{:?}", eval);
 }
@@ -0,0 +1,56 @@
+use crate::evaluation::Interp;
+use crate::evaluation::PHASE_SCALE;
+use crate::evaluation::score::Score;
+use crate::setup::values::Value;
+
+#[test]
+fn lanes_round_trip() {
+    for (early, mg, eg) in [
+        (0, 0, 0),
+        (1, -1, 1),
+        (-1, -1, -1),
+        (-900, 310, -1050),
+        (32_000, -32_000, 32_000),
+        ((1 << 20) - 1, -(1 << 20), (1 << 21) - 1),
+    ] {
+        let score = Score::phased(early, mg, eg);
+        assert_eq!(
+            (score.early(), score.mg(), score.eg()),
+            (early, mg, eg),
+            "{score:?}"
+        );
+    }
+}
+
+#[test]
+fn lanes_are_independent() {
+    let a = Score::phased(100, -95, 180);
+    let b = Score::new(-310, 200);
+    let sum = a + b;
+    assert_eq!((sum.early(), sum.mg(), sum.eg()), (-210, -405, 380));
+    let diff = a - b;
+    assert_eq!((diff.early(), diff.mg(), diff.eg()), (410, 215, -20));
+    let neg = -a;
+    assert_eq!((neg.early(), neg.mg(), neg.eg()), (-100, 95, -180));
+    let scaled = b * -3;
+    assert_eq!((scaled.early(), scaled.mg(), scaled.eg()), (930, 930, -600));
+
+    let mut acc = Score::ZERO;
+    for _ in 0..8 {
+        acc += a;
+    }
+    acc -= a;
+    assert_eq!(acc, a * 7);
+}
+
+#[test]
+fn taper_blends_phases() {
+    let score = Score::phased(100, 200, -300);
+    let only = |early, middle, end| Interp { early, middle, end };
+    assert_eq!(score.taper(only(PHASE_SCALE, 0, 0)), Value(100));
+    assert_eq!(score.taper(only(0, PHASE_SCALE, 0)), Value(200));
+    assert_eq!(score.taper(only(0, 0, PHASE_SCALE)), Value(-300));
+    let half = PHASE_SCALE / 2;
+    assert_eq!(score.taper(only(half, half, 0)), Value(150));
+    assert_eq!(score.taper(only(0, half, half)), Value(-50));
+}
@@ -0,0 +1,57 @@
+use std::str::FromStr;
+
+use super::*;
+use crate::util::bench_positions;
+
+fn board(fen: &str) -> Board {
+    Board::from_str(fen).unwrap()
+}
+
+#[test]
+fn flipping_colours() {
+    assert_eq!(
+        flip_colours(&board(
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w Kq f6 0 3"
+        )),
+        board("rnbqkbnr/pppp1ppp/8/8/3PpP2/8/PPP1P1PP/RNBQKBNR b Qk f3 0 1")
+    );
+    for board in epd_positions() {
+        assert_eq!(flip_colours(&flip_colours(&board)), board);
+    }
+}
+
+#[test]
+fn mirroring() {
+    assert_eq!(
+        mirror(&board("8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1")),
+        Some(board("8/8/8/5k2/3pP3/8/8/3K4 b - e3 0 1"))
+    );
+    assert_eq!(mirror(&board("r3k2r/8/8/8/8/8/8/R3K2R w K - 0 1")), None);
+    for board in epd_positions().iter().filter_map(mirror) {
+        assert_eq!(mirror(&mirror(&board).unwrap()), Some(board));
+    }
+}
+
+#[test]
+fn default_tables_are_not_mirrored() {
+    assert!(!is_mirrored(&EvalParams::DEFAULT));
+    let mirrored = mirrored_params(&EvalParams::DEFAULT);
+    assert!(is_mirrored(&mirrored));
+    assert_eq!(mirrored_params(&mirrored), mirrored);
+}
+
+#[test]
+fn evaluation_is_symmetric() {
+    let mut positions = bench_positions();
+    positions.extend(epd_positions());
+    let found = check_all(&positions);
+    assert!(
+        found.is_empty(),
+        "{}",
+        found
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+}
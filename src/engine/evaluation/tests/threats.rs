@@ -0,0 +1,68 @@
+use std::str::FromStr;
+
+use chess::Board;
+use chess::Color;
+use chess::Piece;
+
+use crate::evaluation::attacks::AttackMap;
+use crate::evaluation::attacks::MOBILITY;
+use crate::evaluation::score::Score;
+use crate::evaluation::threats::ThreatParams;
+use crate::evaluation::threats::threats;
+
+/// the threats of `color` in the position given as a FEN
+fn threat(fen: &str, color: Color) -> Score {
+    let board = Board::from_str(fen).unwrap();
+    threats(
+        &board,
+        color,
+        &AttackMap::new(&board, &MOBILITY),
+        &ThreatParams::DEFAULT,
+    )
+}
+
+#[test]
+fn no_threats_at_the_start() {
+    let fen = Board::default().to_string();
+    assert_eq!(threat(&fen, Color::White), Score::ZERO);
+    assert_eq!(threat(&fen, Color::Black), Score::ZERO);
+}
+
+#[test]
+fn pawn_attacks_a_piece() {
+    let p = ThreatParams::DEFAULT;
+    let fen = "4k3/8/8/3n4/4P3/8/8/4K3 w - - 0 1";
+    assert_eq!(threat(fen, Color::White), p.by_safe_pawn + p.hanging);
+}
+
+#[test]
+fn pawn_push_threat() {
+    let p = ThreatParams::DEFAULT;
+    let fen = "4k3/8/8/3n4/8/4P3/8/4K3 w - - 0 1";
+    assert_eq!(threat(fen, Color::White), p.pawn_push);
+    // meanwhile the knight attacks the undefended pawn
+    let expected = p.by_minor[Piece::Pawn.to_index()] + p.hanging;
+    assert_eq!(threat(fen, Color::Black), expected);
+}
+
+#[test]
+fn rook_attacks_a_hanging_queen() {
+    let p = ThreatParams::DEFAULT;
+    let fen = "4k3/8/8/8/q7/8/8/R3K3 w - - 0 1";
+    let expected = p.by_rook[Piece::Queen.to_index()] + p.hanging;
+    assert_eq!(threat(fen, Color::White), expected);
+    // defending the queen takes away the hanging bonus
+    let fen = "4k3/8/8/1p6/q7/8/8/R3K3 w - - 0 1";
+    assert_eq!(threat(fen, Color::White), Score::ZERO);
+}
+
+#[test]
+fn safe_checks() {
+    let p = ThreatParams::DEFAULT;
+    // a8, b8 and c8 are out of reach of the black king
+    let fen = "4k3/8/8/8/8/8/8/R3K3 w - - 0 1";
+    assert_eq!(threat(fen, Color::White), p.safe_check[2]);
+    // with a bishop on f8, the only check square left is d8, next to the king
+    let fen = "4kb2/8/8/8/8/8/8/3RK3 w - - 0 1";
+    assert_eq!(threat(fen, Color::White), Score::ZERO);
+}
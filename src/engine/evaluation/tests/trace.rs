@@ -0,0 +1,71 @@
+use std::str::FromStr;
+
+use chess::Board;
+use chess::Color;
+use chess::Square;
+
+use crate::evaluation::TEMPO;
+use crate::evaluation::endgame::scale;
+use crate::evaluation::score::Score;
+use crate::evaluation::trace::trace_board;
+use crate::setup::values::Value;
+use crate::util::bench_positions;
+use crate::util::print_squares;
+
+#[test]
+fn terms_add_up_to_the_evaluation() {
+    for board in bench_positions() {
+        let trace = trace_board(&board);
+        if trace.special.is_some() {
+            continue;
+        }
+        // everything but the tempo, which is added after tapering
+        let (tempo, terms) = trace.terms.split_last().unwrap();
+        assert_eq!(tempo.name, "tempo");
+        let total = terms.iter().fold(Score::ZERO, |a, t| a + t.total());
+        let total = if trace.stm == Color::White {
+            total
+        } else {
+            -total
+        };
+        assert_eq!(
+            scale(total, trace.scale).taper(trace.interp) + TEMPO,
+            trace.value,
+            "{board}"
+        );
+    }
+}
+
+#[test]
+fn startpos_is_symmetric() {
+    let trace = trace_board(&Board::default());
+    for term in &trace.terms[..trace.terms.len() - 1] {
+        assert_eq!(term.total(), Score::ZERO, "{}", term.name);
+    }
+    assert_eq!(trace.value, TEMPO);
+    assert_eq!(trace.special, None);
+    let (e1, e8) = (Square::E1.to_index(), Square::E8.to_index());
+    assert_eq!(trace.squares[e1], trace.squares[e8].map(|v| -v));
+    assert_eq!(trace.squares[Square::E4.to_index()], None);
+
+    let map = trace.heat_map();
+    assert_eq!(map.lines().count(), 9);
+    // laid out like the board itself, see [`crate::util::Print`]
+    assert!(map.starts_with('8'));
+    assert_eq!(
+        map.lines().last(),
+        print_squares(6, |_| String::new()).lines().last()
+    );
+    assert!(trace.to_string().contains("phase weights"));
+}
+
+#[test]
+fn reports_why_terms_are_ignored() {
+    let trace = trace_board(&Board::from_str("4k3/8/8/8/8/8/8/4KB2 w - - 0 1").unwrap());
+    assert_eq!(trace.special, Some("known draw"));
+    assert_eq!(trace.value, Value::DRAW);
+
+    let trace = trace_board(&Board::from_str("4k3/8/8/8/8/8/8/3QK3 b - - 0 1").unwrap());
+    assert_eq!(trace.special, Some("mop-up"));
+    assert!(trace.white_value() > Value::KNOWN_WIN);
+}
@@ -0,0 +1,155 @@
+//! threats: attacks on enemy pieces, pawn push threats and safe checks
+use chess::BitBoard;
+use chess::Board;
+use chess::Color;
+use chess::EMPTY;
+use chess::Piece;
+use chess::Rank;
+use chess::Square;
+use chess::get_rank;
+
+use crate::evaluation::attacks::AttackMap;
+use crate::evaluation::attacks::MOBILE_PIECES;
+use crate::evaluation::attacks::piece_attacks;
+use crate::evaluation::pawns::pawn_attacks;
+use crate::evaluation::score::Score;
+
+/// The weights of the threat terms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThreatParams {
+    /// bonus for a pawn, that can't be taken for free, attacking an enemy
+    /// piece
+    pub by_safe_pawn: Score,
+    /// bonus for a knight or bishop attacking an enemy piece, by
+    /// [`Piece::to_index`] of the attacked piece
+    pub by_minor: [Score; 6],
+    /// bonus for a rook attacking a weak enemy piece, by [`Piece::to_index`]
+    /// of the attacked piece
+    pub by_rook: [Score; 6],
+    /// bonus for every weak enemy piece that is attacked and not defended, or
+    /// attacked twice
+    pub hanging: Score,
+    /// bonus for every enemy piece that a pawn could safely move up to attack
+    pub pawn_push: Score,
+    /// bonus for being able to give a check that can't be answered by taking
+    /// the checking piece, for the pieces in [`MOBILE_PIECES`]
+    pub safe_check: [Score; 4],
+}
+
+impl ThreatParams {
+    /// the default weights
+    pub const DEFAULT: Self = Self {
+        by_safe_pawn: Score::new(110, 60),
+        by_minor: [
+            Score::new(3, 21),
+            Score::new(37, 27),
+            Score::new(37, 27),
+            Score::new(50, 36),
+            Score::new(57, 77),
+            Score::ZERO,
+        ],
+        by_rook: [
+            Score::new(2, 28),
+            Score::new(24, 44),
+            Score::new(26, 38),
+            Score::ZERO,
+            Score::new(50, 90),
+            Score::ZERO,
+        ],
+        hanging: Score::new(45, 25),
+        pawn_push: Score::new(31, 23),
+        safe_check: [
+            Score::new(35, 5),
+            Score::new(20, 5),
+            Score::new(35, 5),
+            Score::new(25, 5),
+        ],
+    };
+}
+
+impl Default for ThreatParams {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// the threats of `color`'s pieces against its opponent
+pub fn threats(board: &Board, color: Color, attacks: &AttackMap, params: &ThreatParams) -> Score {
+    let (us, them) = (color.to_index(), (!color).to_index());
+    let occupied = *board.combined();
+    let ours = *board.color_combined(color);
+    let theirs = *board.color_combined(!color);
+    let pawns = *board.pieces(Piece::Pawn);
+    let non_pawn_enemies = theirs & !pawns & !board.pieces(Piece::King);
+
+    let mut score = Score::ZERO;
+
+    // squares defended by an enemy pawn, or more often by the enemy than by us
+    let strongly_protected =
+        attacks.attacked_by(!color, Piece::Pawn) | (attacks.double[them] & !attacks.double[us]);
+    let defended = non_pawn_enemies & strongly_protected;
+    let weak = theirs & !strongly_protected & attacks.all[us];
+
+    if defended | weak != EMPTY {
+        let minors =
+            attacks.attacked_by(color, Piece::Knight) | attacks.attacked_by(color, Piece::Bishop);
+        for sq in (defended | weak) & minors {
+            score += piece_threat(board, sq, &params.by_minor);
+        }
+        for sq in weak & attacks.attacked_by(color, Piece::Rook) {
+            score += piece_threat(board, sq, &params.by_rook);
+        }
+        let hanging = weak & (!attacks.all[them] | (non_pawn_enemies & attacks.double[us]));
+        score += params.hanging * hanging.popcnt() as i32;
+    }
+
+    // pawns that are defended or not attacked, attacking pieces
+    let safe = !attacks.all[them] | attacks.all[us];
+    let safe_pawns = pawns & ours & safe;
+    let pawn_hits = pawn_attacks(safe_pawns, color) & non_pawn_enemies;
+    score += params.by_safe_pawn * pawn_hits.popcnt() as i32;
+
+    // pawns that can move up to a safe square and attack a piece from there
+    let third = get_rank(match color {
+        Color::White => Rank::Third,
+        Color::Black => Rank::Sixth,
+    });
+    let single = forward(pawns & ours, color) & !occupied;
+    let double = forward(single & third, color) & !occupied;
+    let pushes = (single | double) & !attacks.attacked_by(!color, Piece::Pawn) & safe;
+    let push_hits = pawn_attacks(pushes, color) & non_pawn_enemies;
+    score += params.pawn_push * push_hits.popcnt() as i32;
+
+    // checks from squares the enemy doesn't attack
+    let king = board.king_square(!color);
+    let safe_squares = !ours & !attacks.all[them];
+    for (idx, piece) in MOBILE_PIECES.into_iter().enumerate() {
+        let checks = piece_attacks(piece, king, occupied);
+        if checks & attacks.attacked_by(color, piece) & safe_squares != EMPTY {
+            score += params.safe_check[idx];
+        }
+    }
+
+    score
+}
+
+/// the entry of `values` for the piece on `sq`
+#[inline(always)]
+fn piece_threat(board: &Board, sq: Square, values: &[Score; 6]) -> Score {
+    board
+        .piece_on(sq)
+        .map_or(Score::ZERO, |piece| values[piece.to_index()])
+}
+
+/// the given squares, moved one rank up from `color`'s point of view
+#[inline(always)]
+fn forward(squares: BitBoard, color: Color) -> BitBoard {
+    BitBoard(match color {
+        Color::White => squares.0 << 8,
+        Color::Black => squares.0 >> 8,
+    })
+}
+
+#[cfg(test)]
+#[path = "tests/threats.rs"]
+mod tests;
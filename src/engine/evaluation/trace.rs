@@ -0,0 +1,269 @@
+//! a breakdown of [`evaluate`] into its terms, to see why a position got the
+//! score it did
+//!
+//! [`evaluate`]: crate::evaluation::evaluate
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use chess::Board;
+use chess::Color;
+use chess::EMPTY;
+use chess::MoveGen;
+use chess::Piece;
+
+use crate::evaluation::Interp;
+use crate::evaluation::PHASE_SCALE;
+use crate::evaluation::activity::piece_activity;
+use crate::evaluation::attacks::AttackMap;
+use crate::evaluation::bitboards::POS_PIECE_TYPES;
+use crate::evaluation::endgame::is_known_draw;
+use crate::evaluation::endgame::mop_up;
+use crate::evaluation::endgame::scale_factor;
+use crate::evaluation::evaluate;
+use crate::evaluation::king_safety::king_safety;
+use crate::evaluation::material::material;
+use crate::evaluation::params::with_weights;
+use crate::evaluation::pawns::PawnEntry;
+use crate::evaluation::pawns::passed_king_proximity;
+use crate::evaluation::position::piece_square;
+use crate::evaluation::score::Score;
+use crate::evaluation::threats::threats;
+use crate::position::Position;
+use crate::setup::values::Value;
+use crate::util::print_squares;
+
+/// one term of the evaluation, for both sides
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Term {
+    /// the name of the term
+    pub name: String,
+    /// the score of white, indexed by [`Color::to_index`]
+    pub scores: [Score; 2],
+}
+
+impl Term {
+    /// white's score minus black's
+    pub fn total(&self) -> Score {
+        self.scores[0] - self.scores[1]
+    }
+}
+
+/// The breakdown of the evaluation of a position, as computed by [`trace`].
+/// scores are from white's point of view, unless stated otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalTrace {
+    /// the side to move
+    pub stm: Color,
+    /// the weights of the game phases
+    pub interp: Interp,
+    /// every term of the evaluation, in the order they are added up
+    pub terms: Vec<Term>,
+    /// the endgame scale factor, out of [`SCALE_NORMAL`]
+    ///
+    /// [`SCALE_NORMAL`]: crate::evaluation::endgame::SCALE_NORMAL
+    pub scale: i32,
+    /// why the terms were ignored, if they were: mate, stalemate, a known
+    /// draw, or a mop-up
+    pub special: Option<&'static str>,
+    /// the material and piece-square value of the piece on every square, by
+    /// [`Square::to_index`]
+    pub squares: [Option<Value>; 64],
+    /// the result of [`evaluate`], from the side to move's point of view
+    pub value: Value,
+}
+
+/// break the evaluation of `pos` down into its terms
+pub fn trace(pos: &Position) -> EvalTrace {
+    let board = &pos.chessboard;
+    let stm = board.side_to_move();
+    let interp = pos.accumulator.interp();
+    let both = |f: &dyn Fn(Color) -> Score| [f(Color::White), f(Color::Black)];
+    let weights = with_weights(|w| *w);
+
+    let mut terms = vec![Term {
+        name: "material".to_string(),
+        scores: both(&|color| material(&weights, board, color)),
+    }];
+    for piece in POS_PIECE_TYPES {
+        terms.push(Term {
+            name: format!("psqt {piece:?}").to_lowercase(),
+            scores: both(&|color| {
+                let mut score = Score::ZERO;
+                for sq in board.pieces(piece) & board.color_combined(color) {
+                    score += piece_square(&weights, piece, color, sq);
+                }
+                score
+            }),
+        });
+    }
+
+    let pawns = board.pieces(Piece::Pawn);
+    let entry = PawnEntry::new(
+        [
+            pawns & board.color_combined(Color::White),
+            pawns & board.color_combined(Color::Black),
+        ],
+        &weights.pawns,
+    );
+    let attacks = AttackMap::new(board, &weights.mobility);
+    terms.extend([
+        Term {
+            name: "pawns".to_string(),
+            scores: both(&|color| {
+                let idx = color.to_index();
+                entry.score[idx]
+                    + passed_king_proximity(board, color, entry.passed[idx], &weights.pawns)
+            }),
+        },
+        Term {
+            name: "mobility".to_string(),
+            scores: attacks.mobility,
+        },
+        Term {
+            name: "activity".to_string(),
+            scores: both(&|color| piece_activity(board, color, &attacks, &weights.activity)),
+        },
+        Term {
+            name: "threats".to_string(),
+            scores: both(&|color| threats(board, color, &attacks, &weights.threats)),
+        },
+        Term {
+            name: "king safety".to_string(),
+            scores: both(&|color| king_safety(board, color, &attacks, &weights.king_safety)),
+        },
+    ]);
+
+    let total = terms
+        .iter()
+        .map(Term::total)
+        .fold(Score::ZERO, |a, b| a + b);
+    let relative = if stm == Color::White { total } else { -total };
+    terms.push(Term {
+        name: "tempo".to_string(),
+        scores: both(&|color| {
+            let tempo = if color == stm {
+                weights.tempo.0 as i32
+            } else {
+                0
+            };
+            Score::new(tempo, tempo)
+        }),
+    });
+
+    let out_of_moves = MoveGen::new_legal(board).len() == 0;
+    let special = if out_of_moves {
+        Some(if *board.checkers() == EMPTY {
+            "stalemate"
+        } else {
+            "checkmate"
+        })
+    } else if is_known_draw(board) {
+        Some("known draw")
+    } else if mop_up(board, stm, &weights.endgame).is_some() {
+        Some("mop-up")
+    } else {
+        None
+    };
+
+    let mut squares = [None; 64];
+    for sq in *board.combined() {
+        let (Some(piece), Some(color)) = (board.piece_on(sq), board.color_on(sq)) else {
+            continue;
+        };
+        let mut score = piece_square(&weights, piece, color, sq);
+        if let Some(material) = weights.piece_scores.get(piece.to_index()) {
+            score += *material;
+        }
+        let value = score.taper(interp);
+        squares[sq.to_index()] = Some(if color == Color::White { value } else { -value });
+    }
+
+    EvalTrace {
+        stm,
+        interp,
+        terms,
+        scale: scale_factor(board, stm, relative, &weights.endgame),
+        special,
+        squares,
+        value: evaluate(pos, out_of_moves),
+    }
+}
+
+impl EvalTrace {
+    /// the evaluation from white's point of view
+    pub fn white_value(&self) -> Value {
+        if self.stm == Color::White {
+            self.value
+        } else {
+            -self.value
+        }
+    }
+
+    /// the material and piece-square values of the pieces as a board
+    pub fn heat_map(&self) -> String {
+        print_squares(6, |sq| match self.squares[sq.to_index()] {
+            Some(value) => value.0.to_string(),
+            None => ".".to_string(),
+        })
+    }
+}
+
+/// the three phases of a score
+fn phases(score: Score) -> String {
+    format!("{:>6} {:>6} {:>6}", score.early(), score.mg(), score.eg())
+}
+
+impl Display for EvalTrace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:>12} | {:^20} | {:^20} | {:^20} | {:>6}",
+            "term", "white", "black", "total", "cp"
+        )?;
+        writeln!(
+            f,
+            "{:>12} | {:^20} | {:^20} | {:^20} |",
+            "", "early mg eg", "early mg eg", "early mg eg"
+        )?;
+        writeln!(f, "{}", "-".repeat(91))?;
+        for term in &self.terms {
+            writeln!(
+                f,
+                "{:>12} | {} | {} | {} | {:>6}",
+                term.name,
+                phases(term.scores[0]),
+                phases(term.scores[1]),
+                phases(term.total()),
+                term.total().taper(self.interp).0,
+            )?;
+        }
+        writeln!(f, "{}", "-".repeat(91))?;
+        writeln!(
+            f,
+            "phase weights: early {}, middle {}, end {} (of {})",
+            self.interp.early, self.interp.middle, self.interp.end, PHASE_SCALE
+        )?;
+        writeln!(f, "endgame scale factor: {}", self.scale)?;
+        if let Some(special) = self.special {
+            writeln!(f, "terms ignored: {special}")?;
+        }
+        writeln!(
+            f,
+            "evaluation: {} for {:?} to move, {} for white",
+            self.value.0,
+            self.stm,
+            self.white_value().0
+        )?;
+        writeln!(f, "\npiece values (material and psqt, white's view):")?;
+        write!(f, "{}", self.heat_map())
+    }
+}
+
+/// same as [`trace`], but for a [`Board`]
+pub fn trace_board(board: &Board) -> EvalTrace {
+    trace(&Position::from(*board))
+}
+
+#[cfg(test)]
+#[path = "tests/trace.rs"]
+mod tests;
@@ -12,10 +12,18 @@ pub mod search;
 pub mod setup;
 pub mod timing;
 pub mod transposition_table;
+pub mod tunable;
+pub mod tuner;
 pub mod uci;
 pub mod util;
 
 use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
 use std::sync::atomic::Ordering;
 use std::thread;
 use std::time::Duration;
@@ -28,6 +36,14 @@ use lockfree::channel::RecvErr;
 use log::info;
 use log::trace;
 
+use crate::evaluation::accumulator::Accumulator;
+use crate::evaluation::cache::CacheStats;
+use crate::evaluation::cache::DEFAULT_EVAL_CACHE_SIZE;
+use crate::evaluation::cache::EvalCache;
+use crate::evaluation::evaluator::DefaultEvaluator;
+use crate::evaluation::evaluator::Evaluator;
+use crate::evaluation::params::EvalParams;
+use crate::evaluation::params::set_params;
 use crate::position::Position;
 use crate::search::Message;
 use crate::search::SEARCH_TO;
@@ -39,39 +55,83 @@ use crate::transposition_table::TT;
 use crate::transposition_table::TranspositionTable;
 
 /// this is why you're here, right?
+///
+/// generic over its evaluation (see [`Evaluator`]), which is the usual one
+/// unless the engine is created with [`Engine::with_evaluator`].
 #[derive(Debug)]
-pub struct Engine {
+pub struct Engine<V: Evaluator = DefaultEvaluator> {
     /// the board the engine will think on
     pub board: Position,
     /// the transposition table
     pub table: TT,
     /// recently played positions. used to detect 3-fold repetition.
     pub history: VecDeque<Position>,
+    /// the evaluation the search uses
+    pub evaluator: V,
 }
 
 impl Engine {
     /// create a new engine!
     pub fn new() -> Result<Self> {
+        Self::with_evaluator(DefaultEvaluator::with_cache(DEFAULT_EVAL_CACHE_SIZE))
+    }
+
+    /// resize the evaluation cache (see [`EvalCache`]), or stop caching
+    /// evaluations with a size of 0, returning the new size in bytes. the
+    /// cached evaluations are only lost if the size changes.
+    pub fn resize_eval_cache(&mut self, bytes: usize) -> usize {
+        if bytes == 0 {
+            self.evaluator.cache = None;
+            return 0;
+        }
+        let cache = EvalCache::new(bytes);
+        let size = cache.size();
+        if self
+            .evaluator
+            .cache
+            .as_ref()
+            .is_none_or(|c| c.size() != size)
+        {
+            self.evaluator.cache = Some(Arc::new(cache));
+        }
+        size
+    }
+}
+
+impl<V: Evaluator> Engine<V> {
+    /// create a new engine that searches with `evaluator`
+    pub fn with_evaluator(evaluator: V) -> Result<Self> {
         info!("creating engine at version {}", env!("CARGO_PKG_VERSION"));
 
         Ok(Self {
             board: Default::default(),
             table: TT::new(),
             history: VecDeque::new(),
+            evaluator,
         })
     }
 
+    /// start the game from `pos`, forgetting the moves that led to the
+    /// previous position
+    pub fn set_position(&mut self, pos: Position) {
+        self.history.clear();
+        self.board = pos.clone();
+        self.log_position(pos);
+    }
+
     /// register a new move that has been played in the game.
     pub fn make_move(&mut self, mv: ChessMove) {
-        self.board = self.board.make_move(mv);
+        self.board = self.evaluator.make_move(&self.board, mv);
         self.log_position(self.board.clone());
     }
 
     /// add a new position to the engine history, preserving only enough
-    /// positions to detect threefold repetition.
+    /// positions to detect threefold repetition: the ones since the last
+    /// capture or pawn move, which can't be repeated.
     pub fn log_position(&mut self, pos: Position) {
+        let repeatable = pos.halfmove_clock as usize + 1;
         self.history.push_front(pos);
-        self.history.truncate(6);
+        self.history.truncate(repeatable);
     }
 
     /// set the global [`SEARCHING`]
@@ -111,9 +171,89 @@ impl Engine {
             .resize(size))
     }
 
+    /// the counters of the evaluation cache, if evaluations are cached
+    pub fn eval_cache_stats(&self) -> Option<CacheStats> {
+        self.evaluator.cache_stats()
+    }
+
+    /// empty the transposition table, keeping its capacity
+    pub fn clear_table(&mut self) -> Result<()> {
+        self.table
+            .get()
+            .write()
+            .map_err(|e| anyhow!("table lock error: {e}"))?
+            .clear();
+        Ok(())
+    }
+
+    /// save the transposition table to a file, returning the number of
+    /// entries saved. see [`transposition_table::persist`] for the format.
+    pub fn save_table(&self, path: impl AsRef<Path>) -> Result<usize> {
+        let mut w = BufWriter::new(File::create(path)?);
+        let saved = self
+            .table
+            .get()
+            .read()
+            .map_err(|e| anyhow!("table lock error: {e}"))?
+            .save(&mut w)?;
+        w.flush()?;
+        Ok(saved)
+    }
+
+    /// load a transposition table saved with [`Engine::save_table`], returning
+    /// the number of entries loaded. the current table keeps its size, and
+    /// entries from a table of a different size are rehashed into it.
+    pub fn load_table(&mut self, path: impl AsRef<Path>) -> Result<usize> {
+        let mut r = BufReader::new(File::open(path)?);
+        self.table
+            .get()
+            .write()
+            .map_err(|e| anyhow!("table lock error: {e}"))?
+            .load(&mut r)
+    }
+
+    /// forget everything about the previous game: the transposition table,
+    /// the evaluation cache, the position history, the board, and any stale
+    /// search limits.
+    pub fn new_game(&mut self) -> Result<()> {
+        self.clear_table()?;
+        self.evaluator.clear();
+        self.set_position(Default::default());
+        let _ = SEARCH_UNTIL
+            .write()
+            .map_err(|e| anyhow!("SEARCH_UNTIL [new_game,write] lock error: {e}"))?
+            .take();
+        self.set_search_to(Depth::ZERO);
+        Ok(())
+    }
+
+    /// replace the parameters of the evaluation (see [`evaluation::params`]).
+    /// the board and history are re-evaluated with them, and the
+    /// transposition table is cleared, as its evaluations are out of date.
+    pub fn set_params(&mut self, params: EvalParams) -> Result<()> {
+        set_params(params);
+        for pos in std::iter::once(&mut self.board).chain(self.history.iter_mut()) {
+            pos.accumulator = Accumulator::new(&pos.chessboard);
+        }
+        self.evaluator.clear();
+        self.clear_table()
+    }
+
+    /// replace the network of the evaluation (see [`evaluation::nnue`]), or
+    /// unload it with `None`
+    #[cfg(feature = "nnue")]
+    pub fn set_network(&mut self, net: Option<evaluation::nnue::Network>) -> Result<()> {
+        evaluation::nnue::set_network(net);
+        for pos in std::iter::once(&mut self.board).chain(self.history.iter_mut()) {
+            pos.nnue = evaluation::nnue::accumulator::NnueAccumulator::new(&pos.chessboard);
+        }
+        self.evaluator.clear();
+        self.clear_table()
+    }
+
     /// # begin setting up the engine
     /// 1. load opening book
-    /// 2. load parameters from file
+    /// 2. load parameters from file (see [`Engine::set_params`])
     /// 3. load endgame tablebases
     /// 4. ...
     pub fn setup(&mut self) -> Result<()> {
@@ -150,6 +290,8 @@ impl Engine {
                         "depth: {}, score: {}, nodes: {}",
                         si.depth.0, si.score, si.nodes
                     ),
+                    Message::TableStats(stats) => trace!("tt {stats}"),
+                    Message::EvalCacheStats(stats) => trace!("eval cache {stats}"),
                 },
                 Err(RecvErr::NoMessage) => {
                     thread::sleep(Duration::from_millis(50));
@@ -7,9 +7,21 @@ use anyhow::bail;
 use vampirc_uci::UciOptionConfig;
 
 use crate::debug::DebugLevel;
+use crate::evaluation::cache::DEFAULT_EVAL_CACHE_SIZE;
 use crate::optlog;
+use crate::search::DEFAULT_CONTEMPT;
+use crate::search::MAX_CONTEMPT;
 use crate::search::SEARCH_THREADS;
+use crate::search::skill::DEFAULT_ELO;
+use crate::search::skill::MAX_ELO;
+use crate::search::skill::MAX_SKILL_LEVEL;
+use crate::search::skill::MIN_ELO;
+use crate::setup::values::Value;
 use crate::transposition_table::DEFAULT_TABLE_SIZE;
+use crate::transposition_table::persist::DEFAULT_HASH_FILE;
+use crate::tunable::TUNABLES;
+#[cfg(feature = "tune")]
+use crate::tunable::find;
 
 /// Read the global options for the engine, attempting to go through the
 /// [`RwLock`] of [`OPTS`] to do so
@@ -95,12 +107,40 @@ pub struct Opts {
     pub use_tt: bool,
     /// should the search use move ordering?
     pub use_mo: bool,
+    /// should the search prune nodes whose static evaluation is far above
+    /// beta (reverse futility pruning)? only with [`Opts::use_ab`],
+    /// and off by default.
+    pub use_rfp: bool,
     /// should the engine ponder?
     pub ponder: bool,
     /// how big should the transposition table be? value in **bytes**
     pub hash_size: usize,
+    /// how big should the evaluation cache be? value in **bytes**, 0 to not
+    /// cache evaluations (see [`EvalCache`])
+    ///
+    /// [`EvalCache`]: crate::evaluation::cache::EvalCache
+    pub eval_cache_size: usize,
     /// how many threads should the search use?
     pub threads: usize,
+    /// should the search evaluate with the network (with the `nnue` feature,
+    /// when one is loaded)?
+    pub use_nnue: bool,
+    /// how much worse than an even position a draw is for the engine, in
+    /// centipawns. negative values make the engine seek draws.
+    pub contempt: i16,
+    /// is the engine analysing, rather than playing? contempt is ignored
+    /// while analysing, draws are scored as even.
+    pub analyse_mode: bool,
+    /// how well the engine plays, from 0 up to [`MAX_SKILL_LEVEL`] (full
+    /// strength), unless its strength is limited to [`Opts::elo`] (see
+    /// [`Skill`])
+    ///
+    /// [`Skill`]: crate::search::skill::Skill
+    pub skill_level: u8,
+    /// should the engine play at the strength of [`Opts::elo`]?
+    pub limit_strength: bool,
+    /// the rating the engine plays at, when its strength is limited
+    pub elo: u16,
 }
 
 impl Opts {
@@ -134,9 +174,17 @@ impl Opts {
             use_pv: false,
             use_tt: false,
             use_mo: false,
+            use_rfp: false,
             ponder: false,
             hash_size: DEFAULT_TABLE_SIZE,
+            eval_cache_size: DEFAULT_EVAL_CACHE_SIZE,
             threads: 1,
+            use_nnue: false,
+            contempt: DEFAULT_CONTEMPT,
+            analyse_mode: false,
+            skill_level: MAX_SKILL_LEVEL,
+            limit_strength: false,
+            elo: DEFAULT_ELO,
         }
     }
 
@@ -157,15 +205,23 @@ impl Opts {
             use_pv: true,
             use_tt: true,
             use_mo: true,
+            use_rfp: false,
             ponder: false,
             hash_size: 32 * 1024,
+            eval_cache_size: 0,
             threads: 1,
+            use_nnue: false,
+            contempt: 0,
+            analyse_mode: false,
+            skill_level: MAX_SKILL_LEVEL,
+            limit_strength: false,
+            elo: DEFAULT_ELO,
         }
     }
 
     /// Register the UCI options for the engine
     pub fn register_options() -> Vec<UciOptionConfig> {
-        vec![
+        let mut options = vec![
             UciOptionConfig::Check {
                 name: "use_ab".to_string(),
                 default: Some(true),
@@ -182,6 +238,10 @@ impl Opts {
                 name: "use_mo".to_string(),
                 default: Some(true),
             },
+            UciOptionConfig::Check {
+                name: "use_rfp".to_string(),
+                default: Some(false),
+            },
             UciOptionConfig::Check {
                 name: "bench_log".to_string(),
                 default: Some(false),
@@ -222,25 +282,93 @@ impl Opts {
             },
             UciOptionConfig::Spin {
                 name: "hash".to_string(),
-                default: Some((DEFAULT_TABLE_SIZE / (1024 * 1024)).max(1) as i64),
+                default: Some(DEFAULT_TABLE_SIZE.div_ceil(1024 * 1024) as i64),
                 min: Some(0),
                 max: Some(4096),
             },
+            UciOptionConfig::Spin {
+                name: "Eval Cache".to_string(),
+                default: Some(DEFAULT_EVAL_CACHE_SIZE.div_ceil(1024 * 1024) as i64),
+                min: Some(0),
+                max: Some(1024),
+            },
             UciOptionConfig::Spin {
                 name: "threads".to_string(),
                 default: Some(SEARCH_THREADS as i64),
                 min: Some(1),
                 max: Some(1024),
             },
-        ]
+            UciOptionConfig::Spin {
+                name: "Contempt".to_string(),
+                default: Some(DEFAULT_CONTEMPT as i64),
+                min: Some(-MAX_CONTEMPT as i64),
+                max: Some(MAX_CONTEMPT as i64),
+            },
+            UciOptionConfig::Check {
+                name: "UCI_AnalyseMode".to_string(),
+                default: Some(false),
+            },
+            UciOptionConfig::Spin {
+                name: "Skill Level".to_string(),
+                default: Some(MAX_SKILL_LEVEL as i64),
+                min: Some(0),
+                max: Some(MAX_SKILL_LEVEL as i64),
+            },
+            UciOptionConfig::Check {
+                name: "UCI_LimitStrength".to_string(),
+                default: Some(false),
+            },
+            UciOptionConfig::Spin {
+                name: "UCI_Elo".to_string(),
+                default: Some(DEFAULT_ELO as i64),
+                min: Some(MIN_ELO as i64),
+                max: Some(MAX_ELO as i64),
+            },
+            UciOptionConfig::Button {
+                name: "Clear Hash".to_string(),
+            },
+            UciOptionConfig::String {
+                name: "Hash File".to_string(),
+                default: Some(DEFAULT_HASH_FILE.to_string()),
+            },
+            UciOptionConfig::Button {
+                name: "Save Hash".to_string(),
+            },
+            UciOptionConfig::Button {
+                name: "Load Hash".to_string(),
+            },
+            UciOptionConfig::String {
+                name: "Eval Params".to_string(),
+                default: Some(String::new()),
+            },
+        ];
+        // the network can only be chosen when it is compiled in
+        if cfg!(feature = "nnue") {
+            options.extend([
+                UciOptionConfig::Check {
+                    name: "Use NNUE".to_string(),
+                    default: Some(false),
+                },
+                UciOptionConfig::String {
+                    name: "EvalFile".to_string(),
+                    default: Some(String::new()),
+                },
+            ]);
+        }
+        // the search constants are only options when they can be tuned
+        if cfg!(feature = "tune") {
+            options.extend(TUNABLES.iter().map(|t| t.option()));
+        }
+        options
     }
 
     /// Parse a UCI option and set the appropriate value in the [`Opts`] struct
     pub fn receive_option(&mut self, name: &str, value: Option<&str>) -> Result<Self> {
+        // GUIs send true/false, on/off is kept for older scripts
         let parse_check = |check: &str, value: Option<&str>| match value.unwrap_or_default() {
-            "on" => Ok(true),
-            "off" => Ok(false),
-            _ => bail!("you need to specify a value (on/off) for {check}"),
+            "true" | "on" => Ok(true),
+            "false" | "off" => Ok(false),
+            _ => bail!("you need to specify a value (true/false) for {check}"),
         };
         let parse_spin: fn(&str, i64, i64, Option<&str>) -> Result<i64> =
             |check: &str, low, high, value: Option<&str>| match value
@@ -258,13 +386,15 @@ impl Opts {
             "use_pv" => self.use_pv = parse_check("use_pv", value)?,
             "use_tt" => self.use_tt = parse_check("use_tt", value)?,
             "use_mo" => self.use_mo = parse_check("use_mo", value)?,
+            "use_rfp" => self.use_rfp = parse_check("use_rfp", value)?,
             "Ponder" => self.ponder = parse_check("Ponder", value)?,
             "bench_log" => {
                 if parse_check("bench_log", value)? {
                     return Ok(Self::bench()
                         .ab(self.use_ab)
                         .pv(self.use_pv)
-                        .tt(self.use_tt));
+                        .tt(self.use_tt)
+                        .rfp(self.use_rfp));
                 }
             }
             "search_debug" => {
@@ -276,7 +406,40 @@ impl Opts {
             "uci_debug" => self.uci = DebugLevel::from(parse_spin("uci_debug", 0, 5, value)?),
             // hash input is in megabytes, according to UCI specification
             "hash" => self.hash_size = 1024 * 1024 * parse_spin("hash", 0, 1024, value)? as usize,
+            "Eval Cache" => {
+                self.eval_cache_size =
+                    1024 * 1024 * parse_spin("Eval Cache", 0, 1024, value)? as usize
+            }
             "threads" => self.threads = parse_spin("threads", 0, 1024, value)? as usize,
+            "Contempt" => {
+                self.contempt =
+                    parse_spin("Contempt", -MAX_CONTEMPT as i64, MAX_CONTEMPT as i64, value)? as i16
+            }
+            "UCI_AnalyseMode" => self.analyse_mode = parse_check("UCI_AnalyseMode", value)?,
+            "Skill Level" => {
+                self.skill_level =
+                    parse_spin("Skill Level", 0, MAX_SKILL_LEVEL as i64, value)? as u8
+            }
+            "UCI_LimitStrength" => self.limit_strength = parse_check("UCI_LimitStrength", value)?,
+            "UCI_Elo" => {
+                self.elo = parse_spin("UCI_Elo", MIN_ELO as i64, MAX_ELO as i64, value)? as u16
+            }
+            // buttons (and the file they use) don't change any options, they are
+            // acted upon by the engine
+            "Clear Hash" | "Hash File" | "Save Hash" | "Load Hash" => {}
+            // the parameter file is loaded by the engine, an empty path means
+            // the built-in parameters
+            "Eval Params" => {}
+            #[cfg(feature = "nnue")]
+            "Use NNUE" => self.use_nnue = parse_check("Use NNUE", value)?,
+            // the network is loaded by the engine
+            #[cfg(feature = "nnue")]
+            "EvalFile" => {}
+            // the tunable constants are global, rather than part of the options
+            #[cfg(feature = "tune")]
+            tunable if let Some(t) = find(tunable) => {
+                t.set(parse_spin(t.name, t.min as i64, t.max as i64, value)? as i32)?
+            }
             unknown => bail!("unknown option: {:?}", unknown),
         }
 
@@ -326,6 +489,56 @@ impl Opts {
         self
     }
 
+    /// Set the contempt for draws, in centipawns
+    pub const fn contempt(self, x: i16) -> Self {
+        Self {
+            contempt: x,
+            ..self
+        }
+    }
+
+    /// Enable or disable analysis mode, where draws are scored as even
+    pub const fn analyse(self, x: bool) -> Self {
+        Self {
+            analyse_mode: x,
+            ..self
+        }
+    }
+
+    /// the score of a draw for the engine: minus the contempt, or even while
+    /// analysing
+    pub const fn draw_value(&self) -> Value {
+        if self.analyse_mode {
+            Value::DRAW
+        } else {
+            Value(-self.contempt)
+        }
+    }
+
+    /// Set the skill level, up to [`MAX_SKILL_LEVEL`] for full strength
+    pub const fn skill_level(self, x: u8) -> Self {
+        Self {
+            skill_level: x,
+            ..self
+        }
+    }
+
+    /// Limit the strength of the engine to a rating, or stop limiting it with
+    /// `None`
+    pub const fn limit_elo(self, x: Option<u16>) -> Self {
+        match x {
+            Some(elo) => Self {
+                limit_strength: true,
+                elo,
+                ..self
+            },
+            None => Self {
+                limit_strength: false,
+                ..self
+            },
+        }
+    }
+
     /// Enable or disable alpha-beta pruning during search
     pub const fn ab(self, x: bool) -> Self {
         Self { use_ab: x, ..self }
@@ -342,6 +555,11 @@ impl Opts {
         Self { use_tt: x, ..self }
     }
 
+    /// Enable or disable reverse futility pruning during search
+    pub const fn rfp(self, x: bool) -> Self {
+        Self { use_rfp: x, ..self }
+    }
+
     /// Set the transposition table size **in kilobytes**
     pub const fn hash_size(self, x: usize) -> Self {
         Self {
@@ -350,8 +568,24 @@ impl Opts {
         }
     }
 
+    /// Set the evaluation cache size **in bytes**
+    pub const fn eval_cache_size(self, x: usize) -> Self {
+        Self {
+            eval_cache_size: x,
+            ..self
+        }
+    }
+
     /// Set the number of threads to be used for the search
     pub const fn num_threads(self, x: usize) -> Self {
         Self { threads: x, ..self }
     }
+
+    /// Enable or disable evaluating with the network during search
+    pub const fn nnue(self, x: bool) -> Self {
+        Self {
+            use_nnue: x,
+            ..self
+        }
+    }
 }
@@ -2,26 +2,72 @@
 //!
 //! just a wrapper around [`chess::Board`] to customise things
 
+use std::str::FromStr;
+
+use anyhow::Error;
+use anyhow::Result;
+use anyhow::anyhow;
 use chess::Board;
 use chess::ChessMove;
+use chess::Piece;
+
+use crate::evaluation::accumulator::Accumulator;
+#[cfg(feature = "nnue")]
+use crate::evaluation::nnue::accumulator::NnueAccumulator;
 
 /// a position in a game
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Position {
     /// the board
     pub chessboard: Board,
     /// the number of plies in this game
     pub moves_played: usize,
+    /// the number of plies since the last capture or pawn move, for the
+    /// fifty-move rule
+    pub halfmove_clock: u8,
+    /// the material and piece-square scores of the board, kept up to date by
+    /// [`Position::make_move`]
+    pub accumulator: Accumulator,
+    /// the first layer of the network, kept up to date by
+    /// [`Position::make_move`] while a network is loaded
+    #[cfg(feature = "nnue")]
+    pub nnue: NnueAccumulator,
 }
 
 impl Position {
-    /// make a move on the board. allocates a new [`Board`] and increments the
-    /// number of plies
+    /// make a move on the board. allocates a new [`Board`], increments the
+    /// number of plies, and updates the [`Accumulator`]s for the move
     pub fn make_move(&self, mv: ChessMove) -> Self {
+        let resets_clock = self.chessboard.piece_on(mv.get_source()) == Some(Piece::Pawn)
+            || self.chessboard.piece_on(mv.get_dest()).is_some();
         let new_pos = self.chessboard.make_move_new(mv);
+        let accumulator = self.accumulator.make_move(&self.chessboard, mv);
+        debug_assert_eq!(
+            accumulator,
+            Accumulator::new(&new_pos),
+            "incremental update of {mv} from {} went wrong",
+            self.chessboard
+        );
+        #[cfg(feature = "nnue")]
+        let nnue = self.nnue.make_move(&self.chessboard, &new_pos, mv);
+        #[cfg(feature = "nnue")]
+        debug_assert_eq!(
+            nnue,
+            NnueAccumulator::new(&new_pos),
+            "incremental network update of {mv} from {} went wrong",
+            self.chessboard
+        );
         Self {
             chessboard: new_pos,
             moves_played: self.moves_played + 1,
+            halfmove_clock: if resets_clock {
+                0
+            } else {
+                self.halfmove_clock.saturating_add(1)
+            },
+            accumulator,
+            #[cfg(feature = "nnue")]
+            nnue,
         }
     }
 
@@ -34,13 +80,49 @@ impl Position {
             .count()
             >= 2
     }
+
+    /// whether the game is drawn by the fifty-move rule, unless the last move
+    /// was checkmate
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.halfmove_clock >= 100
+    }
 }
 
 impl From<Board> for Position {
     fn from(board: Board) -> Self {
         Self {
+            accumulator: Accumulator::new(&board),
+            #[cfg(feature = "nnue")]
+            nnue: NnueAccumulator::new(&board),
             chessboard: board,
             moves_played: 0,
+            halfmove_clock: 0,
         }
     }
 }
+
+/// a position from a FEN, keeping its halfmove clock, which [`Board`] doesn't
+impl FromStr for Position {
+    type Err = Error;
+
+    fn from_str(fen: &str) -> Result<Self> {
+        let board = Board::from_str(fen).map_err(|e| anyhow!("invalid FEN {fen:?}: {e}"))?;
+        let halfmove_clock = match fen.split_whitespace().nth(4) {
+            Some(clock) => clock
+                .parse::<u16>()
+                .map_err(|e| anyhow!("invalid halfmove clock in {fen:?}: {e}"))?
+                .min(u8::MAX as u16) as u8,
+            None => 0,
+        };
+        Ok(Self {
+            halfmove_clock,
+            ..Self::from(board)
+        })
+    }
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::from(Board::default())
+    }
+}
@@ -2,7 +2,6 @@
 use std::sync::atomic::AtomicI16;
 use std::sync::atomic::Ordering;
 use std::thread;
-use std::time::Duration;
 use std::time::Instant;
 
 use anyhow::Result;
@@ -14,14 +13,13 @@ use rayon::iter::IntoParallelRefIterator;
 use rayon::iter::ParallelIterator;
 
 use crate::Engine;
-use crate::evaluation::evaluate;
+use crate::evaluation::evaluator::Evaluator;
 use crate::move_generation::prio_iterator;
 use crate::optlog;
 use crate::opts::opts;
 use crate::search::MV;
 use crate::search::Message;
 use crate::search::RootNode;
-use crate::search::SEARCH_THREADS;
 use crate::search::SearchOptions;
 use crate::search::SearchResult;
 use crate::search::exit_condition;
@@ -30,13 +28,15 @@ use crate::search::negamax::negamax;
 use crate::search::negamax::search_to;
 use crate::search::search_until;
 use crate::search::send;
+use crate::search::skill::Skill;
 use crate::setup::depth::Depth;
 use crate::setup::depth::ONE_PLY;
 use crate::setup::values::Value;
 use crate::transposition_table::TranspositionTable;
-use crate::uci::UCI_LISTENING_FREQUENCY;
+use crate::transposition_table::stats::TableStats;
+use crate::util::Rng;
 
-impl Engine {
+impl<V: Evaluator> Engine<V> {
     /// Begin the search for the best move, spawns a new thread to actually do
     /// the search, and returns a listener for [`Message`]s.
     pub fn begin_search(&mut self) -> Result<Receiver<Message>> {
@@ -50,13 +50,12 @@ impl Engine {
         let mut root = RootNode {
             board: self.board.clone(),
             pv: Vec::new(),
-            eval: Value::MIN,
-            previous_eval: Value::MIN,
         };
 
         let tt = self.table.get();
 
         let engine_history = self.history.make_contiguous().to_vec();
+        let evaluator = self.evaluator.clone();
 
         thread::spawn(move || {
             let mut best_move: Option<ChessMove> = None;
@@ -67,23 +66,41 @@ impl Engine {
             let mut max_depth = Depth::ZERO;
             let mut min_depth = Depth::MAX;
 
-            // for now I'm using tablebase_hits to refer to transposition table hits,
-            // because it is displayed more prominently on cutechess UI, and I
-            // don't have an endgame tablebase yet.
+            // endgame tablebase hits. transposition table usage is reported
+            // separately, as [`Message::TableStats`] once the search is done.
             let mut tb_hits = 0;
+            let table_stats = tt.read().map_or(TableStats::default(), |l| l.stats());
+            let cache_stats = evaluator.cache_stats();
             let start_time = Instant::now();
 
-            let initial_options = SearchOptions {
-                extensions: Depth::ZERO,
-            };
-
             // SAFETY: if it fails it's due to poison,
             // and that means another thread panicked,
             // so we should panic as well anyway
             let search_options = opts().unwrap();
 
+            // the children of the root are searched from the opponent's side
+            let root_options = SearchOptions::root(&search_options);
+            let initial_options = SearchOptions {
+                draw: -root_options.draw,
+                ..root_options
+            };
+
+            // when playing weaker, the move picked from the scores of every root
+            // move in the last complete iteration. it is sent as the best move
+            // instead of the full strength one, which is never sent
+            let skill = Skill::from_opts(&search_options);
+            let mut rng = Rng::from_time();
+            let mut picked: Option<MV> = None;
+
+            // set when the search is stopped halfway through an iteration, whose
+            // best move so far was only sent as a guess
+            let mut interrupted = false;
+
             // iterative deepening loop
-            while !exit_condition() && target_depth < search_to() {
+            'deepening: while !exit_condition()
+                && target_depth < search_to()
+                && skill.is_none_or(|s| target_depth < s.depth() && total_nodes < s.nodes())
+            {
                 // record the time it takes to reach this depth to see if it's worth it to go
                 // deeper
                 let cur_depth_start = Instant::now();
@@ -118,23 +135,30 @@ impl Engine {
                 // call the [`negamax`] search, update the alpha value and return the
                 // [`SearchResult`]
                 let search_fn = |mv: &ChessMove| {
-                    let next_position = root.board.make_move(*mv);
+                    let next_position = evaluator.make_move(&root.board, *mv);
                     if next_position.causes_threefold(&engine_history) {
                         SearchResult {
                             pv: vec![],
-                            next_position_value: -evaluate(&next_position, true),
+                            next_position_value: root_options.draw,
                             nodes_searched: 1,
                             tb_hits: 0,
                             depth: ONE_PLY,
                         }
                     } else {
+                        // playing weaker needs the exact score of every move
+                        let alpha = if skill.is_some() {
+                            Value::MIN
+                        } else {
+                            Value(par_alpha.load(Ordering::Relaxed))
+                        };
                         let partial = -negamax(
                             next_position,
                             target_depth - 1,
-                            Value(par_alpha.load(Ordering::Relaxed)),
+                            alpha,
                             Value::MAX,
                             initial_options,
                             &search_options,
+                            &evaluator,
                             &tt,
                         );
                         par_alpha.store(
@@ -161,8 +185,11 @@ impl Engine {
                         .collect::<Vec<SearchResult>>()
                 };
 
+                let mut scores = Vec::with_capacity(moves.len());
+
                 // iterate through all the possible moves from [`RootNode`]
-                for (mv, search_result) in moves.iter().zip(all_results.into_iter()) {
+                for (mv, search_result) in moves.iter().zip(all_results) {
+                    scores.push(MV(*mv, search_result.next_position_value));
                     optlog!(
                         search;
                         debug;
@@ -173,7 +200,7 @@ impl Engine {
 
                     // add up all the recursively searched nodes, and the one the search begun from
                     total_nodes += search_result.nodes_searched + 1;
-                    // add up all the transposition table hits
+                    // add up all the tablebase hits
                     tb_hits += search_result.tb_hits;
 
                     max_depth = max_depth.max(search_result.depth);
@@ -193,26 +220,25 @@ impl Engine {
                         root.pv = vec![MV(*mv, search_result.next_position_value)];
                         root.pv.extend(search_result.pv);
 
-                        // UCI guess, not final move but have one ready in case stop is received
-                        if let Some(mv) = best_move {
-                            if let Err(e) = publisher.send(Message::BestGuess(MV(mv, best_value))) {
-                                optlog!(comm;debug;"error sending best guess: {:?}", e);
-                                break;
-                            }
+                        // UCI guess, not final move but have one ready in case stop is received.
+                        // playing weaker, only until there is a move to pick
+                        if let Some(mv) = best_move
+                            && (skill.is_none() || picked.is_none())
+                            && let Err(e) = publisher.send(Message::BestGuess(MV(mv, best_value)))
+                        {
+                            optlog!(comm;debug;"error sending best guess: {:?}", e);
+                            break;
                         }
                     }
 
                     // check on [`SEARCHING`] and [`SEARCH_UNTIL`] to see if we need to quit this
                     // search
                     if exit_condition() {
-                        return;
+                        interrupted = true;
+                        break 'deepening;
                     }
                 } // we have checked all moves for this depth
 
-                // save previous evaluation of the root node
-                root.previous_eval = root.eval;
-                root.eval = best_value;
-
                 {
                     // new depth info
                     info(
@@ -229,10 +255,21 @@ impl Engine {
                     );
                 } // ensure lock is dropped asap
 
-                if let Some(mv) = best_move {
+                // playing weaker, the best move is only one of the candidates
+                if let Some(s) = skill {
+                    picked = s.pick(&scores, &mut rng);
+                    if let Some(pick) = picked {
+                        optlog!(search;debug;"level {:.1} picked {pick}", s.level());
+                        send(&mut publisher, Message::BestMove(pick));
+                    }
+                } else if let Some(mv) = best_move {
                     send(&mut publisher, Message::BestMove(MV(mv, best_value)));
                 }
-                if let Some(ponder) = root.pv.get(1) {
+                // the reply to the best move, which isn't always played when
+                // playing weaker
+                if skill.is_none()
+                    && let Some(ponder) = root.pv.get(1)
+                {
                     send(&mut publisher, Message::Ponder(*ponder));
                 }
 
@@ -255,20 +292,98 @@ impl Engine {
                 }
             }
 
+            send(
+                &mut publisher,
+                Message::TableStats(
+                    tt.read().map_or(TableStats::default(), |l| l.stats()) - table_stats,
+                ),
+            );
+            if let (Some(before), Some(after)) = (cache_stats, evaluator.cache_stats()) {
+                send(&mut publisher, Message::EvalCacheStats(after - before));
+            }
+
             optlog!(search;debug;"sending best move {:?}", best_move);
             optlog!(comm;debug;"sending best move {:?}", best_move);
 
-            if let Some(mv) = best_move {
-                send(&mut publisher, Message::BestMove(MV(mv, best_value)))
+            if !interrupted && let Some(mv) = picked.or(best_move.map(|mv| MV(mv, best_value))) {
+                send(&mut publisher, Message::BestMove(mv))
             }
-
-            // looks sketchy, but it's to prevent dropping the sender before the receiver
-            // has gotten the best move.
-            thread::sleep(Duration::from_millis(
-                (SEARCH_THREADS * 2 * UCI_LISTENING_FREQUENCY) as u64,
-            ));
         });
 
         Ok(receiver)
     }
+
+    /// search the current position in the calling thread, deepening until
+    /// `depth`, or until `nodes` (if given) have been searched, which is only
+    /// checked between iterations. returns the best move, with its value for
+    /// the side to move, and the number of nodes searched, or `None` if there
+    /// are no legal moves.
+    ///
+    /// unlike [`Engine::begin_search`], this ignores the time and depth limits
+    /// of the search globals, so that several engines can search at once, eg
+    /// to generate training data.
+    pub fn search_fixed(&mut self, depth: Depth, nodes: Option<u32>) -> Result<Option<(MV, u32)>> {
+        let Some(any_move) = MoveGen::new_legal(&self.board.chessboard).next() else {
+            return Ok(None);
+        };
+        self.set_search(true);
+        let search_options = opts()?;
+        let table = self.table.get();
+        let history = self.history.make_contiguous().to_vec();
+
+        // the children of the root are searched from the opponent's side
+        let root_options = SearchOptions::root(&search_options);
+        let child_options = SearchOptions {
+            draw: -root_options.draw,
+            ..root_options
+        };
+
+        let mut best: Option<MV> = None;
+        let mut total_nodes = 0;
+        let mut target_depth = Depth::ZERO;
+        while target_depth < depth && nodes.is_none_or(|n| total_nodes < n) {
+            target_depth += ONE_PLY;
+            let previous = best.map(|mv| mv.0);
+            let moves = prio_iterator(
+                MoveGen::new_legal(&self.board.chessboard),
+                &self.board.chessboard,
+                previous.as_slice(),
+            );
+
+            // moves that repeat the position a third time are draws, like in
+            // [`Engine::begin_search`]
+            let mut iteration_best: Option<MV> = None;
+            for mv in moves {
+                let next_position = self.evaluator.make_move(&self.board, mv);
+                let value = if next_position.causes_threefold(&history) {
+                    total_nodes += 1;
+                    root_options.draw
+                } else {
+                    let alpha = iteration_best.map_or(Value::MIN, |b| b.1);
+                    let result = -negamax(
+                        next_position,
+                        target_depth - 1,
+                        -Value::MAX,
+                        -alpha,
+                        child_options,
+                        &search_options,
+                        &self.evaluator,
+                        &table,
+                    );
+                    total_nodes += result.nodes_searched + 1;
+                    result.next_position_value
+                };
+                if iteration_best.is_none_or(|b| value > b.1) {
+                    iteration_best = Some(MV(mv, value));
+                }
+            }
+            best = iteration_best.or(best);
+        }
+
+        // draws are not searched, any move will do
+        Ok(Some((
+            best.unwrap_or(MV(any_move, search_options.draw_value())),
+            total_nodes,
+        )))
+    }
 }
@@ -3,6 +3,7 @@ mod main_search;
 pub mod moveordering;
 pub mod mv_heuristics;
 pub mod negamax;
+pub mod skill;
 
 use std::fmt::Display;
 use std::ops::Neg;
@@ -18,13 +19,22 @@ use chess::ChessMove;
 use lockfree::channel::spsc::Sender;
 use log::debug;
 
+use crate::evaluation::cache::CacheStats;
+use crate::opts::Opts;
 use crate::position::Position;
 use crate::setup::depth::Depth;
 use crate::setup::values::Value;
+use crate::transposition_table::stats::TableStats;
 
 /// how many os threads should the search use?
 pub const SEARCH_THREADS: usize = 8;
 
+/// the contempt for draws, in centipawns, unless the `Contempt` option is set
+pub const DEFAULT_CONTEMPT: i16 = 0;
+
+/// the largest contempt the `Contempt` option accepts, either way
+pub const MAX_CONTEMPT: i16 = 100;
+
 /// when should the search stop?
 pub static SEARCH_UNTIL: RwLock<Option<Instant>> = RwLock::new(None);
 /// what's the maximum depth the search should go to?
@@ -45,10 +55,6 @@ pub struct RootNode {
     pub board: Position,
     /// the principal variation
     pub pv: Vec<MV>,
-    /// the current evaluation of the root node
-    pub eval: Value,
-    /// the previous evaluation of the root node
-    pub previous_eval: Value,
 }
 
 /// The result of a single negamax search call
@@ -60,7 +66,7 @@ pub struct SearchResult {
     pub next_position_value: Value,
     /// how many nodes were searched by this call and its recursive sub-calls
     pub nodes_searched: u32,
-    /// how many transposition table hits were made
+    /// how many endgame tablebase hits were made
     pub tb_hits: u32,
     /// actual depth the search reached
     pub depth: Depth,
@@ -77,6 +83,12 @@ pub enum Message {
     BestGuess(MV),
     /// A UCI info message
     Info(SearchInfo),
+    /// how the transposition table was used during the search, sent once the
+    /// search is done
+    TableStats(TableStats),
+    /// how the evaluation cache was used during the search, sent once the
+    /// search is done, if evaluations are cached
+    EvalCacheStats(CacheStats),
 }
 
 /// a UCI info message during a search
@@ -111,6 +123,20 @@ pub struct SearchOptions {
     /// how many times have we already extended the search? this is necessary to
     /// ensure the recursion terminates, and to prevent stack overflow.
     pub extensions: Depth,
+    /// the score of a draw (by stalemate, repetition, the fifty-move rule or
+    /// insufficient material) for the side to move. the engine's
+    /// [`Opts::draw_value`] on its turns, and the opposite on its opponent's.
+    pub draw: Value,
+}
+
+impl SearchOptions {
+    /// the options of the root of a search, where the engine is to move
+    pub const fn root(opts: &Opts) -> Self {
+        Self {
+            extensions: Depth::ZERO,
+            draw: opts.draw_value(),
+        }
+    }
 }
 
 /// wrapper around [`SEARCH_UNTIL`]
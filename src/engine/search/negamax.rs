@@ -8,10 +8,12 @@ use std::sync::atomic::Ordering;
 use anyhow::Result;
 use chess::Board;
 use chess::ChessMove;
+use chess::EMPTY;
 use chess::MoveGen;
 
 use super::SearchOptions;
-use crate::evaluation::evaluate;
+use crate::evaluation::evaluator::DefaultEvaluator;
+use crate::evaluation::evaluator::Evaluator;
 use crate::move_generation::prio_iterator;
 use crate::optlog;
 use crate::opts::Opts;
@@ -26,11 +28,13 @@ use crate::setup::depth::Depth;
 use crate::setup::depth::ONE_PLY;
 use crate::setup::values::Value;
 use crate::transposition_table::EvalBound;
-use crate::transposition_table::ShareImpl;
 use crate::transposition_table::TEntry;
 use crate::transposition_table::TT;
 use crate::transposition_table::TableAccess;
 use crate::transposition_table::TranspositionTable;
+use crate::tunable::MAX_EXTEND;
+use crate::tunable::RFP_DEPTH;
+use crate::tunable::RFP_MARGIN;
 
 /// wrapper around [`SEARCHING`]
 #[inline(always)]
@@ -80,22 +84,38 @@ pub fn ng_bench(
         to_depth,
         alpha,
         beta,
-        Default::default(),
+        SearchOptions::root(&opt),
         &opt,
+        &DefaultEvaluator::default(),
         &tt.get(),
     ))
 }
 
 /// mmmmmmmmmmmmm
-pub fn negamax(
+///
+/// generic over the evaluation (see [`Evaluator`]), and over the
+/// transposition table, so that different implementations can be compared
+/// (see [`harness`]). the engine itself always searches with a [`ShareImpl`].
+///
+/// [`harness`]: crate::transposition_table::harness
+/// [`ShareImpl`]: crate::transposition_table::ShareImpl
+#[allow(clippy::too_many_arguments)]
+pub fn negamax<V, E, T, A>(
     pos: Position,
     to_depth: Depth,
     mut alpha: Value,
     mut beta: Value,
     mut search_options: SearchOptions,
     opts: &Opts,
-    table: &ShareImpl,
-) -> SearchResult {
+    eval: &V,
+    table: &A,
+) -> SearchResult
+where
+    V: Evaluator,
+    E: TEntry,
+    T: TranspositionTable<u64, E> + Send + Sync,
+    A: TableAccess<u64, E, T>,
+{
     // the initial move generator
     let mut base_gen = MoveGen::new_legal(&pos.chessboard);
     // slice for already generated moves.
@@ -106,37 +126,65 @@ pub fn negamax(
 
     optlog!(search;trace;"ng: {pos}, td: {to_depth:?}, a: {alpha:?}, b: {beta:?}");
 
+    // stalemates, and positions drawn by the fifty-move rule or that can't be
+    // won by either side, are all scored as draws, with the contempt of the
+    // engine. checkmate on the last move before the fifty-move rule still counts.
+    let stalemate = out_of_moves && *pos.chessboard.checkers() == EMPTY;
+    if stalemate
+        || (!out_of_moves && (pos.is_fifty_move_draw() || eval.is_known_draw(&pos.chessboard)))
+    {
+        optlog!(search;trace;"draw");
+        return SearchResult {
+            pv: vec![],
+            next_position_value: search_options.draw,
+            nodes_searched: 1,
+            tb_hits: 0,
+            depth: ONE_PLY,
+        };
+    }
+
+    // the static evaluation of this position, reused from the table if it has
+    // been computed before, and otherwise only computed when it's needed
+    let mut static_eval = None;
+
     /* source: https://en.wikipedia.org/wiki/Negamax */
     let alpha_orig = alpha;
     if opts.use_tt {
         let current_hash = pos.chessboard.get_hash(); // change
-        if let Ok(Some(tt_entry)) = table.read().map(|l| l.get(current_hash)) {
-            if tt_entry.is_valid() {
-                if tt_entry.depth() >= to_depth {
-                    match tt_entry.bound() {
-                        EvalBound::Exact => return tt_entry.search_result(),
-                        EvalBound::LowerBound => {
-                            alpha = alpha.max(tt_entry.search_result().next_position_value)
-                        }
-                        EvalBound::UpperBound => {
-                            beta = beta.min(tt_entry.search_result().next_position_value)
-                        }
-                    }
-                    if alpha >= beta {
+        if let Some(tt_entry) = table.probe(current_hash)
+            && tt_entry.is_valid()
+        {
+            if tt_entry.depth() >= to_depth {
+                match tt_entry.bound() {
+                    EvalBound::Exact => {
+                        table.cutoff();
                         return tt_entry.search_result();
                     }
+                    EvalBound::LowerBound => {
+                        alpha = alpha.max(tt_entry.search_result().next_position_value)
+                    }
+                    EvalBound::UpperBound => {
+                        beta = beta.min(tt_entry.search_result().next_position_value)
+                    }
+                }
+                if alpha >= beta {
+                    table.cutoff();
+                    return tt_entry.search_result();
                 }
-                pre_generated[0] = Some(tt_entry.mv());
-                base_gen.remove_move(tt_entry.mv());
             }
+            static_eval = tt_entry.static_eval();
+            pre_generated[0] = Some(tt_entry.mv());
+            base_gen.remove_move(tt_entry.mv());
         }
     }
 
     // ordering wrapper around the move generation iterator
     let mut mgen = prio_iterator(base_gen, &pos.chessboard, &[]);
 
+    // leaves are not stored in the table, their evaluations are cached by the
+    // evaluator instead
     if to_depth == Depth::ZERO || out_of_moves {
-        let ev = evaluate(&pos, out_of_moves);
+        let ev = eval.evaluate(&pos, out_of_moves, opts);
         optlog!(search;trace;"return eval: {:?}", ev);
         return SearchResult {
             pv: vec![],
@@ -147,8 +195,32 @@ pub fn negamax(
         };
     }
 
+    // reverse futility pruning: close to the horizon, a position that is
+    // already far above beta without moving is unlikely to drop below it
+    let in_check = *pos.chessboard.checkers() != EMPTY;
+    if opts.use_ab
+        && opts.use_rfp
+        && !in_check
+        && to_depth.0 <= RFP_DEPTH.get() as u16
+        && beta < Value::MATE_IN_MAX_PLY
+    {
+        // only evaluated when needed, and then stored with the entry so that
+        // it is computed once however often the node is searched
+        let value = *static_eval.get_or_insert_with(|| eval.evaluate(&pos, false, opts));
+        if value.0 as i32 - RFP_MARGIN.get() * to_depth.0 as i32 >= beta.0 as i32 {
+            optlog!(search;trace;"rfp: {value:?} >= {beta:?}");
+            return SearchResult {
+                pv: vec![],
+                next_position_value: value,
+                nodes_searched: 1,
+                tb_hits: 0,
+                depth: ONE_PLY,
+            };
+        }
+    }
+
     // adjust depth based on heuristics
-    let next_depth = if search_options.extensions >= Depth::MAX_EXTEND {
+    let next_depth = if search_options.extensions >= Depth(MAX_EXTEND.get() as u16) {
         Depth::ZERO
     } else {
         // check the first 3 moves generated from the current position,
@@ -165,7 +237,8 @@ pub fn negamax(
         extensions: search_options
             .extensions
             .max(search_options.extensions + next_depth + 1 - to_depth),
-        // ..search_options
+        // a draw is worth the opposite to the other side
+        draw: -search_options.draw,
     };
 
     let mut best = None;
@@ -176,12 +249,13 @@ pub fn negamax(
 
     'next_moves: for mv in pre_generated.into_iter().flatten().chain(mgen) {
         let mut deeper = -negamax(
-            pos.make_move(mv),
+            eval.make_move(&pos, mv),
             next_depth,
             -beta,
             -alpha,
             search_options,
             opts,
+            eval,
             table,
         );
         total_nodes += deeper.nodes_searched + 1;
@@ -190,7 +264,8 @@ pub fn negamax(
 
         if !searching() {
             optlog!(search;trace;"searching() == false, breaking early");
-            deeper.next_position_value = evaluate(&pos, out_of_moves);
+            deeper.next_position_value =
+                static_eval.unwrap_or_else(|| eval.evaluate(&pos, out_of_moves, opts));
             deeper.nodes_searched = total_nodes;
             return deeper;
         }
@@ -238,10 +313,8 @@ pub fn negamax(
             EvalBound::Exact
         };
         let current_hash = pos.chessboard.get_hash(); // change
-        let entry = TEntry::new_from_result(current_hash, to_depth, &search_result, bound);
-        if let Ok(mut lock) = table.share().write() {
-            lock.insert(current_hash, entry)
-        };
+        let entry = E::new_from_result(current_hash, to_depth, &search_result, bound, static_eval);
+        table.store(current_hash, entry);
     }
 
     optlog!(search;trace;"return max_val: {:?}", best);
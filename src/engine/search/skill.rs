@@ -0,0 +1,123 @@
+//! playing weaker on purpose, for the `Skill Level` and `UCI_LimitStrength`
+//! options.
+//!
+//! a limited engine searches shallower, and fewer nodes. on top of that, it
+//! doesn't always play its best move: every root move is scored exactly, and
+//! the move is picked from the best few, with a random error that grows as the
+//! level drops (the same scheme as Stockfish's skill levels).
+use std::cmp::Reverse;
+
+use crate::opts::Opts;
+use crate::search::MV;
+use crate::setup::depth::Depth;
+use crate::util::Rng;
+
+/// the level of the engine at full strength, which isn't limited at all
+pub const MAX_SKILL_LEVEL: u8 = 20;
+
+/// the rating of the weakest level, [`Skill::from_elo`]
+pub const MIN_ELO: u16 = 800;
+
+/// the rating of full strength, [`Skill::from_elo`]. measured with `chesseng
+/// calibrate` (12 pairs of games between each level and the next, at
+/// 2s+0.02s), chaining the rating differences up from level 0 at [`MIN_ELO`],
+/// so it's a self-play rating relative to that. the levels in between were
+/// too close to tell apart in so few games, so they're spread evenly.
+pub const MAX_ELO: u16 = 1900;
+
+/// the rating `UCI_Elo` limits the engine to, unless it is set
+pub const DEFAULT_ELO: u16 = 1500;
+
+/// how many of the best moves are considered
+const CANDIDATES: usize = 4;
+
+/// the largest spread of the candidates that is randomised over, so that the
+/// random bonus stays under two pawns
+const MAX_SPREAD: i32 = 200;
+
+/// how weak the engine plays
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Skill {
+    /// from 0 (weakest) up to [`MAX_SKILL_LEVEL`], but fractional, for ratings
+    /// between two levels
+    level: f64,
+}
+
+impl Skill {
+    /// the strength set in `opts`, if it's limited: `UCI_Elo` below
+    /// [`MAX_ELO`] if `UCI_LimitStrength` is on, otherwise `Skill Level` below
+    /// [`MAX_SKILL_LEVEL`]
+    pub fn from_opts(opts: &Opts) -> Option<Self> {
+        if opts.limit_strength {
+            Some(Self::from_elo(opts.elo)).filter(|_| opts.elo < MAX_ELO)
+        } else if opts.skill_level < MAX_SKILL_LEVEL {
+            Some(Self::from_level(opts.skill_level))
+        } else {
+            None
+        }
+    }
+
+    /// the skill of one of the levels, up to [`MAX_SKILL_LEVEL`]
+    pub fn from_level(level: u8) -> Self {
+        Self {
+            level: level.min(MAX_SKILL_LEVEL) as f64,
+        }
+    }
+
+    /// the skill for a rating, in between the levels: [`MIN_ELO`] is level
+    /// 0 and [`MAX_ELO`] full strength, 55 points a level
+    pub fn from_elo(elo: u16) -> Self {
+        let elo = elo.clamp(MIN_ELO, MAX_ELO);
+        Self {
+            level: (elo - MIN_ELO) as f64 / (MAX_ELO - MIN_ELO) as f64 * MAX_SKILL_LEVEL as f64,
+        }
+    }
+
+    /// the level, from 0 up to [`MAX_SKILL_LEVEL`]
+    pub fn level(&self) -> f64 {
+        self.level
+    }
+
+    /// how deep to search: 1 ply at level 0, and 1 more every 2 levels
+    pub fn depth(&self) -> Depth {
+        Depth(1 + (self.level / 2.0) as u16)
+    }
+
+    /// how many nodes to search, after which no deeper iteration is started.
+    /// doubles every 2 levels.
+    pub fn nodes(&self) -> u32 {
+        2f64.powf(10.0 + self.level / 2.0) as u32
+    }
+
+    /// how much is randomised, 120 at level 0 down to 80 at level 20
+    fn weakness(&self) -> i32 {
+        120 - (2.0 * self.level) as i32
+    }
+
+    /// pick a move from the scores of every root move. the best
+    /// [`CANDIDATES`] are pulled towards the best score, the more the weaker
+    /// the engine, and then each gets a random bonus of up to their spread.
+    /// the one that ends up highest is played, so moves a little worse than
+    /// the best are played often at low levels, and much worse ones rarely.
+    /// `None` if there are no moves.
+    pub fn pick(&self, moves: &[MV], rng: &mut Rng) -> Option<MV> {
+        let mut candidates = moves.to_vec();
+        candidates.sort_by_key(|mv| Reverse(mv.1));
+        candidates.truncate(CANDIDATES);
+        let best = candidates.first()?.1.0 as i32;
+        let worst = candidates.last()?.1.0 as i32;
+        let spread = (best - worst).min(MAX_SPREAD);
+        let weakness = self.weakness();
+
+        candidates.into_iter().max_by_key(|mv| {
+            let score = mv.1.0 as i32;
+            let push =
+                (weakness * (best - score) + spread * rng.below(weakness as u64) as i32) / 128;
+            score + push
+        })
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/skill.rs"]
+mod tests;
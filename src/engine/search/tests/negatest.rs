@@ -4,19 +4,27 @@ use std::time::Duration;
 
 use chess::Board;
 use chess::BoardStatus;
+use chess::ChessMove;
 use chess::Color;
+use chess::Square;
 
 use crate::Engine;
 use crate::debug::DebugLevel::debug;
+use crate::evaluation::evaluator::DefaultEvaluator;
+use crate::evaluation::evaluator::Evaluator;
 use crate::opts::opts;
 use crate::opts::setopts;
 use crate::position::Position;
 use crate::search::SEARCHING;
 use crate::search::moveordering::ordered_moves;
 use crate::search::negamax::Opts;
+use crate::search::negamax::ng_bench;
 use crate::search::negamax::ng_test;
 use crate::setup::depth::Depth;
 use crate::setup::values::Value;
+use crate::transposition_table::TT;
+use crate::transposition_table::TableAccess;
+use crate::transposition_table::TranspositionTable;
 use crate::util::Print;
 use crate::util::short_benches;
 
@@ -48,6 +56,152 @@ fn mate_is_mate() {
     }
 }
 
+#[test]
+fn known_draw_is_draw() {
+    // a pawn up, but the bishop can't cover h8
+    let pos = Board::from_str("6k1/8/8/7P/8/8/8/4KB2 w - - 0 1").unwrap();
+    SEARCHING.store(true, Ordering::Relaxed);
+    let result = ng_test(pos, Depth(4), Value::MIN, Value::MAX, Opts::new()).unwrap();
+    assert_eq!(result.next_position_value, Value::DRAW);
+    assert_eq!(result.nodes_searched, 1);
+}
+
+#[test]
+fn draws_are_scored_with_contempt() {
+    let draws = [
+        // stalemate
+        "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1",
+        // a pawn up, but the bishop can't cover h8
+        "6k1/8/8/7P/8/8/8/4KB2 w - - 0 1",
+    ];
+    for fen in draws {
+        for (opts, draw) in [
+            (Opts::new(), Value::DRAW),
+            (Opts::new().contempt(30), Value(-30)),
+            (Opts::new().contempt(-30), Value(30)),
+            (Opts::new().contempt(30).analyse(true), Value::DRAW),
+        ] {
+            SEARCHING.store(true, Ordering::Relaxed);
+            let pos = Position::from_str(fen).unwrap();
+            let result = ng_bench(pos, Depth(3), Value::MIN, Value::MAX, opts, &TT::new());
+            assert_eq!(result.unwrap().next_position_value, draw, "{fen}");
+        }
+    }
+}
+
+#[test]
+fn fifty_move_rule() {
+    let pos = Position::from_str("4k3/8/8/8/8/8/4P3/R3K3 w - - 99 80").unwrap();
+    assert_eq!(pos.halfmove_clock, 99);
+    let pawn_move = ChessMove::new(Square::E2, Square::E4, None);
+    assert_eq!(pos.make_move(pawn_move).halfmove_clock, 0);
+    let rook_move = ChessMove::new(Square::A1, Square::A2, None);
+    assert!(pos.make_move(rook_move).is_fifty_move_draw());
+
+    // a rook up, but every move ends the game in a draw
+    let opts = Opts::new().contempt(20);
+    let pos = Position::from_str("8/8/8/4k3/8/8/8/R3K3 w - - 99 80").unwrap();
+    SEARCHING.store(true, Ordering::Relaxed);
+    let drawn = ng_bench(
+        pos.clone(),
+        Depth(2),
+        Value::MIN,
+        Value::MAX,
+        opts,
+        &TT::new(),
+    );
+    assert_eq!(drawn.unwrap().next_position_value, Value(-20));
+    let won = ng_bench(
+        Position::from(pos.chessboard),
+        Depth(2),
+        Value::MIN,
+        Value::MAX,
+        opts,
+        &TT::new(),
+    );
+    assert!(won.unwrap().next_position_value > Value(300));
+
+    // unless the last move is checkmate
+    let pos = Position::from_str("7k/R7/6K1/8/8/8/8/8 w - - 99 80").unwrap();
+    let mate = ng_bench(pos, Depth(2), Value::MIN, Value::MAX, opts, &TT::new());
+    assert!(mate.unwrap().next_position_value >= Value::MATE_IN_MAX_PLY);
+}
+
+#[test]
+fn pv_has_no_empty_moves() {
+    // the king and pawn shuffle around, so nodes transpose into each other
+    let pos = Position::from_str("8/8/4k3/8/8/4K3/4P3/8 w - - 0 1").unwrap();
+    let tt = TT::new();
+    tt.get().write().unwrap().resize(1 << 20);
+    for depth in 1..12 {
+        SEARCHING.store(true, Ordering::Relaxed);
+        let result = ng_bench(
+            pos.clone(),
+            Depth(depth),
+            Value::MIN,
+            Value::MAX,
+            Opts::new().tt(true),
+            &tt,
+        );
+        let pv = result.unwrap().pv;
+        assert!(
+            pv.iter().all(|mv| mv.0 != ChessMove::default()),
+            "depth={depth} pv={pv:?}"
+        );
+    }
+}
+
+#[test]
+fn interior_entries_keep_the_static_eval() {
+    let pos =
+        Position::from_str("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 10")
+            .unwrap();
+    // the static eval of the root, which is only computed for pruning, and
+    // the root is only pruned with a window below mate
+    let stored = |rfp| {
+        let tt = TT::new();
+        tt.get().write().unwrap().resize(1 << 20);
+        SEARCHING.store(true, Ordering::Relaxed);
+        ng_bench(
+            pos.clone(),
+            Depth(3),
+            Value(-50),
+            Value(50),
+            Opts::bench().rfp(rfp),
+            &tt,
+        )
+        .unwrap();
+        let entry = tt.get().probe(pos.chessboard.get_hash()).unwrap();
+        entry.static_eval()
+    };
+
+    let static_eval = DefaultEvaluator::default().evaluate(&pos, false, &Opts::bench());
+    assert_eq!(stored(true), Some(static_eval));
+    assert_eq!(stored(false), None);
+}
+
+#[test]
+fn reverse_futility_pruning_skips_nodes() {
+    // a queen up, so that most nodes near the horizon are far above beta
+    let pos = Position::from_str("4k3/pppp4/8/8/8/8/PPPP4/3QK3 w - - 0 1").unwrap();
+    let nodes = |rfp| {
+        SEARCHING.store(true, Ordering::Relaxed);
+        let opts = Opts::bench().rfp(rfp);
+        ng_bench(
+            pos.clone(),
+            Depth(4),
+            Value::MIN,
+            Value::MAX,
+            opts,
+            &TT::new(),
+        )
+        .unwrap()
+        .nodes_searched
+    };
+    let (pruned, full) = (nodes(true), nodes(false));
+    assert!(pruned < full, "{pruned} >= {full}");
+}
+
 #[test]
 fn mate_in_1_is_mate() {
     let pos = Board::from_str("8/8/8/6Q1/8/8/8/5K1k w - - 0 1").unwrap();
@@ -241,3 +395,46 @@ fn checkmate_the_author() {
         pos.print()
     );
 }
+
+#[test]
+fn fixed_search() {
+    SEARCHING.store(true, Ordering::Relaxed);
+    let mut engine = Engine::new().unwrap();
+    engine.board = Board::from_str("8/8/8/6Q1/8/8/8/5K1k w - - 0 1")
+        .unwrap()
+        .into();
+    let (mv, _) = engine.search_fixed(Depth(3), None).unwrap().unwrap();
+    assert!(mv.1 >= Value::MATE_IN_MAX_PLY, "{}", mv.1);
+    assert_eq!(
+        engine.board.chessboard.make_move_new(mv.0).status(),
+        BoardStatus::Checkmate
+    );
+
+    // the node limit stops the deepening after the first iteration
+    engine.board = Position::default();
+    let (_, shallow) = engine.search_fixed(Depth(20), Some(1)).unwrap().unwrap();
+    assert!(shallow < 100, "{shallow}");
+
+    engine.board = Board::from_str("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1")
+        .unwrap()
+        .into();
+    assert!(engine.search_fixed(Depth(3), None).unwrap().is_none());
+}
+
+#[test]
+fn fixed_search_avoids_repetition() {
+    SEARCHING.store(true, Ordering::Relaxed);
+    let mut engine = Engine::new().unwrap();
+    engine.board = Board::from_str("8/8/4k3/8/8/3QK3/8/8 w - - 10 40")
+        .unwrap()
+        .into();
+    let (first, _) = engine.search_fixed(Depth(3), None).unwrap().unwrap();
+
+    // the position after that move has been seen twice, so playing it again
+    // is a draw, which a won position must not settle for
+    let repeated = engine.board.make_move(first.0);
+    engine.history = [engine.board.clone(), repeated.clone(), repeated].into();
+    let (mv, _) = engine.search_fixed(Depth(3), None).unwrap().unwrap();
+    assert_ne!(mv.0, first.0);
+    assert!(mv.1 > Value(0), "{}", mv.1);
+}
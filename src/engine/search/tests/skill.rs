@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use chess::ChessMove;
+use chess::Square;
+
+use super::*;
+use crate::setup::values::Value;
+
+/// a move from a1 to `to`, with `value`
+fn mv(to: Square, value: i16) -> MV {
+    MV(ChessMove::new(Square::A1, to, None), Value(value))
+}
+
+/// how many times each move is picked out of 1000
+fn picks(skill: Skill, moves: &[MV]) -> HashMap<Square, usize> {
+    let mut rng = Rng::new(7);
+    let mut counts = HashMap::new();
+    for _ in 0..1000 {
+        let pick = skill.pick(moves, &mut rng).unwrap();
+        *counts.entry(pick.0.get_dest()).or_default() += 1;
+    }
+    counts
+}
+
+#[test]
+fn options() {
+    assert_eq!(Skill::from_opts(&Opts::new()), None);
+    assert_eq!(
+        Skill::from_opts(&Opts::new().skill_level(5)),
+        Some(Skill::from_level(5))
+    );
+    // the rating takes precedence over the level
+    assert_eq!(
+        Skill::from_opts(&Opts::new().skill_level(5).limit_elo(Some(MIN_ELO))),
+        Some(Skill::from_level(0))
+    );
+    assert_eq!(
+        Skill::from_opts(&Opts::new().skill_level(5).limit_elo(Some(MAX_ELO))),
+        None
+    );
+    assert_eq!(
+        Skill::from_opts(&Opts::new().limit_elo(Some(1500)).limit_elo(None)),
+        None
+    );
+}
+
+#[test]
+fn ratings() {
+    assert_eq!(Skill::from_elo(MIN_ELO).level(), 0.0);
+    assert_eq!(Skill::from_elo(0).level(), 0.0);
+    assert_eq!(Skill::from_elo(MAX_ELO).level(), MAX_SKILL_LEVEL as f64);
+    assert_eq!(Skill::from_elo(1350).level(), 10.0);
+    // the level goes up with the rating, a fraction of a level at a time
+    for elo in MIN_ELO..MAX_ELO {
+        let step = Skill::from_elo(elo + 1).level() - Skill::from_elo(elo).level();
+        assert!(step > 0.0 && step < 1.0, "{elo}: {step}");
+    }
+}
+
+#[test]
+fn limits() {
+    assert_eq!(Skill::from_level(0).depth(), Depth(1));
+    assert_eq!(Skill::from_level(0).nodes(), 1024);
+    for level in 1..MAX_SKILL_LEVEL {
+        let (weaker, stronger) = (Skill::from_level(level - 1), Skill::from_level(level));
+        assert!(weaker.depth() <= stronger.depth());
+        assert!(weaker.nodes() < stronger.nodes());
+    }
+}
+
+#[test]
+fn picking() {
+    let skill = Skill::from_level(0);
+    let mut rng = Rng::new(1);
+    assert!(skill.pick(&[], &mut rng).is_none());
+    let only = [mv(Square::A2, -300)];
+    assert_eq!(skill.pick(&only, &mut rng).unwrap().0, only[0].0);
+
+    // a mate is always played
+    let mate = [
+        mv(Square::A2, 20),
+        mv(Square::A3, Value::MATE.0 - 3),
+        mv(Square::A4, 10),
+    ];
+    assert_eq!(picks(skill, &mate)[&Square::A3], 1000);
+
+    // only the best few are considered, and the best most of all
+    let moves = [
+        mv(Square::A2, 30),
+        mv(Square::A3, 60),
+        mv(Square::A4, 0),
+        mv(Square::A5, -20),
+        mv(Square::A6, -40),
+    ];
+    let weakest = picks(skill, &moves);
+    assert!(!weakest.contains_key(&Square::A6), "{weakest:?}");
+    assert!(weakest.len() > 2, "{weakest:?}");
+    let strongest = picks(Skill::from_level(MAX_SKILL_LEVEL - 1), &moves);
+    assert!(
+        strongest[&Square::A3] > weakest[&Square::A3],
+        "{strongest:?} {weakest:?}"
+    );
+    assert!(strongest[&Square::A3] > 500, "{strongest:?}");
+}
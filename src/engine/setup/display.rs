@@ -4,6 +4,7 @@ use std::fmt::Formatter;
 
 use crate::position::Position;
 use crate::setup::values::Value;
+use crate::transposition_table::TEntry;
 use crate::transposition_table::TT;
 use crate::transposition_table::TranspositionTable;
 use crate::transposition_table::entry::TableEntry;
@@ -34,9 +35,11 @@ impl Display for TableEntry {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{}:{}/{:?}/{}/{:?}/Pv {}/V {}",
-            self.key,
+            "{}:{}/{}/{:?}/{}/{:?}/Pv {}/V {}",
+            self.key(),
             self.eval(),
+            self.static_eval()
+                .map_or("-".to_string(), |v| v.to_string()),
             self.depth(),
             self.mv(),
             self.bound(),
@@ -6,6 +6,10 @@ use std::time::Instant;
 use anyhow::Result;
 
 use crate::Engine;
+use crate::evaluation::evaluator::Evaluator;
+use crate::tunable::CONCERN_THRESHOLD;
+use crate::tunable::MOVES_LEFT;
+use crate::tunable::TIME_LEAD_DIVISOR;
 
 /// one hundred years :)
 const MAX_TIME: Duration = Duration::from_secs(60 * 60 * 24 * 365 * 100);
@@ -13,16 +17,12 @@ const MAX_TIME: Duration = Duration::from_secs(60 * 60 * 24 * 365 * 100);
 /// The duration before the search should end to allow for submitting the move.
 pub const SUBMIT_DURATION: Duration = Duration::from_millis(5);
 
-/// If the time-remaining difference between the players is greater than this,
-/// maybe we should change our time management a bit.
-const CONCERN_THRESHOLD: Duration = Duration::from_secs(15);
-
 /// Returns the maximum instant that can be represented.
 pub fn max_instant() -> Instant {
     Instant::now() + MAX_TIME
 }
 
-impl Engine {
+impl<V: Evaluator> Engine<V> {
     /// Set the time until which the engine should search.
     pub fn game_time_constraints(
         &mut self,
@@ -42,7 +42,10 @@ impl Engine {
 
         // estimate how long the game still has to go.
         // TODO: improve this estimate.
-        let est_moves_left = moves_to_go.unwrap_or(50).max(1) as u32;
+        let est_moves_left = moves_to_go
+            .map_or(MOVES_LEFT.get() as u32, u32::from)
+            .max(1);
+        let concern_threshold = Duration::from_millis(CONCERN_THRESHOLD.get() as u64);
 
         if let Some(inc) = our_inc {
             // if there is an increment, we should use all of it
@@ -79,7 +82,7 @@ impl Engine {
             // quickly, we should speed up (as is more natural in human games)
             match t1.cmp(&t2) {
                 Ordering::Less => {
-                    if t2 - t1 > CONCERN_THRESHOLD / 2 {
+                    if t2 - t1 > concern_threshold / 2 {
                         // maybe we should think faster
                         stop_search_at -= (t2 - t1) / est_moves_left;
                     }
@@ -92,9 +95,10 @@ impl Engine {
                 Ordering::Greater => {
                     // we seem to have more time left than the opponent.
                     // is this difference significant?
-                    if t1 - t2 > CONCERN_THRESHOLD {
+                    if t1 - t2 > concern_threshold {
                         // maybe we should think longer about our moves
-                        stop_search_at += (t1 - t2) / ((est_moves_left / 4).max(1));
+                        stop_search_at +=
+                            (t1 - t2) / ((est_moves_left / TIME_LEAD_DIVISOR.get() as u32).max(1));
                     }
                 }
             }
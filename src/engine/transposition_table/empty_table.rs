@@ -1,13 +1,17 @@
 //! a no-op transposition table
 use std::marker::PhantomData;
 
+use chess::ChessMove;
+
 use crate::search::SearchResult;
 use crate::setup::depth::Depth;
+use crate::setup::values::Value;
 use crate::transposition_table::EvalBound;
 use crate::transposition_table::TEntry;
 use crate::transposition_table::TKey;
 use crate::transposition_table::TableAccess;
 use crate::transposition_table::TranspositionTable;
+use crate::transposition_table::stats::TableStats;
 
 /// a no-op entry
 #[derive(Debug, Clone)]
@@ -48,6 +52,7 @@ impl TEntry for EmptyEntry {
         _depth: Depth,
         _result: &SearchResult,
         _bound: EvalBound,
+        _static_eval: Option<Value>,
     ) -> Self {
         EmptyEntry
     }
@@ -64,12 +69,20 @@ impl TEntry for EmptyEntry {
         SearchResult::default()
     }
 
+    fn static_eval(&self) -> Option<Value> {
+        None
+    }
+
+    fn mv(&self) -> ChessMove {
+        ChessMove::default()
+    }
+
     fn is_valid(&self) -> bool {
         false
     }
 }
 
-impl<E: TEntry, K: TKey<FromType = ()>> TranspositionTable<K, E> for EmptyTable<K, E> {
+impl<E: TEntry, K: TKey> TranspositionTable<K, E> for EmptyTable<K, E> {
     fn new(_kb: usize) -> Self {
         EmptyTable {
             _phantom: Default::default(),
@@ -92,13 +105,19 @@ impl<E: TEntry, K: TKey<FromType = ()>> TranspositionTable<K, E> for EmptyTable<
     fn hashfull(&self) -> usize {
         0
     }
+    fn stats(&self) -> TableStats {
+        TableStats::default()
+    }
 }
 
-impl TableAccess<EmptyHash, EmptyEntry, EmptyTable<EmptyHash, EmptyEntry>>
-    for EmptyTable<EmptyHash, EmptyEntry>
-{
-    fn hit(&self) {}
-    fn share(&self) -> EmptyTable<EmptyHash, EmptyEntry> {
+impl<E: TEntry + Send, K: TKey + Send> TableAccess<K, E, EmptyTable<K, E>> for EmptyTable<K, E> {
+    fn cutoff(&self) {}
+    fn probe(&self, _hash: K) -> Option<E> {
+        None
+    }
+    fn store(&self, _hash: K, _entry: E) {}
+    fn clear(&self) {}
+    fn share(&self) -> EmptyTable<K, E> {
         // the table is always empty, just make a new one
         EmptyTable::new(0)
     }
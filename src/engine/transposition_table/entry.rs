@@ -18,24 +18,26 @@ use crate::transposition_table::TEntry;
 /// # A single transposition table entry.
 /// [`TranspositionTable`]
 /// ## Memory Layout
-/// * 2 bytes for the key
+/// **key:** the full key, to tell positions at the same index apart
 ///
-/// **[`AtomicU64`]:**
+/// **value [`AtomicU64`]:**
 /// * evaluation value: 2 bytes
-/// * depth: 2 bytes
-/// * source square: 1 byte
-/// * destination square: 1 byte
+/// * static evaluation: 2 bytes ([`Value::NONE`] if unknown)
+/// * depth: 1 byte (saturating at [`u8::MAX`], far above [`Depth::MAX`])
+/// * source square: 6 bits
+/// * destination square: 6 bits
 /// * promotion: 3 bits
 /// * bound: 2 bits
 /// * is_pv: 1 bit
+/// * 4 unused bits
 /// * is_valid_entry: 1 bit
 /// * 1 parity bit for checking
 #[derive(Debug)]
 pub struct TableEntry {
-    /// only store 2 bytes of the key to verify if a collision occurred
-    pub key: u64,
+    /// the key of this entry
+    key: u64,
     /// the packed value of this entry
-    pub value: AtomicU64,
+    value: AtomicU64,
 }
 
 impl TableEntry {
@@ -55,38 +57,51 @@ impl TableEntry {
     pub fn pack(
         key: u64,
         eval: Value,
+        static_eval: Value,
         depth: Depth,
         mv: ChessMove,
         bound: EvalBound,
         is_pv: bool,
     ) -> Self {
         let mut value = 0u64;
-        value |= (eval.0 as u64) << 48;
-        value |= (depth.0 as u64) << 32;
-        value |= (mv.get_source().to_int() as u64) << 24;
-        value |= (mv.get_dest().to_int() as u64) << 16;
+        value |= (eval.0 as u16 as u64) << 48;
+        value |= (static_eval.0 as u16 as u64) << 32;
+        value |= (depth.0.min(u8::MAX as u16) as u64) << 24;
+        value |= (mv.get_source().to_int() as u64) << 18;
+        value |= (mv.get_dest().to_int() as u64) << 12;
         value |= (Self::PROMOTION_BITS
             .iter()
             .position(|x| x.eq(&mv.get_promotion()))
             .unwrap_or(0) as u64)
-            << 13;
-        value |= (bound as u64) << 11;
-        value |= (is_pv as u64) << 10;
+            << 9;
+        value |= (bound as u64) << 7;
+        value |= (is_pv as u64) << 6;
         // since we just created this entry, it is valid (unlike the empty ones
         // initially in the table)
         value |= 0b10;
         // parity bit, set to 1 if the number of bits set in the value is even
         // ensures the full value is always odd parity
         value |= 1 ^ (value.count_ones() & 1) as u64;
+        Self::from_raw(key, value)
+    }
+
+    /// reconstruct an entry from its raw `(key, value)` pair, eg one that was
+    /// read from disk. use [`TableEntry::is_valid_entry`] to check it.
+    pub fn from_raw(key: u64, value: u64) -> Self {
         Self {
             key,
             value: AtomicU64::new(value),
         }
     }
 
+    /// the raw `(key, value)` pair of this entry
+    pub fn raw(&self) -> (u64, u64) {
+        (self.key, self.value.load(Ordering::Relaxed))
+    }
+
     /// get the depth of this entry's value
     pub fn depth(&self) -> Depth {
-        Depth((self.value.load(Ordering::Relaxed) >> 32) as u16)
+        Depth((self.value.load(Ordering::Relaxed) >> 24) as u8 as u16)
     }
 
     /// get the evaluation value from this entry
@@ -94,12 +109,17 @@ impl TableEntry {
         Value((self.value.load(Ordering::Relaxed) >> 48) as i16)
     }
 
+    /// get the static evaluation of this entry's position, if it was stored
+    pub fn static_eval(&self) -> Option<Value> {
+        Some(Value((self.value.load(Ordering::Relaxed) >> 32) as i16)).filter(|v| *v != Value::NONE)
+    }
+
     /// get the [`ChessMove`] from this entry
     pub fn mv(&self) -> ChessMove {
         let value = self.value.load(Ordering::Relaxed);
-        let src = (value >> 24) as u8;
-        let dest = (value >> 16) as u8;
-        let promotion = Self::PROMOTION_BITS[(0b111 & (value >> 13)) as usize];
+        let src = (0b111111 & (value >> 18)) as u8;
+        let dest = (0b111111 & (value >> 12)) as u8;
+        let promotion = Self::PROMOTION_BITS[(0b111 & (value >> 9)) as usize];
         // SAFETY: the values are stored in the correct range since they're always
         // created from a [`ChessMove`] struct in the first place
         unsafe { ChessMove::new(Square::new(src), Square::new(dest), promotion) }
@@ -112,7 +132,7 @@ impl TableEntry {
 
     /// get the [`EvalBound`] for this entry
     pub fn bound(&self) -> EvalBound {
-        match 0b11 & (self.value.load(Ordering::Relaxed) >> 11) as u8 {
+        match 0b11 & (self.value.load(Ordering::Relaxed) >> 7) as u8 {
             0 => EvalBound::Exact,
             1 => EvalBound::LowerBound,
             2 => EvalBound::UpperBound,
@@ -122,7 +142,7 @@ impl TableEntry {
 
     /// check if the entry is in the principal variation
     pub fn is_pv(&self) -> bool {
-        (self.value.load(Ordering::Relaxed) >> 10) & 1 == 1
+        (self.value.load(Ordering::Relaxed) >> 6) & 1 == 1
     }
 
     /// check if the entry is valid using the 'valid' bit
@@ -130,14 +150,21 @@ impl TableEntry {
         let val = self.value.load(Ordering::Relaxed);
         (val & 0b10) == 0b10 && val.count_ones() & 1 == 1
     }
+
+    /// check that the bound and promotion bits hold values that [`Self::pack`]
+    /// could have written, which the parity check can't tell, eg for entries
+    /// read from a file
+    pub fn is_well_formed(&self) -> bool {
+        let val = self.value.load(Ordering::Relaxed);
+        let promotion = (0b111 & (val >> 9)) as usize;
+        0b11 & (val >> 7) != 0b11 && (promotion == 0 || Self::PROMOTION_BITS[promotion].is_some())
+    }
 }
 
 impl Clone for TableEntry {
     fn clone(&self) -> Self {
-        Self {
-            key: self.key,
-            value: AtomicU64::new(self.value.load(Ordering::Relaxed)),
-        }
+        let (key, value) = self.raw();
+        Self::from_raw(key, value)
     }
 }
 
@@ -146,21 +173,25 @@ impl TEntry for TableEntry {
 
     #[inline]
     fn key(&self) -> Self::Key {
-        self.key
+        self.raw().0
     }
 
     #[inline]
     fn new_empty() -> Self {
-        Self {
-            key: 0,
-            value: AtomicU64::new(0),
-        }
+        Self::from_raw(0, 0)
     }
 
-    fn new_from_result(hash: u64, depth: Depth, result: &SearchResult, bound: EvalBound) -> Self {
+    fn new_from_result(
+        hash: u64,
+        depth: Depth,
+        result: &SearchResult,
+        bound: EvalBound,
+        static_eval: Option<Value>,
+    ) -> Self {
         Self::pack(
             hash,
             result.next_position_value,
+            static_eval.unwrap_or(Value::NONE),
             depth,
             result.pv[0].0,
             bound,
@@ -184,11 +215,21 @@ impl TEntry for TableEntry {
             pv: vec![self.mv_struct()], // ?
             next_position_value: self.eval(),
             nodes_searched: 1,
-            tb_hits: 1,
+            tb_hits: 0,
             depth: ONE_PLY,
         }
     }
 
+    #[inline]
+    fn static_eval(&self) -> Option<Value> {
+        self.static_eval()
+    }
+
+    #[inline]
+    fn mv(&self) -> ChessMove {
+        self.mv()
+    }
+
     #[inline]
     fn is_valid(&self) -> bool {
         self.is_valid_entry()
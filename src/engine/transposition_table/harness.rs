@@ -0,0 +1,169 @@
+//! a harness to compare transposition table implementations side by side.
+//!
+//! everything here is generic over the [`TableAccess`] type, so a new table
+//! design only has to implement the traits of [`transposition_table`] to be
+//! measured against [`VL`] and [`EmptyTable`]. used by the `tt` benchmarks.
+//!
+//! [`transposition_table`]: crate::transposition_table
+//! [`VL`]: crate::transposition_table::vl::VL
+//! [`EmptyTable`]: crate::transposition_table::empty_table::EmptyTable
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+use chess::ChessMove;
+
+use crate::evaluation::evaluator::DefaultEvaluator;
+use crate::opts::Opts;
+use crate::position::Position;
+use crate::search::MV;
+use crate::search::SEARCHING;
+use crate::search::SearchOptions;
+use crate::search::SearchResult;
+use crate::search::negamax::negamax;
+use crate::setup::depth::Depth;
+use crate::setup::depth::ONE_PLY;
+use crate::setup::values::Value;
+use crate::transposition_table::EvalBound;
+use crate::transposition_table::TEntry;
+use crate::transposition_table::TableAccess;
+use crate::transposition_table::TranspositionTable;
+use crate::util::Rng;
+use crate::util::bench_positions;
+
+/// how many inserts ago was the key that each [`throughput`] probe looks for
+const PROBE_LAG: usize = 16;
+
+/// The outcome of a [`throughput`] run
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Throughput {
+    /// the number of inserts, across all threads
+    pub inserts: u64,
+    /// the number of probes, across all threads
+    pub probes: u64,
+    /// how many probes found a valid entry
+    pub hits: u64,
+    /// the wall-clock time of the run
+    pub elapsed: Duration,
+}
+
+impl Throughput {
+    /// inserts and probes per second
+    pub fn ops_per_sec(&self) -> f64 {
+        (self.inserts + self.probes) as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// The outcome of a [`nodes_to_depth`] run
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SearchRun {
+    /// how many positions were searched
+    pub positions: usize,
+    /// the number of nodes needed to reach the depth, over all positions
+    pub nodes: u64,
+    /// the wall-clock time of the run
+    pub elapsed: Duration,
+}
+
+impl SearchRun {
+    /// nodes per second
+    pub fn nps(&self) -> f64 {
+        self.nodes as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// a deterministic stream of pseudo-random keys, one per seed
+fn keys(seed: u64) -> impl Iterator<Item = u64> {
+    let mut rng = Rng::new(seed);
+    std::iter::repeat_with(move || rng.next_u64())
+}
+
+/// hammer the table from `threads` threads at once. every thread does `ops`
+/// inserts of one-ply entries with pseudo-random keys, each followed by a probe
+/// for the key it inserted [`PROBE_LAG`] inserts before, which is a hit unless
+/// that entry has been replaced since.
+pub fn throughput<E, T, A>(table: &A, threads: usize, ops: usize) -> Throughput
+where
+    E: TEntry,
+    T: TranspositionTable<u64, E> + Send + Sync,
+    A: TableAccess<u64, E, T> + Send,
+{
+    let start = Instant::now();
+    let hits = thread::scope(|s| {
+        let handles = (0..threads.max(1))
+            .map(|t| {
+                let table = table.share();
+                s.spawn(move || {
+                    let mut recent = [0u64; PROBE_LAG];
+                    let mut hits = 0;
+                    for (i, key) in keys(t as u64).take(ops).enumerate() {
+                        let result = SearchResult {
+                            pv: vec![MV(ChessMove::default(), Value((i % 1024) as i16))],
+                            ..Default::default()
+                        };
+                        let entry =
+                            E::new_from_result(key, ONE_PLY, &result, EvalBound::Exact, None);
+                        table.store(key, entry);
+                        let old = std::mem::replace(&mut recent[i % PROBE_LAG], key);
+                        if i >= PROBE_LAG && table.probe(old).is_some_and(|e| e.is_valid()) {
+                            hits += 1;
+                        }
+                    }
+                    hits
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|h| h.join().unwrap_or_default())
+            .sum::<u64>()
+    });
+    let elapsed = start.elapsed();
+    let total = (threads.max(1) * ops) as u64;
+    Throughput {
+        inserts: total,
+        probes: total.saturating_sub((threads.max(1) * PROBE_LAG.min(ops)) as u64),
+        hits,
+        elapsed,
+    }
+}
+
+/// search every one of [`bench_positions`] to `depth`, with a cleared table
+/// for each position, and count the nodes it took to get there.
+///
+/// this sets [`SEARCHING`], as the search returns early without it.
+pub fn nodes_to_depth<E, T, A>(table: &A, depth: Depth, opts: &Opts) -> SearchRun
+where
+    E: TEntry,
+    T: TranspositionTable<u64, E> + Send + Sync,
+    A: TableAccess<u64, E, T>,
+{
+    let positions = bench_positions();
+    let start = Instant::now();
+    let mut nodes = 0;
+    for board in positions.iter() {
+        table.clear();
+        SEARCHING.store(true, Ordering::Relaxed);
+        let result = negamax(
+            Position::from(*board),
+            depth,
+            Value::MIN,
+            Value::MAX,
+            SearchOptions::root(opts),
+            opts,
+            &DefaultEvaluator::default(),
+            table,
+        );
+        nodes += result.nodes_searched as u64;
+    }
+    SearchRun {
+        positions: positions.len(),
+        nodes,
+        elapsed: start.elapsed(),
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/harness.rs"]
+mod tests;
@@ -3,13 +3,20 @@
 use std::sync::Arc;
 use std::sync::RwLock;
 
+use chess::ChessMove;
+
 use crate::search::SearchResult;
 use crate::setup::depth::Depth;
+use crate::setup::values::Value;
+use crate::transposition_table::stats::TableStats;
 use crate::transposition_table::vl::VL;
 use crate::transposition_table::vl::VlShare;
 
 pub mod empty_table;
 pub mod entry;
+pub mod harness;
+pub mod persist;
+pub mod stats;
 pub mod vl;
 
 /// The default size of a transposition table, in bytes [release mode]
@@ -42,14 +49,25 @@ pub trait TEntry: Sync {
     fn key(&self) -> Self::Key;
     /// create a new empty entry
     fn new_empty() -> Self;
-    /// create a new entry to store a search result
-    fn new_from_result(hash: u64, depth: Depth, result: &SearchResult, bound: EvalBound) -> Self;
+    /// create a new entry to store a search result, along with the static
+    /// evaluation of the position if it is known
+    fn new_from_result(
+        hash: u64,
+        depth: Depth,
+        result: &SearchResult,
+        bound: EvalBound,
+        static_eval: Option<Value>,
+    ) -> Self;
     /// the depth of the search that created this entry
     fn depth(&self) -> Depth;
     /// the relative evaluation of the entry
     fn bound(&self) -> EvalBound;
     /// a [`SearchResult`] from this entry
     fn search_result(&self) -> SearchResult;
+    /// the static evaluation of the entry's position, if it was stored
+    fn static_eval(&self) -> Option<Value>;
+    /// the best move found in the entry's position
+    fn mv(&self) -> ChessMove;
     /// do the entry's values make sense?
     fn is_valid(&self) -> bool;
     // ...
@@ -68,7 +86,8 @@ pub trait TranspositionTable<Key: TKey, Entry: TEntry> {
     ///
     /// intended as a wrapper around [`entry`]
     fn insert(&mut self, hash: Key, entry: Entry);
-    /// empty the transposition table
+    /// empty the transposition table, invalidating every entry while keeping
+    /// its capacity. also resets the [`TableStats`].
     fn clear(&mut self);
     /// the number of entries in the table
     fn entry_count(&self) -> usize;
@@ -76,13 +95,22 @@ pub trait TranspositionTable<Key: TKey, Entry: TEntry> {
     fn capacity(&self) -> usize;
     /// UCI hashfull
     fn hashfull(&self) -> usize;
+    /// the counters of this table since it was created or last cleared
+    fn stats(&self) -> TableStats;
 }
 
 /// A model for concurrent access to the transposition table
 pub trait TableAccess<K: TKey, E: TEntry, T: Send + Sync + TranspositionTable<K, E>> {
-    /// increment number of successful reads were made in this transposition
-    /// table
-    fn hit(&self);
+    /// count an entry read from this table that was good enough to cut off
+    /// the search. (probes, hits, and collisions are counted by the table
+    /// itself)
+    fn cutoff(&self);
+    /// read the entry stored for `hash`, if there is one
+    fn probe(&self, hash: K) -> Option<E>;
+    /// store `entry` for `hash`, following the table's replacement scheme
+    fn store(&self, hash: K, entry: E);
+    /// invalidate every entry of the table
+    fn clear(&self);
     /// access the table. the implementation must ensure that this is
     /// - safe
     /// - fast
@@ -124,6 +152,13 @@ impl TT {
     pub fn get_mut(&mut self) -> VlShare {
         self.table.share()
     }
+
+    /// the [`TableStats`] of the table, or the default if the lock is poisoned
+    pub fn stats(&self) -> TableStats {
+        self.table
+            .read()
+            .map_or_else(|_| TableStats::default(), |l| l.stats())
+    }
 }
 
 /// The bound of an evaluation
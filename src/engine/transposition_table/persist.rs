@@ -0,0 +1,139 @@
+//! saving and loading transposition tables to and from disk
+//!
+//! ## File Format
+//! all integers are little-endian.
+//!
+//! **header:**
+//! * magic bytes: 8 bytes, [`MAGIC`]
+//! * format version: 2 bytes, [`VERSION`]
+//! * table size (capacity, in entries) of the saved table: 8 bytes
+//! * number of saved entries: 8 bytes
+//!
+//! **entries:**
+//! * key: 8 bytes
+//! * packed value: 8 bytes (the [`TableEntry`]'s [`AtomicU64`])
+//!
+//! only valid entries are saved, so a sparse table makes for a small file.
+//!
+//! [`AtomicU64`]: std::sync::atomic::AtomicU64
+use std::io::Read;
+use std::io::Write;
+
+use anyhow::Result;
+use anyhow::bail;
+
+use crate::optlog;
+use crate::transposition_table::entry::TableEntry;
+
+/// the first bytes of every saved table
+pub const MAGIC: [u8; 8] = *b"SANDYTT\0";
+
+/// the current version of the file format. version 2 added the static
+/// evaluation to the packed [`TableEntry`] value.
+pub const VERSION: u16 = 2;
+
+/// the file used by the UCI `Save Hash` and `Load Hash` buttons, unless set
+/// with the `Hash File` option
+pub const DEFAULT_HASH_FILE: &str = "sandy.tt";
+
+/// The header of a saved transposition table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableHeader {
+    /// the file format version
+    pub version: u16,
+    /// the capacity of the table that was saved, in entries
+    pub size: u64,
+    /// how many entries follow the header
+    pub count: u64,
+}
+
+impl TableHeader {
+    /// write the header, including the magic bytes
+    pub fn write<W: Write>(&self, w: &mut W) -> Result<()> {
+        w.write_all(&MAGIC)?;
+        w.write_all(&self.version.to_le_bytes())?;
+        w.write_all(&self.size.to_le_bytes())?;
+        w.write_all(&self.count.to_le_bytes())?;
+        Ok(())
+    }
+
+    /// read a header, checking the magic bytes and the version
+    pub fn read<R: Read>(r: &mut R) -> Result<Self> {
+        let mut magic = [0u8; 8];
+        r.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            bail!("not a transposition table file (magic bytes {magic:?})");
+        }
+        let version = u16::from_le_bytes(read_bytes(r)?);
+        if version != VERSION {
+            bail!("unsupported transposition table file version {version}, expected {VERSION}");
+        }
+        Ok(Self {
+            version,
+            size: u64::from_le_bytes(read_bytes(r)?),
+            count: u64::from_le_bytes(read_bytes(r)?),
+        })
+    }
+}
+
+/// read exactly `N` bytes
+fn read_bytes<R: Read, const N: usize>(r: &mut R) -> Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    r.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+/// write a table of capacity `size` to `w`, containing the given entries.
+///
+/// returns the number of entries written.
+pub fn write_entries<'a, W: Write>(
+    w: &mut W,
+    size: usize,
+    entries: impl Iterator<Item = &'a TableEntry>,
+) -> Result<usize> {
+    let entries = entries
+        .filter(|e| e.is_valid_entry())
+        .map(TableEntry::raw)
+        .collect::<Vec<_>>();
+    TableHeader {
+        version: VERSION,
+        size: size as u64,
+        count: entries.len() as u64,
+    }
+    .write(w)?;
+    for (key, value) in &entries {
+        w.write_all(&key.to_le_bytes())?;
+        w.write_all(&value.to_le_bytes())?;
+    }
+    Ok(entries.len())
+}
+
+/// read a saved table from `r`, passing every valid entry to `insert`.
+///
+/// entries that fail the validity (parity) check, or that hold a bound or a
+/// promotion no entry is packed with, are skipped as corrupted. returns the
+/// header of the saved table and the number of entries inserted.
+pub fn read_entries<R: Read>(
+    r: &mut R,
+    mut insert: impl FnMut(TableEntry),
+) -> Result<(TableHeader, usize)> {
+    let header = TableHeader::read(r)?;
+    let mut inserted = 0;
+    for _ in 0..header.count {
+        let key = u64::from_le_bytes(read_bytes(r)?);
+        let value = u64::from_le_bytes(read_bytes(r)?);
+        let entry = TableEntry::from_raw(key, value);
+        if entry.is_valid_entry() && entry.is_well_formed() {
+            insert(entry);
+            inserted += 1;
+        }
+    }
+    if inserted as u64 != header.count {
+        optlog!(tt;warn;"skipped {} corrupted entries", header.count - inserted as u64);
+    }
+    Ok((header, inserted))
+}
+
+#[cfg(test)]
+#[path = "tests/persist.rs"]
+mod tests;
@@ -0,0 +1,206 @@
+//! transposition table instrumentation
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::ops::Sub;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+/// A snapshot of the counters of a transposition table
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TableStats {
+    /// how many times the table was read from
+    pub probes: u64,
+    /// how many reads found a valid entry for the same position
+    pub hits: u64,
+    /// how many hits were good enough to cut off the search
+    pub cutoffs: u64,
+    /// how many reads found a valid entry, but for a different position
+    pub collisions: u64,
+    /// how many inserts replaced a valid entry
+    pub overwrites: u64,
+    /// how many overwrites replaced an entry searched deeper than the new one
+    pub replaced_deeper: u64,
+}
+
+/// how many stripes a [`StripedCounters`] has, enough for every search thread
+/// (see [`SEARCH_THREADS`]) to usually count on its own
+///
+/// [`SEARCH_THREADS`]: crate::search::SEARCH_THREADS
+const STRIPES: usize = 16;
+
+/// the next stripe to hand out to a thread
+static NEXT_STRIPE: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// the stripe the current thread counts in
+    static STRIPE: usize = NEXT_STRIPE.fetch_add(1, Ordering::Relaxed) % STRIPES;
+}
+
+/// one thread's copy of the counters, on a cache line of its own
+#[repr(align(64))]
+#[derive(Debug)]
+struct Stripe<const N: usize>([AtomicU64; N]);
+
+/// `N` counters that many threads update at once, eg the search threads
+/// probing a shared table. every thread counts in its own [`Stripe`], so that
+/// the threads don't fight over the cache line, and the stripes are summed
+/// when the counters are read.
+#[derive(Debug)]
+pub struct StripedCounters<const N: usize> {
+    /// the copies of the counters
+    stripes: Box<[Stripe<N>]>,
+}
+
+impl<const N: usize> Default for StripedCounters<N> {
+    fn default() -> Self {
+        Self {
+            stripes: (0..STRIPES)
+                .map(|_| Stripe(std::array::from_fn(|_| AtomicU64::new(0))))
+                .collect(),
+        }
+    }
+}
+
+impl<const N: usize> StripedCounters<N> {
+    /// add one to the `counter`th counter
+    #[inline(always)]
+    pub fn add(&self, counter: usize) {
+        STRIPE.with(|stripe| self.stripes[*stripe].0[counter].fetch_add(1, Ordering::Relaxed));
+    }
+
+    /// set all counters back to zero
+    pub fn reset(&self) {
+        for counter in self.stripes.iter().flat_map(|s| &s.0) {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// the sum of every counter over all the threads
+    pub fn load(&self) -> [u64; N] {
+        let mut sums = [0; N];
+        for stripe in self.stripes.iter() {
+            for (sum, counter) in sums.iter_mut().zip(&stripe.0) {
+                *sum += counter.load(Ordering::Relaxed);
+            }
+        }
+        sums
+    }
+}
+
+/// where each counter of [`TableStats`] is in [`StatCounters`]
+const PROBES: usize = 0;
+/// see [`PROBES`]
+const HITS: usize = 1;
+/// see [`PROBES`]
+const CUTOFFS: usize = 2;
+/// see [`PROBES`]
+const COLLISIONS: usize = 3;
+/// see [`PROBES`]
+const OVERWRITES: usize = 4;
+/// see [`PROBES`]
+const REPLACED_DEEPER: usize = 5;
+
+/// The counters behind [`TableStats`], safe to update through a shared
+/// reference (eg behind a read lock)
+#[derive(Debug, Default)]
+pub struct StatCounters(StripedCounters<6>);
+
+impl StatCounters {
+    /// count a read from the table
+    #[inline(always)]
+    pub fn probe(&self) {
+        self.0.add(PROBES);
+    }
+
+    /// count a read that found the position it was looking for
+    #[inline(always)]
+    pub fn hit(&self) {
+        self.0.add(HITS);
+    }
+
+    /// count a hit that cut off the search
+    #[inline(always)]
+    pub fn cutoff(&self) {
+        self.0.add(CUTOFFS);
+    }
+
+    /// count a read that found a different position at the same index
+    #[inline(always)]
+    pub fn collision(&self) {
+        self.0.add(COLLISIONS);
+    }
+
+    /// count an insert that replaced a valid entry, which may have been
+    /// searched deeper than the new one
+    #[inline(always)]
+    pub fn overwrite(&self, deeper: bool) {
+        self.0.add(OVERWRITES);
+        if deeper {
+            self.0.add(REPLACED_DEEPER);
+        }
+    }
+
+    /// set all counters back to zero
+    pub fn reset(&self) {
+        self.0.reset();
+    }
+
+    /// read the current value of all counters
+    pub fn load(&self) -> TableStats {
+        let counts = self.0.load();
+        TableStats {
+            probes: counts[PROBES],
+            hits: counts[HITS],
+            cutoffs: counts[CUTOFFS],
+            collisions: counts[COLLISIONS],
+            overwrites: counts[OVERWRITES],
+            replaced_deeper: counts[REPLACED_DEEPER],
+        }
+    }
+}
+
+impl TableStats {
+    /// the fraction of probes that were hits, in per mille
+    pub fn hit_rate(&self) -> u64 {
+        (self.hits * 1000)
+            .checked_div(self.probes)
+            .unwrap_or_default()
+    }
+}
+
+/// the counters accumulated between two snapshots
+impl Sub for TableStats {
+    type Output = TableStats;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        TableStats {
+            probes: self.probes.saturating_sub(rhs.probes),
+            hits: self.hits.saturating_sub(rhs.hits),
+            cutoffs: self.cutoffs.saturating_sub(rhs.cutoffs),
+            collisions: self.collisions.saturating_sub(rhs.collisions),
+            overwrites: self.overwrites.saturating_sub(rhs.overwrites),
+            replaced_deeper: self.replaced_deeper.saturating_sub(rhs.replaced_deeper),
+        }
+    }
+}
+
+impl Display for TableStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "probes {} hits {} hitrate {} cutoffs {} collisions {} overwrites {} replaceddeeper {}",
+            self.probes,
+            self.hits,
+            self.hit_rate(),
+            self.cutoffs,
+            self.collisions,
+            self.overwrites,
+            self.replaced_deeper
+        )
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/stats.rs"]
+mod tests;
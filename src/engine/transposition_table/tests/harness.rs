@@ -0,0 +1,48 @@
+use std::sync::Arc;
+use std::sync::RwLock;
+
+use crate::opts::Opts;
+use crate::setup::depth::Depth;
+use crate::transposition_table::TranspositionTable;
+use crate::transposition_table::empty_table::EmptyEntry;
+use crate::transposition_table::empty_table::EmptyTable;
+use crate::transposition_table::entry::TableEntry;
+use crate::transposition_table::harness::nodes_to_depth;
+use crate::transposition_table::harness::throughput;
+use crate::transposition_table::vl::VL;
+use crate::transposition_table::vl::VlShare;
+
+#[test]
+fn throughput_counts_operations() {
+    let vl: VlShare = Arc::new(RwLock::new(VL::new(1024 * 1024)));
+    let run = throughput(&vl, 4, 1000);
+    assert_eq!(run.inserts, 4000);
+    assert_eq!(run.probes, 4000 - 4 * 16);
+    // a table this big keeps almost all of the recent entries
+    assert!(run.hits > run.probes / 2, "{run:?}");
+    assert_eq!(vl.read().unwrap().stats().probes, run.probes);
+
+    let empty: EmptyTable<u64, TableEntry> = EmptyTable::new(0);
+    let run = throughput(&empty, 4, 1000);
+    assert_eq!(run.inserts, 4000);
+    assert_eq!(run.hits, 0);
+}
+
+#[test]
+fn table_saves_nodes_to_depth() {
+    let vl: VlShare = Arc::new(RwLock::new(VL::new(1024 * 1024)));
+    let empty: EmptyTable<u64, EmptyEntry> = EmptyTable::new(0);
+    let with_table = nodes_to_depth(&vl, Depth(3), &Opts::bench());
+    let without = nodes_to_depth(&empty, Depth(3), &Opts::bench());
+    assert_eq!(with_table.positions, without.positions);
+    assert!(with_table.nodes > 0);
+    assert!(
+        with_table.nodes < without.nodes,
+        "{with_table:?} {without:?}"
+    );
+    // a table that remembers nothing searches the same tree every time
+    assert_eq!(
+        nodes_to_depth(&empty, Depth(3), &Opts::bench()).nodes,
+        without.nodes
+    );
+}
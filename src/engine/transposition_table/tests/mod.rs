@@ -7,21 +7,25 @@ use chess::Square;
 use crate::setup::depth::Depth;
 use crate::setup::values::Value;
 use crate::transposition_table::EvalBound;
+use crate::transposition_table::TranspositionTable;
 use crate::transposition_table::entry::TableEntry;
+use crate::transposition_table::vl::VL;
 
 #[test]
 fn test_pack_then_unpack() {
     let key = 0x1234567890abcdef;
     let eval = Value(0x1234);
-    let depth = Depth(0x1234);
+    let static_eval = Value(-0x0123);
+    let depth = Depth(0x12);
     let mv = ChessMove::new(
         Square::from_str("a7").unwrap(),
         Square::from_str("a8").unwrap(),
         Some(Piece::Queen),
     );
     let bound = EvalBound::Exact;
-    let entry = TableEntry::pack(key, eval, depth, mv, bound, true);
+    let entry = TableEntry::pack(key, eval, static_eval, depth, mv, bound, true);
     assert_eq!(eval, entry.eval());
+    assert_eq!(Some(static_eval), entry.static_eval());
     assert_eq!(depth, entry.depth());
     assert_eq!(mv, entry.mv());
     assert_eq!(bound, entry.bound());
@@ -32,15 +36,17 @@ fn test_pack_then_unpack() {
 fn test_pack_then_unpack_with_promotion() {
     let key = 0x1234567890abcdef;
     let eval = Value(0x1234);
-    let depth = Depth(0x1234);
+    let static_eval = Value(-0x0123);
+    let depth = Depth(0x12);
     let mv = ChessMove::new(
         Square::from_str("a7").unwrap(),
         Square::from_str("a8").unwrap(),
         Some(Piece::Queen),
     );
     let bound = EvalBound::Exact;
-    let entry = TableEntry::pack(key, eval, depth, mv, bound, true);
+    let entry = TableEntry::pack(key, eval, static_eval, depth, mv, bound, true);
     assert_eq!(eval, entry.eval());
+    assert_eq!(Some(static_eval), entry.static_eval());
     assert_eq!(depth, entry.depth());
     assert_eq!(mv, entry.mv());
     assert_eq!(bound, entry.bound());
@@ -51,15 +57,17 @@ fn test_pack_then_unpack_with_promotion() {
 fn test_pack_then_unpack_with_no_promotion() {
     let key = 0x1234567890abcdef;
     let eval = Value(0x1234);
-    let depth = Depth(0x1234);
+    let static_eval = Value(-0x0123);
+    let depth = Depth(0x12);
     let mv = ChessMove::new(
         Square::from_str("a7").unwrap(),
         Square::from_str("a8").unwrap(),
         None,
     );
     let bound = EvalBound::Exact;
-    let entry = TableEntry::pack(key, eval, depth, mv, bound, false);
+    let entry = TableEntry::pack(key, eval, static_eval, depth, mv, bound, false);
     assert_eq!(eval, entry.eval());
+    assert_eq!(Some(static_eval), entry.static_eval());
     assert_eq!(depth, entry.depth());
     assert_eq!(mv, entry.mv());
     assert_eq!(bound, entry.bound());
@@ -70,17 +78,80 @@ fn test_pack_then_unpack_with_no_promotion() {
 fn test_pack_then_unpack_edge_case_values() {
     let key = 0x1234567890abcdef;
     let eval = Value(0x7fff);
-    let depth = Depth(0xffff);
+    let static_eval = Value::MIN;
+    let depth = Depth(0xff);
     let mv = ChessMove::new(
         Square::from_str("h8").unwrap(),
         Square::from_str("h1").unwrap(),
         Some(Piece::Queen),
     );
     let bound = EvalBound::UpperBound;
-    let entry = TableEntry::pack(key, eval, depth, mv, bound, true);
+    let entry = TableEntry::pack(key, eval, static_eval, depth, mv, bound, true);
     assert_eq!(eval, entry.eval());
+    assert_eq!(Some(static_eval), entry.static_eval());
     assert_eq!(depth, entry.depth());
     assert_eq!(mv, entry.mv());
     assert_eq!(bound, entry.bound());
     assert!(entry.is_pv());
 }
+
+#[test]
+fn clear_keeps_capacity() {
+    let mut table = VL::new(1024);
+    let capacity = table.capacity();
+    let mv = ChessMove::new(
+        Square::from_str("e2").unwrap(),
+        Square::from_str("e4").unwrap(),
+        None,
+    );
+    for key in 0..capacity as u64 {
+        let entry = TableEntry::pack(
+            key,
+            Value(1),
+            Value(1),
+            Depth(1),
+            mv,
+            EvalBound::Exact,
+            false,
+        );
+        table.insert(key, entry);
+    }
+    assert_eq!(table.entry_count(), capacity);
+
+    table.clear();
+    assert_eq!(table.capacity(), capacity);
+    assert_eq!(table.entry_count(), 0);
+    assert_eq!(table.hashfull(), 0);
+    for key in 0..capacity as u64 {
+        assert!(table.get(key).is_none_or(|e| !e.is_valid_entry()));
+    }
+
+    // the table is still usable after being cleared
+    let entry = TableEntry::pack(3, Value(7), Value(7), Depth(2), mv, EvalBound::Exact, false);
+    table.insert(3, entry);
+    assert_eq!(table.get(3).map(|e| e.eval()), Some(Value(7)));
+}
+
+#[test]
+fn test_depth_saturates() {
+    let mv = ChessMove::new(
+        Square::from_str("b1").unwrap(),
+        Square::from_str("c3").unwrap(),
+        None,
+    );
+    let entry = TableEntry::pack(
+        1,
+        Value(-5),
+        Value::NONE,
+        Depth(0x1234),
+        mv,
+        EvalBound::LowerBound,
+        false,
+    );
+    assert_eq!(entry.depth(), Depth(0xff));
+    assert_eq!(entry.eval(), Value(-5));
+    assert_eq!(entry.static_eval(), None);
+    assert_eq!(entry.mv(), mv);
+    assert_eq!(entry.bound(), EvalBound::LowerBound);
+    assert!(entry.is_valid_entry());
+}
@@ -0,0 +1,144 @@
+use std::str::FromStr;
+
+use chess::ChessMove;
+use chess::Square;
+
+use crate::Engine;
+use crate::setup::depth::Depth;
+use crate::setup::values::Value;
+use crate::transposition_table::EvalBound;
+use crate::transposition_table::TranspositionTable;
+use crate::transposition_table::entry::TableEntry;
+use crate::transposition_table::persist::TableHeader;
+use crate::transposition_table::persist::VERSION;
+use crate::transposition_table::persist::write_entries;
+use crate::transposition_table::vl::VL;
+
+/// a table of `bytes` bytes, with an entry at every key in `keys`
+fn filled_table(bytes: usize, keys: &[u64]) -> VL {
+    let mut table = VL::new(bytes);
+    let mv = ChessMove::new(
+        Square::from_str("g1").unwrap(),
+        Square::from_str("f3").unwrap(),
+        None,
+    );
+    for key in keys {
+        let entry = TableEntry::pack(
+            *key,
+            Value(*key as i16),
+            Value(-(*key as i16)),
+            Depth(3),
+            mv,
+            EvalBound::LowerBound,
+            false,
+        );
+        table.insert(*key, entry);
+    }
+    table
+}
+
+#[test]
+fn save_then_load() {
+    let keys = [1, 2, 3, 17, 30];
+    let table = filled_table(1024, &keys);
+
+    let mut buf = vec![];
+    assert_eq!(table.save(&mut buf).unwrap(), keys.len());
+
+    let header = TableHeader::read(&mut buf.as_slice()).unwrap();
+    assert_eq!(header.version, VERSION);
+    assert_eq!(header.size, table.capacity() as u64);
+    assert_eq!(header.count, keys.len() as u64);
+
+    let mut loaded = VL::new(1024);
+    assert_eq!(loaded.load(&mut buf.as_slice()).unwrap(), keys.len());
+    assert_eq!(loaded.entry_count(), keys.len());
+    for key in keys {
+        let original = table.get(key).unwrap();
+        let entry = loaded.get(key).unwrap();
+        assert_eq!(original.raw(), entry.raw());
+    }
+}
+
+#[test]
+fn load_into_different_size() {
+    let keys = [0x1234_0001, 0x5678_0002, 0x9abc_0003];
+    let table = filled_table(4096, &keys);
+    let mut buf = vec![];
+    table.save(&mut buf).unwrap();
+
+    // a smaller table, where the entries end up at different indices
+    let mut smaller = VL::new(256);
+    assert_ne!(smaller.capacity(), table.capacity());
+    assert_eq!(smaller.load(&mut buf.as_slice()).unwrap(), keys.len());
+    for key in keys {
+        assert_eq!(smaller.get(key).unwrap().eval(), Value(key as i16));
+    }
+}
+
+#[test]
+fn reject_invalid_files() {
+    assert!(VL::new(1024).load(&mut &b"not a table"[..]).is_err());
+
+    let mut buf = vec![];
+    filled_table(1024, &[5]).save(&mut buf).unwrap();
+    // bump the version
+    buf[8] = buf[8].wrapping_add(1);
+    assert!(VL::new(1024).load(&mut buf.as_slice()).is_err());
+}
+
+#[test]
+fn skip_corrupted_entries() {
+    let mut buf = vec![];
+    filled_table(1024, &[5, 6]).save(&mut buf).unwrap();
+    // flip one bit of the last entry's value, breaking its parity
+    let last = buf.len() - 1;
+    buf[last] ^= 0b100;
+
+    let mut loaded = VL::new(1024);
+    assert_eq!(loaded.load(&mut buf.as_slice()).unwrap(), 1);
+    assert_eq!(loaded.entry_count(), 1);
+}
+
+#[test]
+fn skip_malformed_entries() {
+    let table = filled_table(1024, &[5, 6, 7]);
+    let (_, value) = table.get(5).unwrap().raw();
+    // bound bits of 3, and promotion bits of 7, with the parity still right
+    let bad_bound = value | (0b11 << 7);
+    let bad_promotion = value | (0b111 << 9);
+    let entries = [
+        TableEntry::from_raw(5, bad_bound ^ (1 ^ (bad_bound.count_ones() as u64 & 1))),
+        TableEntry::from_raw(
+            6,
+            bad_promotion ^ (1 ^ (bad_promotion.count_ones() as u64 & 1)),
+        ),
+        table.get(7).unwrap(),
+    ];
+    assert!(entries.iter().all(TableEntry::is_valid_entry));
+
+    let mut buf = vec![];
+    assert_eq!(write_entries(&mut buf, 1024, entries.iter()).unwrap(), 3);
+    let mut loaded = VL::new(1024);
+    assert_eq!(loaded.load(&mut buf.as_slice()).unwrap(), 1);
+    assert!(loaded.get(5).is_none());
+    assert!(loaded.get(6).is_none());
+    assert_eq!(loaded.get(7).unwrap().bound(), EvalBound::LowerBound);
+}
+
+#[test]
+fn engine_save_then_load() {
+    let path = std::env::temp_dir().join(format!("sandy_tt_test_{}.tt", std::process::id()));
+    let mut engine = Engine::new().unwrap();
+    *engine.table.get().write().unwrap() = filled_table(1024, &[7, 8, 9]);
+
+    assert_eq!(engine.save_table(&path).unwrap(), 3);
+    engine.clear_table().unwrap();
+    assert_eq!(engine.load_table(&path).unwrap(), 3);
+    assert_eq!(
+        engine.table.get().read().unwrap().get(8).unwrap().eval(),
+        Value(8)
+    );
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,107 @@
+use std::str::FromStr;
+use std::sync::atomic::Ordering;
+
+use chess::Board;
+use chess::ChessMove;
+use chess::Square;
+
+use crate::opts::Opts;
+use crate::position::Position;
+use crate::search::SEARCHING;
+use crate::search::negamax::ng_bench;
+use crate::setup::depth::Depth;
+use crate::setup::values::Value;
+use crate::transposition_table::EvalBound;
+use crate::transposition_table::TT;
+use crate::transposition_table::TranspositionTable;
+use crate::transposition_table::entry::TableEntry;
+use crate::transposition_table::stats::StatCounters;
+use crate::transposition_table::stats::TableStats;
+use crate::transposition_table::vl::VL;
+
+#[test]
+fn counts_table_events() {
+    let mut table = VL::new(1024);
+    let size = table.capacity() as u64;
+    let mv = ChessMove::new(
+        Square::from_str("e2").unwrap(),
+        Square::from_str("e4").unwrap(),
+        None,
+    );
+    let entry = |key, depth| {
+        TableEntry::pack(
+            key,
+            Value(0),
+            Value::NONE,
+            Depth(depth),
+            mv,
+            EvalBound::Exact,
+            false,
+        )
+    };
+
+    table.insert(1, entry(1, 5));
+    assert!(table.get(1).is_some()); // hit
+    assert!(table.get(2).is_none()); // empty slot, no collision
+    assert!(table.get(1 + size).is_none()); // collision
+
+    table.insert(1, entry(1, 6)); // overwrite, shallower entry
+    table.insert(1 + size, entry(1 + size, 2)); // overwrite, deeper entry
+
+    assert_eq!(
+        table.stats(),
+        TableStats {
+            probes: 3,
+            hits: 1,
+            cutoffs: 0,
+            collisions: 1,
+            overwrites: 2,
+            replaced_deeper: 1,
+        }
+    );
+
+    table.clear();
+    assert_eq!(table.stats(), TableStats::default());
+}
+
+#[test]
+fn sums_counts_from_every_thread() {
+    let counters = StatCounters::default();
+    std::thread::scope(|s| {
+        for _ in 0..20 {
+            s.spawn(|| {
+                for _ in 0..100 {
+                    counters.probe();
+                }
+                counters.overwrite(true);
+            });
+        }
+    });
+    let stats = counters.load();
+    assert_eq!(stats.probes, 2000);
+    assert_eq!((stats.overwrites, stats.replaced_deeper), (20, 20));
+
+    counters.reset();
+    assert_eq!(counters.load(), TableStats::default());
+}
+
+#[test]
+fn search_reports_table_usage() {
+    let tt = TT::new();
+    tt.get().write().unwrap().resize(1024 * 1024);
+    let pos = Position::from(
+        Board::from_str("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 10")
+            .unwrap(),
+    );
+
+    SEARCHING.store(true, Ordering::Relaxed);
+    let result = ng_bench(pos, Depth(4), Value::MIN, Value::MAX, Opts::bench(), &tt).unwrap();
+
+    let stats = tt.stats();
+    assert!(stats.probes > 0, "{stats}");
+    assert!(stats.hits > 0, "{stats}");
+    assert!(stats.cutoffs > 0, "{stats}");
+    assert!(stats.cutoffs <= stats.hits, "{stats}");
+    // there are no tablebases to hit
+    assert_eq!(result.tb_hits, 0);
+}
@@ -1,8 +1,12 @@
 //! a lock-based Vec transposition table
 
+use std::io::Read;
+use std::io::Write;
 use std::sync::Arc;
 use std::sync::RwLock;
 
+use anyhow::Result;
+use anyhow::bail;
 use chess::Board;
 
 use crate::optlog;
@@ -11,6 +15,10 @@ use crate::transposition_table::TKey;
 use crate::transposition_table::TableAccess;
 use crate::transposition_table::TranspositionTable;
 use crate::transposition_table::entry::TableEntry;
+use crate::transposition_table::persist::read_entries;
+use crate::transposition_table::persist::write_entries;
+use crate::transposition_table::stats::StatCounters;
+use crate::transposition_table::stats::TableStats;
 
 /// a lock-based Vec transposition table
 #[derive(Debug)]
@@ -21,6 +29,8 @@ pub struct VL {
     size: usize,
     /// number of valid entries in the table
     occupied: usize,
+    /// instrumentation of how the table is used
+    stats: StatCounters,
 }
 
 impl TranspositionTable<u64, TableEntry> for VL {
@@ -36,6 +46,7 @@ impl TranspositionTable<u64, TableEntry> for VL {
             table,
             size,
             occupied: 0,
+            stats: StatCounters::default(),
         }
     }
 
@@ -58,27 +69,40 @@ impl TranspositionTable<u64, TableEntry> for VL {
     }
 
     fn get(&self, hash: u64) -> Option<TableEntry> {
+        self.stats.probe();
         let idx = (hash as usize) % self.size;
-        let entry = self.table.get(idx);
-        if entry.is_some_and(|e| hash.equals(&e.key())) {
-            entry.cloned()
-        } else {
-            None
+        match self.table.get(idx) {
+            Some(e) if hash.equals(&e.key()) => {
+                if e.is_valid_entry() {
+                    self.stats.hit();
+                }
+                Some(e.clone())
+            }
+            Some(e) if e.is_valid_entry() => {
+                self.stats.collision();
+                None
+            }
+            _ => None,
         }
     }
 
     fn insert(&mut self, hash: u64, entry: TableEntry) {
         let idx = (hash as usize) % self.size;
-        if !self.table[idx].is_valid_entry() {
+        let old = &self.table[idx];
+        if !old.is_valid_entry() {
             self.occupied += 1;
+        } else {
+            self.stats.overwrite(old.depth() > entry.depth());
         }
         self.table[idx] = entry;
         debug_assert!(self.table[idx].is_valid_entry());
     }
 
     fn clear(&mut self) {
-        self.table.clear();
+        // keep the allocation (and `size`), only invalidate every entry
+        self.table.fill(TableEntry::new_empty());
         self.occupied = 0;
+        self.stats.reset();
     }
 
     fn entry_count(&self) -> usize {
@@ -90,10 +114,38 @@ impl TranspositionTable<u64, TableEntry> for VL {
     }
 
     fn hashfull(&self) -> usize {
-        (self.occupied * 1000)
+        (self.entry_count() * 1000)
             .checked_div(self.size)
             .unwrap_or_default()
     }
+
+    fn stats(&self) -> TableStats {
+        self.stats.load()
+    }
+}
+
+impl VL {
+    /// save the valid entries of this table, in the [`persist`] format.
+    /// returns the number of entries saved.
+    ///
+    /// [`persist`]: crate::transposition_table::persist
+    pub fn save<W: Write>(&self, w: &mut W) -> Result<usize> {
+        write_entries(w, self.size, self.table.iter())
+    }
+
+    /// load a saved table into this one, keeping this table's size.
+    /// if the sizes differ, the entries are rehashed into this table.
+    /// returns the number of entries loaded.
+    pub fn load<R: Read>(&mut self, r: &mut R) -> Result<usize> {
+        if self.size == 0 {
+            bail!("cannot load entries into a table of size 0");
+        }
+        let (header, loaded) = read_entries(r, |entry| self.insert(entry.key(), entry))?;
+        if header.size != self.size as u64 {
+            optlog!(tt;info;"rehashed {loaded} entries from a table of {} entries into {}.", header.size, self.size);
+        }
+        Ok(loaded)
+    }
 }
 
 impl TKey for u64 {
@@ -111,10 +163,28 @@ impl TKey for u64 {
 pub type VlShare = Arc<RwLock<VL>>;
 
 impl TableAccess<u64, TableEntry, VL> for VlShare {
-    fn hit(&self) {
-        // to-do
-        // currently hit counts are accumulated in the search functionality as
-        // TB_HITS
+    fn cutoff(&self) {
+        if let Ok(table) = self.read() {
+            table.stats.cutoff();
+        }
+    }
+
+    #[inline]
+    fn probe(&self, hash: u64) -> Option<TableEntry> {
+        self.read().ok().and_then(|table| table.get(hash))
+    }
+
+    #[inline]
+    fn store(&self, hash: u64, entry: TableEntry) {
+        if let Ok(mut table) = self.write() {
+            table.insert(hash, entry);
+        }
+    }
+
+    fn clear(&self) {
+        if let Ok(mut table) = self.write() {
+            table.clear();
+        }
     }
 
     fn share(&self) -> VlShare {
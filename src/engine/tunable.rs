@@ -0,0 +1,128 @@
+//! search and time-management constants that can be tuned.
+//!
+//! normally every [`Tunable`] is a plain constant. when the engine is built
+//! with the `tune` feature, each one becomes a UCI spin option (see
+//! [`crate::opts::Opts::register_options`]), so that an external driver can
+//! change them between games, eg the `spsa` subcommand of the CLI.
+//!
+//! to make a new constant tunable, add it to the [`tunables!`] list below and
+//! read it with [`Tunable::get`].
+#[cfg(feature = "tune")]
+use std::sync::atomic::AtomicI32;
+#[cfg(feature = "tune")]
+use std::sync::atomic::Ordering;
+
+#[cfg(feature = "tune")]
+use anyhow::Result;
+#[cfg(feature = "tune")]
+use anyhow::bail;
+use vampirc_uci::UciOptionConfig;
+
+/// a constant of the search that can be tuned
+#[derive(Debug)]
+pub struct Tunable {
+    /// the name of the UCI option
+    pub name: &'static str,
+    /// the value used when not tuning
+    pub default: i32,
+    /// the smallest sensible value
+    pub min: i32,
+    /// the largest sensible value
+    pub max: i32,
+    /// the value currently in use
+    #[cfg(feature = "tune")]
+    value: AtomicI32,
+}
+
+impl Tunable {
+    /// a new tunable constant
+    const fn new(name: &'static str, default: i32, min: i32, max: i32) -> Self {
+        Self {
+            name,
+            default,
+            min,
+            max,
+            #[cfg(feature = "tune")]
+            value: AtomicI32::new(default),
+        }
+    }
+
+    /// the value currently in use, which is always the default without the
+    /// `tune` feature
+    #[inline(always)]
+    pub fn get(&self) -> i32 {
+        #[cfg(feature = "tune")]
+        {
+            self.value.load(Ordering::Relaxed)
+        }
+        #[cfg(not(feature = "tune"))]
+        {
+            self.default
+        }
+    }
+
+    /// change the value in use, as long as it is within bounds
+    #[cfg(feature = "tune")]
+    pub fn set(&self, value: i32) -> Result<()> {
+        if !(self.min..=self.max).contains(&value) {
+            bail!(
+                "value {value} for {} is out of bounds [{}, {}]",
+                self.name,
+                self.min,
+                self.max
+            );
+        }
+        self.value.store(value, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// the UCI option for this constant
+    pub fn option(&self) -> UciOptionConfig {
+        UciOptionConfig::Spin {
+            name: self.name.to_string(),
+            default: Some(self.default as i64),
+            min: Some(self.min as i64),
+            max: Some(self.max as i64),
+        }
+    }
+}
+
+/// the tunable constant called `name`, if there is one
+pub fn find(name: &str) -> Option<&'static Tunable> {
+    TUNABLES.iter().copied().find(|t| t.name == name)
+}
+
+/// declare [`Tunable`]s, and list them all in [`TUNABLES`]
+macro_rules! tunables {
+    ($($(#[$doc:meta])* $ident:ident: $name:literal = $default:literal, $min:literal..=$max:literal;)*) => {
+        $(
+            $(#[$doc])*
+            pub static $ident: Tunable = Tunable::new($name, $default, $min, $max);
+        )*
+
+        /// every tunable constant
+        pub static TUNABLES: &[&Tunable] = &[$(&$ident),*];
+    };
+}
+
+tunables! {
+    /// the largest allowed value for sel_depth - target_depth in a search, in
+    /// plies
+    MAX_EXTEND: "max_extend" = 16, 0..=64;
+    /// how many more moves the game is expected to last, when the GUI doesn't
+    /// say
+    MOVES_LEFT: "moves_left" = 50, 5..=120;
+    /// if the time-remaining difference between the players is greater than
+    /// this (in milliseconds), maybe we should change our time management a
+    /// bit
+    CONCERN_THRESHOLD: "concern_threshold" = 15000, 0..=60000;
+    /// when we have much more time than the opponent, their difference is
+    /// spread over the moves left divided by this
+    TIME_LEAD_DIVISOR: "time_lead_divisor" = 4, 1..=16;
+    /// nodes at most this many plies from the horizon are cut off when their
+    /// static evaluation beats beta by [`RFP_MARGIN`] per ply (reverse
+    /// futility pruning)
+    RFP_DEPTH: "rfp_depth" = 3, 0..=8;
+    /// the margin of reverse futility pruning, in centipawns per ply
+    RFP_MARGIN: "rfp_margin" = 120, 20..=400;
+}
@@ -0,0 +1,321 @@
+//! reading labelled positions for the tuner: FENs with game results, every
+//! position of the games in a PGN file, or positions generated with self-play
+//! ([`DataPoint`]), as text or in a compact binary format
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use chess::ALL_PIECES;
+use chess::BitBoard;
+use chess::Board;
+use chess::BoardBuilder;
+use chess::CastleRights;
+use chess::ChessMove;
+use chess::Color;
+use chess::File;
+use log::warn;
+
+/// how many plies at the start of every game are skipped, as the positions
+/// come from the opening book more often than not
+pub const SKIP_OPENING_PLIES: usize = 8;
+
+/// a position, labelled with the result of the game it was played in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    /// the position
+    pub board: Board,
+    /// the result of the game for white: 1 for a win, 0.5 for a draw, 0 for
+    /// a loss
+    pub result: f64,
+}
+
+/// parse a game result, in any of the forms `1-0`, `1/2-1/2`, `0.5`,
+/// `[1.0]` or `"0-1";`
+pub fn parse_result(token: &str) -> Option<f64> {
+    match token.trim_matches(|c| matches!(c, '[' | ']' | '"' | ';' | '(' | ')')) {
+        "1-0" | "1.0" => Some(1.0),
+        "1/2-1/2" | "0.5" | "1/2" => Some(0.5),
+        "0-1" | "0.0" => Some(0.0),
+        _ => None,
+    }
+}
+
+/// the first bytes of a file of binary [`DataPoint`]s
+pub const BINARY_MAGIC: &[u8; 8] = b"SANDYDG1";
+
+/// the size of a [`DataPoint`] in the binary format
+pub const RECORD_SIZE: usize = 32;
+
+/// a position from self-play, with the score the search gave it and the
+/// result of the game
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DataPoint {
+    /// the position
+    pub board: Board,
+    /// the score of the search, in centipawns, for white
+    pub score: i16,
+    /// the result of the game for white, as in [`Sample`]
+    pub result: f64,
+}
+
+impl From<DataPoint> for Sample {
+    fn from(point: DataPoint) -> Self {
+        Self {
+            board: point.board,
+            result: point.result,
+        }
+    }
+}
+
+impl DataPoint {
+    /// the text format, `<fen> | <score> | <result>`, which
+    /// [`parse_labelled`] reads
+    pub fn to_text(&self) -> String {
+        format!("{} | {} | {:.1}", self.board, self.score, self.result)
+    }
+
+    /// the binary format, [`RECORD_SIZE`] bytes:
+    /// * the occupied squares, as a little-endian `u64`
+    /// * a nibble for the piece on each occupied square, from a1 to h8, the low
+    ///   nibble first: `8 * colour + piece`
+    /// * the side to move in bit 0, and the castling rights of white and black
+    ///   in bits 1-2 and 3-4
+    /// * the file of the en passant square plus one, 0 if there is none
+    /// * the score, as a little-endian `i16`
+    /// * the result: 0 for a loss, 1 for a draw, 2 for a win
+    /// * 3 bytes of padding
+    pub fn encode(&self) -> [u8; RECORD_SIZE] {
+        let board = &self.board;
+        let mut bytes = [0; RECORD_SIZE];
+        bytes[..8].copy_from_slice(&board.combined().0.to_le_bytes());
+        for (i, sq) in board.combined().enumerate() {
+            // SAFETY: the square is occupied
+            let piece = board.piece_on(sq).unwrap();
+            let color = board.color_on(sq).unwrap();
+            let nibble = (color.to_index() * 8 + piece.to_index()) as u8;
+            bytes[8 + i / 2] |= nibble << (4 * (i % 2));
+        }
+        bytes[24] = (board.side_to_move() == Color::Black) as u8
+            | (board.castle_rights(Color::White).to_index() as u8) << 1
+            | (board.castle_rights(Color::Black).to_index() as u8) << 3;
+        bytes[25] = board
+            .en_passant()
+            .map_or(0, |sq| sq.get_file().to_index() as u8 + 1);
+        bytes[26..28].copy_from_slice(&self.score.to_le_bytes());
+        bytes[28] = (self.result * 2.0).round() as u8;
+        bytes
+    }
+
+    /// the inverse of [`DataPoint::encode`]
+    pub fn decode(bytes: &[u8; RECORD_SIZE]) -> Result<Self> {
+        let occupied = BitBoard(u64::from_le_bytes(bytes[..8].try_into()?));
+        if occupied.popcnt() > 32 {
+            bail!("too many pieces");
+        }
+        let mut builder = BoardBuilder::new();
+        for (i, sq) in occupied.enumerate() {
+            let nibble = (bytes[8 + i / 2] >> (4 * (i % 2))) as usize & 0xf;
+            let piece = *ALL_PIECES
+                .get(nibble & 7)
+                .ok_or_else(|| anyhow!("invalid piece on {sq}"))?;
+            let color = if nibble & 8 == 0 {
+                Color::White
+            } else {
+                Color::Black
+            };
+            builder.piece(sq, piece, color);
+        }
+        let flags = bytes[24] as usize;
+        builder
+            .side_to_move(if flags & 1 == 0 {
+                Color::White
+            } else {
+                Color::Black
+            })
+            .castle_rights(Color::White, CastleRights::from_index(flags >> 1 & 3))
+            .castle_rights(Color::Black, CastleRights::from_index(flags >> 3 & 3))
+            .en_passant(match bytes[25] {
+                0 => None,
+                file @ 1..=8 => Some(File::from_index(file as usize - 1)),
+                _ => bail!("invalid en passant file"),
+            });
+        let board = Board::try_from(&builder).map_err(|e| anyhow!("invalid position: {e}"))?;
+        if bytes[28] > 2 {
+            bail!("invalid result");
+        }
+        Ok(Self {
+            board,
+            score: i16::from_le_bytes([bytes[26], bytes[27]]),
+            result: bytes[28] as f64 / 2.0,
+        })
+    }
+}
+
+/// parse a file of binary [`DataPoint`]s: [`BINARY_MAGIC`], followed by
+/// records of [`RECORD_SIZE`] bytes
+pub fn parse_binary(bytes: &[u8]) -> Result<Vec<DataPoint>> {
+    let Some(records) = bytes.strip_prefix(BINARY_MAGIC) else {
+        bail!("not a binary data file");
+    };
+    if records.len() % RECORD_SIZE != 0 {
+        bail!("truncated record at the end");
+    }
+    records
+        .chunks_exact(RECORD_SIZE)
+        .enumerate()
+        .map(|(i, record)| {
+            // SAFETY: the chunks are exactly this size
+            DataPoint::decode(record.try_into().unwrap())
+                .with_context(|| format!("record {}", i + 1))
+        })
+        .collect()
+}
+
+/// read the samples from a file: binary [`DataPoint`]s if it starts with
+/// [`BINARY_MAGIC`], a PGN file if it has the `.pgn` extension, labelled FENs
+/// otherwise
+pub fn read_samples(path: impl AsRef<Path>) -> Result<Vec<Sample>> {
+    let path = path.as_ref();
+    let bytes = fs::read(path)
+        .with_context(|| format!("could not read positions from {}", path.display()))?;
+    if bytes.starts_with(BINARY_MAGIC) {
+        return parse_binary(&bytes)
+            .map(|points| points.into_iter().map(Sample::from).collect())
+            .with_context(|| format!("invalid positions in {}", path.display()));
+    }
+    let text = String::from_utf8(bytes)
+        .with_context(|| format!("could not read positions from {}", path.display()))?;
+    let is_pgn = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pgn"));
+    if is_pgn {
+        parse_pgn(&text)
+    } else {
+        parse_labelled(&text)
+    }
+    .with_context(|| format!("invalid positions in {}", path.display()))
+}
+
+/// parse one labelled position per line: a FEN (the move counters may be left
+/// out, as in EPD), followed by the result. anything after the result, and
+/// lines starting with `#`, are ignored.
+pub fn parse_labelled(text: &str) -> Result<Vec<Sample>> {
+    let mut samples = vec![];
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let tokens: Vec<&str> = line
+            .split(|c: char| c.is_whitespace() || c == '|' || c == ',')
+            .filter(|t| !t.is_empty())
+            .collect();
+        // the result is the first token after the 4 fields every FEN has
+        let Some((idx, result)) = tokens
+            .iter()
+            .enumerate()
+            .skip(4)
+            .find_map(|(idx, t)| parse_result(t).map(|r| (idx, r)))
+        else {
+            bail!("line {}: no result found", number + 1);
+        };
+        let mut fen = tokens[..idx.min(6)].join(" ");
+        if idx == 4 {
+            fen += " 0 1";
+        }
+        let board = Board::from_str(&fen)
+            .map_err(|e| anyhow!("line {}: invalid FEN {fen:?}: {e}", number + 1))?;
+        samples.push(Sample { board, result });
+    }
+    Ok(samples)
+}
+
+/// parse every game of a PGN file, and label all of its positions (after the
+/// first [`SKIP_OPENING_PLIES`]) with its result. games without a result are
+/// skipped, as are games that start from a custom position, and games with a
+/// move that can't be played, which are logged.
+pub fn parse_pgn(text: &str) -> Result<Vec<Sample>> {
+    let mut samples = vec![];
+    let mut game = 1;
+    let mut movetext = String::new();
+    let mut result = None;
+    let mut custom_start = false;
+    let mut in_moves = false;
+
+    for line in text.lines().chain(std::iter::once("[Event \"end\"]")) {
+        let line = line.trim();
+        if line.starts_with('[') {
+            if in_moves {
+                // the headers of the next game
+                if let (Some(result), false) = (result, custom_start) {
+                    match game_samples(&movetext, result) {
+                        Ok(game_samples) => samples.extend(game_samples),
+                        Err(e) => warn!("skipping game {game}: {e}"),
+                    }
+                }
+                game += 1;
+                movetext.clear();
+                result = None;
+                custom_start = false;
+                in_moves = false;
+            }
+            if let Some(value) = line.strip_prefix("[Result ") {
+                result = parse_result(value.trim_end_matches(']'));
+            }
+            custom_start |= line.starts_with("[FEN ") || line.starts_with("[SetUp ");
+        } else if !line.is_empty() {
+            in_moves = true;
+            movetext += line;
+            movetext += " ";
+        }
+    }
+
+    Ok(samples)
+}
+
+/// play through the moves of a game, collecting its positions
+fn game_samples(movetext: &str, result: f64) -> Result<Vec<Sample>> {
+    let mut board = Board::default();
+    let mut samples = vec![];
+    for (ply, san) in san_moves(movetext).into_iter().enumerate() {
+        let mv = ChessMove::from_san(&board, &san)
+            .map_err(|e| anyhow!("invalid move {san:?} in {board}: {e}"))?;
+        board = board.make_move_new(mv);
+        if ply + 1 >= SKIP_OPENING_PLIES {
+            samples.push(Sample { board, result });
+        }
+    }
+    Ok(samples)
+}
+
+/// the moves of a game's movetext, without comments, variations, move
+/// numbers, annotations, or the result
+fn san_moves(movetext: &str) -> Vec<String> {
+    let mut clean = String::new();
+    let (mut comment, mut variation) = (false, 0);
+    for c in movetext.chars() {
+        match c {
+            '{' => comment = true,
+            '}' => comment = false,
+            '(' if !comment => variation += 1,
+            ')' if !comment => variation -= 1,
+            _ if comment || variation > 0 => {}
+            c => clean.push(c),
+        }
+    }
+    clean
+        .split_whitespace()
+        .filter(|t| parse_result(t).is_none() && *t != "*")
+        .map(|t| t.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.'))
+        .filter(|t| !t.is_empty() && !t.starts_with('$'))
+        .map(|t| t.replace('=', "").trim_end_matches(['!', '?']).to_string())
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "tests/data.rs"]
+mod tests;
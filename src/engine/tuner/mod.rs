@@ -0,0 +1,361 @@
+//! Texel tuning of the evaluation parameters against game results.
+//!
+//! every labelled position is first resolved with a capture-only search, so
+//! that the static evaluation of the quiet position it leads to can be
+//! compared with the result of the game. the error is the mean squared
+//! difference between the results and `sigmoid(K * eval)`, and it is
+//! minimised with a coordinate descent (local search) over every parameter in
+//! [`EvalParams`].
+//!
+//! <https://www.chessprogramming.org/Texel%27s_Tuning_Method>
+//!
+//! the search constants, which can't be judged on static positions, are tuned
+//! with self-play instead, see [`spsa`]. the network evaluation is trained
+//! by gradient descent, see `nnue` (with `--features nnue`).
+pub mod data;
+#[cfg(feature = "nnue")]
+pub mod nnue;
+pub mod spsa;
+
+use chess::Board;
+use chess::Color;
+use chess::EMPTY;
+use chess::MoveGen;
+use rayon::iter::IntoParallelRefIterator;
+use rayon::iter::ParallelIterator;
+
+use crate::evaluation::bitboards::PestoTable;
+use crate::evaluation::evaluate;
+use crate::evaluation::params::EvalParams;
+use crate::evaluation::params::params;
+use crate::evaluation::params::set_params;
+use crate::evaluation::score::Score;
+use crate::position::Position;
+use crate::setup::values::Value;
+use crate::tuner::data::Sample;
+
+/// the options of a tuning run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TuneOptions {
+    /// the most passes over all the parameters, the tuner stops earlier when
+    /// a pass changes nothing
+    pub passes: usize,
+    /// how much a parameter is changed by at a time
+    pub step: i32,
+}
+
+impl Default for TuneOptions {
+    fn default() -> Self {
+        Self {
+            passes: 100,
+            step: 1,
+        }
+    }
+}
+
+/// the expected score of white for an evaluation (from white's point of view)
+#[inline(always)]
+pub fn sigmoid(eval: f64, k: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * eval / 400.0))
+}
+
+/// a capture-only search, returning the value of `pos` for the side to move
+/// and the quiet position at the end of its principal variation
+pub fn quiesce(pos: &Position, mut alpha: Value, beta: Value) -> (Value, Position) {
+    let stand_pat = evaluate(pos, false);
+    if stand_pat >= beta {
+        return (stand_pat, pos.clone());
+    }
+    alpha = alpha.max(stand_pat);
+
+    let mut best = (stand_pat, None);
+    let mut captures = MoveGen::new_legal(&pos.chessboard);
+    let stm = pos.chessboard.side_to_move();
+    captures.set_iterator_mask(*pos.chessboard.color_combined(!stm));
+    for mv in captures {
+        let (value, leaf) = quiesce(&pos.make_move(mv), -beta, -alpha);
+        let value = -value;
+        if value > best.0 {
+            best = (value, Some(leaf));
+        }
+        alpha = alpha.max(value);
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    (best.0, best.1.unwrap_or_else(|| pos.clone()))
+}
+
+/// replace every sample by the quiet position its capture sequence leads to.
+/// positions in check, and positions without legal moves, are dropped, as
+/// their static evaluation says little about them.
+pub fn resolve(samples: &[Sample]) -> Vec<Sample> {
+    samples
+        .par_iter()
+        .filter(|s| *s.board.checkers() == EMPTY && MoveGen::new_legal(&s.board).len() > 0)
+        .map(|s| {
+            let (_, leaf) = quiesce(&Position::from(s.board), Value::MIN, Value::MAX);
+            Sample {
+                board: leaf.chessboard,
+                result: s.result,
+            }
+        })
+        .collect()
+}
+
+/// the static evaluation of `board` from white's point of view, with the
+/// parameters currently in use
+fn white_eval(board: &Board) -> f64 {
+    let value = evaluate(&Position::from(*board), false).0 as f64;
+    if board.side_to_move() == Color::White {
+        value
+    } else {
+        -value
+    }
+}
+
+/// the mean squared error of the evaluations (from white's point of view)
+/// against the results of `samples`
+fn mean_error(evals: &[f64], samples: &[Sample], k: f64) -> f64 {
+    let total: f64 = evals
+        .iter()
+        .zip(samples)
+        .map(|(eval, s)| (s.result - sigmoid(*eval, k)).powi(2))
+        .sum();
+    total / samples.len().max(1) as f64
+}
+
+/// the error of the parameters currently in use over `samples`
+pub fn error(samples: &[Sample], k: f64) -> f64 {
+    let total: f64 = samples
+        .par_iter()
+        .map(|s| (s.result - sigmoid(white_eval(&s.board), k)).powi(2))
+        .sum();
+    total / samples.len().max(1) as f64
+}
+
+/// the scaling constant `K` that minimises the error of the parameters
+/// currently in use, found with a golden section search
+pub fn find_k(samples: &[Sample]) -> f64 {
+    let evals: Vec<f64> = samples.par_iter().map(|s| white_eval(&s.board)).collect();
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let (mut low, mut high) = (0.0, 10.0);
+    while high - low > 1e-4 {
+        let a = high - ratio * (high - low);
+        let b = low + ratio * (high - low);
+        if mean_error(&evals, samples, a) < mean_error(&evals, samples, b) {
+            high = b;
+        } else {
+            low = a;
+        }
+    }
+    (low + high) / 2.0
+}
+
+/// the index of the pawn structure weights in [`flatten`], after the piece
+/// values, tempo, piece-square tables and king safety
+const PAWNS: usize = 3 * 5 + 1 + 2 * 6 * 64 + 3 + 4 + 2 + 4 + 2;
+
+/// the number of values in [`flatten`]: the piece values, tempo, piece-square
+/// tables and king safety, then the pawn, mobility, activity, threat and
+/// endgame weights
+pub const PARAM_COUNT: usize =
+    PAWNS + 2 * (3 + 8 + 8) + 2 + 2 * 4 + 2 * 5 + 2 * (1 + 6 + 6 + 2 + 4) + 5;
+
+/// every parameter as a flat list, in the order of the fields of
+/// [`EvalParams`]. scores are split into their middlegame and endgame values.
+pub fn flatten(params: &EvalParams) -> Vec<i32> {
+    let table = |tables: &[PestoTable; 6]| -> Vec<i32> {
+        tables
+            .as_flattened()
+            .as_flattened()
+            .iter()
+            .map(|v| *v as i32)
+            .collect()
+    };
+    let scores =
+        |scores: &[Score]| -> Vec<i32> { scores.iter().flat_map(|s| [s.mg(), s.eg()]).collect() };
+    let ks = &params.king_safety;
+    let pawns = &params.pawns;
+    let activity = &params.activity;
+    let threats = &params.threats;
+    let endgame = &params.endgame;
+    [
+        &params.initial_values[..],
+        &params.midgame_values,
+        &params.endgame_values,
+        &[params.tempo],
+        &table(&params.mg_psqt),
+        &table(&params.eg_psqt),
+        &ks.shield,
+        &ks.storm,
+        &[ks.semi_open_file, ks.open_file],
+        &ks.attack_weight,
+        &[ks.danger_scale, ks.max_danger],
+        &scores(&[pawns.doubled, pawns.isolated, pawns.backward]),
+        &scores(&pawns.connected),
+        &scores(&pawns.passed),
+        &[pawns.passed_enemy_king, pawns.passed_own_king],
+        &scores(&params.mobility),
+        &scores(&[
+            activity.bishop_pair,
+            activity.rook_open_file,
+            activity.rook_semi_open_file,
+            activity.rook_on_seventh,
+            activity.knight_outpost,
+        ]),
+        &scores(&[threats.by_safe_pawn]),
+        &scores(&threats.by_minor),
+        &scores(&threats.by_rook),
+        &scores(&[threats.hanging, threats.pawn_push]),
+        &scores(&threats.safe_check),
+        &[
+            endgame.opposite_bishops,
+            endgame.no_pawns,
+            endgame.mop_up_edge,
+            endgame.mop_up_corner,
+            endgame.mop_up_kings,
+        ],
+    ]
+    .concat()
+}
+
+/// the inverse of [`flatten`]
+pub fn unflatten(values: &[i32]) -> EvalParams {
+    assert_eq!(values.len(), PARAM_COUNT, "wrong number of parameters");
+    let mut values = values.iter().copied();
+    let mut next = || values.next().unwrap_or_default();
+    let mut params = EvalParams::DEFAULT;
+
+    let ks = &mut params.king_safety;
+    for field in [
+        &mut params.initial_values,
+        &mut params.midgame_values,
+        &mut params.endgame_values,
+    ] {
+        field.iter_mut().for_each(|v| *v = next());
+    }
+    params.tempo = next();
+    for tables in [&mut params.mg_psqt, &mut params.eg_psqt] {
+        for v in tables.as_flattened_mut().as_flattened_mut() {
+            *v = next() as i16;
+        }
+    }
+    ks.shield.iter_mut().for_each(|v| *v = next());
+    ks.storm.iter_mut().for_each(|v| *v = next());
+    ks.semi_open_file = next();
+    ks.open_file = next();
+    ks.attack_weight.iter_mut().for_each(|v| *v = next());
+    ks.danger_scale = next();
+    ks.max_danger = next();
+
+    let pawns = &mut params.pawns;
+    for score in [&mut pawns.doubled, &mut pawns.isolated, &mut pawns.backward]
+        .into_iter()
+        .chain(&mut pawns.connected)
+        .chain(&mut pawns.passed)
+    {
+        *score = Score::new(next(), next());
+    }
+    pawns.passed_enemy_king = next();
+    pawns.passed_own_king = next();
+    for score in &mut params.mobility {
+        *score = Score::new(next(), next());
+    }
+    let activity = &mut params.activity;
+    for score in [
+        &mut activity.bishop_pair,
+        &mut activity.rook_open_file,
+        &mut activity.rook_semi_open_file,
+        &mut activity.rook_on_seventh,
+        &mut activity.knight_outpost,
+    ] {
+        *score = Score::new(next(), next());
+    }
+    let threats = &mut params.threats;
+    for score in std::iter::once(&mut threats.by_safe_pawn)
+        .chain(&mut threats.by_minor)
+        .chain(&mut threats.by_rook)
+        .chain([&mut threats.hanging, &mut threats.pawn_push])
+        .chain(&mut threats.safe_check)
+    {
+        *score = Score::new(next(), next());
+    }
+    let endgame = &mut params.endgame;
+    endgame.opposite_bishops = next();
+    endgame.no_pawns = next();
+    endgame.mop_up_edge = next();
+    endgame.mop_up_corner = next();
+    endgame.mop_up_kings = next();
+
+    params
+}
+
+/// whether the parameter at `idx` of [`flatten`] can't affect the evaluation:
+/// the piece-square values of pawns on the first and last ranks, and the
+/// connected and passed pawn bonuses for those ranks
+fn frozen(idx: usize) -> bool {
+    let first_table = 3 * 5 + 1;
+    let on_edge_ranks = [first_table, first_table + 6 * 64]
+        .into_iter()
+        .any(|pawns| (pawns..pawns + 8).contains(&idx) || (pawns + 56..pawns + 64).contains(&idx));
+    // the connected and passed bonuses, after the other 3 pawn scores
+    let by_rank = [PAWNS + 2 * 3, PAWNS + 2 * (3 + 8)]
+        .into_iter()
+        .any(|ranks| [ranks, ranks + 1, ranks + 14, ranks + 15].contains(&idx));
+    on_edge_ranks || by_rank
+}
+
+/// tune the parameters, starting from `start`, to minimise the [`error`] over
+/// `samples` (which should be [`resolve`]d first) with the scaling constant
+/// `k` (see [`find_k`]).
+///
+/// `on_pass` is called after every pass over the parameters with the pass
+/// number, the best parameters so far and their error, eg to save them.
+///
+/// the parameters of the evaluation are replaced for the whole process while
+/// tuning, and restored afterwards, so nothing else should be evaluating
+/// positions in the meantime.
+pub fn tune(
+    samples: &[Sample],
+    start: EvalParams,
+    k: f64,
+    opts: &TuneOptions,
+    mut on_pass: impl FnMut(usize, &EvalParams, f64),
+) -> EvalParams {
+    let original = params();
+    set_params(start);
+
+    let mut best = flatten(&start);
+    let mut best_error = error(samples, k);
+
+    for pass in 1..=opts.passes {
+        let mut improved = false;
+        for idx in (0..best.len()).filter(|idx| !frozen(*idx)) {
+            for delta in [opts.step, -opts.step] {
+                let mut trial = best.clone();
+                trial[idx] += delta;
+                set_params(unflatten(&trial));
+                let trial_error = error(samples, k);
+                if trial_error < best_error {
+                    best = trial;
+                    best_error = trial_error;
+                    improved = true;
+                    break;
+                }
+            }
+        }
+        on_pass(pass, &unflatten(&best), best_error);
+        if !improved {
+            break;
+        }
+    }
+
+    set_params(original);
+    unflatten(&best)
+}
+
+#[cfg(test)]
+#[path = "tests/mod.rs"]
+mod tests;
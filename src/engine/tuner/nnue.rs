@@ -0,0 +1,438 @@
+//! training the network of [`crate::evaluation::nnue`] on labelled positions.
+//!
+//! the network is trained in floating point, with the same inputs as the
+//! engine ([`features`]), and quantised into a [`Network`] to be saved. the
+//! loss is the squared difference between the result of the game and the
+//! win probability of the evaluation, `sigmoid(eval / EVAL_SCALE)`.
+//!
+//! the gradients of each batch are computed in parallel with rayon, and the
+//! weights are updated with Adam. they are clipped so that the quantised
+//! weights fit their integers.
+use std::fs;
+use std::path::Path;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use chess::Board;
+use rayon::iter::IntoParallelRefIterator;
+use rayon::iter::ParallelIterator;
+use rayon::slice::ParallelSlice;
+
+use crate::evaluation::nnue::HIDDEN;
+use crate::evaluation::nnue::INPUTS;
+use crate::evaluation::nnue::Network;
+use crate::evaluation::nnue::QA;
+use crate::evaluation::nnue::QB;
+use crate::evaluation::nnue::SCALE;
+use crate::evaluation::nnue::features;
+use crate::tuner::data::Sample;
+use crate::util::Rng;
+
+/// the evaluation (in centipawns) at which the side to move is expected to
+/// score ~73%
+pub const EVAL_SCALE: f32 = 400.0;
+
+/// where the feature weights start in [`FloatNetwork::params`]
+const FEATURE_WEIGHTS: usize = 0;
+/// where the feature bias starts
+const FEATURE_BIAS: usize = FEATURE_WEIGHTS + INPUTS * HIDDEN;
+/// where the output weights start
+const OUTPUT_WEIGHTS: usize = FEATURE_BIAS + HIDDEN;
+/// where the output bias is
+pub(crate) const OUTPUT_BIAS: usize = OUTPUT_WEIGHTS + 2 * HIDDEN;
+/// the number of parameters of the network
+pub const PARAMS: usize = OUTPUT_BIAS + 1;
+
+/// the largest feature weight or bias, so that the accumulator of the
+/// quantised network can't overflow with every piece on the board
+const FEATURE_CLIP: f32 = 1.98;
+/// the largest output weight, so that it fits an `i16` once quantised
+const OUTPUT_CLIP: f32 = i16::MAX as f32 / QB as f32 - 1.0;
+/// the largest output bias, so that it fits an `i16` once quantised
+const BIAS_CLIP: f32 = i16::MAX as f32 / (QA * QB) as f32;
+
+/// the first bytes of a checkpoint file
+const CHECKPOINT_MAGIC: &[u8; 8] = b"SANDYCK1";
+
+/// a position ready for training: its features from the side to move's
+/// perspective, then the opponent's, and the score of the side to move
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrainingSample {
+    /// the active features of each perspective, side to move first
+    pub features: [Vec<u16>; 2],
+    /// the result of the game for the side to move
+    pub target: f32,
+}
+
+impl TrainingSample {
+    /// the features of `board`, and `result` (for white) from the side to
+    /// move's point of view
+    pub fn new(board: &Board, result: f64) -> Self {
+        let stm = board.side_to_move();
+        let perspective = |c| features(board, c).map(|f| f as u16).collect();
+        Self {
+            features: [perspective(stm), perspective(!stm)],
+            target: if stm == chess::Color::White {
+                result as f32
+            } else {
+                1.0 - result as f32
+            },
+        }
+    }
+}
+
+impl From<&Sample> for TrainingSample {
+    fn from(sample: &Sample) -> Self {
+        Self::new(&sample.board, sample.result)
+    }
+}
+
+/// the logistic function
+#[inline(always)]
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+/// the network in floating point, with every parameter in a flat list
+#[derive(Debug, Clone, PartialEq)]
+pub struct FloatNetwork {
+    /// the feature weights (feature by feature), the feature bias, the output
+    /// weights (side to move first), and the output bias, like the network
+    /// file
+    pub params: Vec<f32>,
+}
+
+impl FloatNetwork {
+    /// a network with small random weights
+    pub fn random(seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        let mut params = vec![0.0; PARAMS];
+        for w in &mut params[FEATURE_WEIGHTS..FEATURE_BIAS] {
+            *w = rng.symmetric() * 0.1;
+        }
+        let output = 1.0 / (HIDDEN as f32).sqrt();
+        for w in &mut params[OUTPUT_WEIGHTS..OUTPUT_BIAS] {
+            *w = rng.symmetric() * output;
+        }
+        Self { params }
+    }
+
+    /// the hidden layer of one perspective, before the activation
+    fn accumulate(&self, features: &[u16]) -> [f32; HIDDEN] {
+        let mut hidden = [0.0; HIDDEN];
+        hidden.copy_from_slice(&self.params[FEATURE_BIAS..OUTPUT_WEIGHTS]);
+        for f in features {
+            let row = FEATURE_WEIGHTS + *f as usize * HIDDEN;
+            for (h, w) in hidden.iter_mut().zip(&self.params[row..row + HIDDEN]) {
+                *h += w;
+            }
+        }
+        hidden
+    }
+
+    /// the hidden layers of both perspectives, and the output of the network
+    /// (in units of [`SCALE`] centipawns)
+    fn forward(&self, sample: &TrainingSample) -> ([[f32; HIDDEN]; 2], f32) {
+        let hidden = [
+            self.accumulate(&sample.features[0]),
+            self.accumulate(&sample.features[1]),
+        ];
+        let mut out = self.params[OUTPUT_BIAS];
+        for (half, layer) in hidden.iter().enumerate() {
+            let weights = &self.params[OUTPUT_WEIGHTS + half * HIDDEN..][..HIDDEN];
+            out += layer
+                .iter()
+                .zip(weights)
+                .map(|(h, w)| h.clamp(0.0, 1.0) * w)
+                .sum::<f32>();
+        }
+        (hidden, out)
+    }
+
+    /// the evaluation of a sample, in centipawns, for the side to move
+    pub fn evaluate(&self, sample: &TrainingSample) -> f32 {
+        self.forward(sample).1 * SCALE as f32
+    }
+
+    /// the loss of a sample
+    pub fn loss(&self, sample: &TrainingSample) -> f32 {
+        let p = sigmoid(self.evaluate(sample) / EVAL_SCALE);
+        (p - sample.target).powi(2)
+    }
+
+    /// add the gradient of the loss of `sample` to `grad`, and return the
+    /// loss
+    pub fn backward(&self, sample: &TrainingSample, grad: &mut [f32]) -> f32 {
+        let (hidden, out) = self.forward(sample);
+        let p = sigmoid(out * SCALE as f32 / EVAL_SCALE);
+        let error = p - sample.target;
+        let d_out = 2.0 * error * p * (1.0 - p) * SCALE as f32 / EVAL_SCALE;
+
+        grad[OUTPUT_BIAS] += d_out;
+        for (half, layer) in hidden.iter().enumerate() {
+            let weights = OUTPUT_WEIGHTS + half * HIDDEN;
+            let mut d_hidden = [0.0; HIDDEN];
+            for (j, h) in layer.iter().enumerate() {
+                grad[weights + j] += d_out * h.clamp(0.0, 1.0);
+                // the clipped ReLU only lets gradients through in its slope
+                if *h > 0.0 && *h < 1.0 {
+                    d_hidden[j] = d_out * self.params[weights + j];
+                }
+            }
+            for (g, d) in grad[FEATURE_BIAS..OUTPUT_WEIGHTS].iter_mut().zip(&d_hidden) {
+                *g += d;
+            }
+            for f in &sample.features[half] {
+                let row = FEATURE_WEIGHTS + *f as usize * HIDDEN;
+                for (g, d) in grad[row..row + HIDDEN].iter_mut().zip(&d_hidden) {
+                    *g += d;
+                }
+            }
+        }
+
+        error * error
+    }
+
+    /// the mean loss over `samples`, in parallel
+    pub fn mean_loss(&self, samples: &[TrainingSample]) -> f32 {
+        let total: f64 = samples.par_iter().map(|s| self.loss(s) as f64).sum();
+        (total / samples.len().max(1) as f64) as f32
+    }
+
+    /// keep every weight within what its quantised integer can hold
+    fn clip(&mut self) {
+        for w in &mut self.params[FEATURE_WEIGHTS..OUTPUT_WEIGHTS] {
+            *w = w.clamp(-FEATURE_CLIP, FEATURE_CLIP);
+        }
+        for w in &mut self.params[OUTPUT_WEIGHTS..OUTPUT_BIAS] {
+            *w = w.clamp(-OUTPUT_CLIP, OUTPUT_CLIP);
+        }
+        let bias = &mut self.params[OUTPUT_BIAS];
+        *bias = bias.clamp(-BIAS_CLIP, BIAS_CLIP);
+    }
+
+    /// the quantised network, as the engine loads it
+    pub fn quantise(&self) -> Network {
+        let q = |w: f32, scale: i32| (w * scale as f32).round() as i16;
+        let mut net = Network::zeroed();
+        for (row, weights) in net
+            .feature_weights
+            .iter_mut()
+            .zip(self.params[FEATURE_WEIGHTS..FEATURE_BIAS].chunks_exact(HIDDEN))
+        {
+            for (w, f) in row.iter_mut().zip(weights) {
+                *w = q(*f, QA);
+            }
+        }
+        for (w, f) in net
+            .feature_bias
+            .iter_mut()
+            .zip(&self.params[FEATURE_BIAS..OUTPUT_WEIGHTS])
+        {
+            *w = q(*f, QA);
+        }
+        for (w, f) in net
+            .output_weights
+            .iter_mut()
+            .flatten()
+            .zip(&self.params[OUTPUT_WEIGHTS..OUTPUT_BIAS])
+        {
+            *w = q(*f, QB);
+        }
+        net.output_bias = q(self.params[OUTPUT_BIAS], QA * QB);
+        net
+    }
+}
+
+/// the Adam optimiser
+#[derive(Debug, Clone, PartialEq)]
+pub struct Adam {
+    /// the learning rate
+    pub lr: f32,
+    /// the decay of the first moment
+    pub beta1: f32,
+    /// the decay of the second moment
+    pub beta2: f32,
+    /// the first moment of every parameter
+    m: Vec<f32>,
+    /// the second moment of every parameter
+    v: Vec<f32>,
+    /// the number of steps taken
+    t: i32,
+}
+
+impl Adam {
+    /// a fresh optimiser with learning rate `lr`
+    pub fn new(lr: f32) -> Self {
+        Self {
+            lr,
+            beta1: 0.9,
+            beta2: 0.999,
+            m: vec![0.0; PARAMS],
+            v: vec![0.0; PARAMS],
+            t: 0,
+        }
+    }
+
+    /// update the parameters of `net` with the gradient `grad`
+    pub fn step(&mut self, net: &mut FloatNetwork, grad: &[f32]) {
+        self.t += 1;
+        let (b1, b2) = (self.beta1, self.beta2);
+        let lr = self.lr * (1.0 - b2.powi(self.t)).sqrt() / (1.0 - b1.powi(self.t));
+        for (((p, g), m), v) in net
+            .params
+            .iter_mut()
+            .zip(grad)
+            .zip(&mut self.m)
+            .zip(&mut self.v)
+        {
+            // most features are absent from a batch
+            if *g == 0.0 && *m == 0.0 {
+                continue;
+            }
+            *m = b1 * *m + (1.0 - b1) * g;
+            *v = b2 * *v + (1.0 - b2) * g * g;
+            *p -= lr * *m / (v.sqrt() + 1e-8);
+        }
+        net.clip();
+    }
+}
+
+/// the state of a training run, which can be saved and resumed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    /// the number of epochs completed
+    pub epoch: usize,
+    /// the network being trained
+    pub net: FloatNetwork,
+    /// the state of its optimiser
+    pub adam: Adam,
+}
+
+impl Checkpoint {
+    /// start training a random network
+    pub fn new(seed: u64, lr: f32) -> Self {
+        Self {
+            epoch: 0,
+            net: FloatNetwork::random(seed),
+            adam: Adam::new(lr),
+        }
+    }
+
+    /// the checkpoint as bytes: a magic string, then little-endian fields:
+    /// the epoch and step (`u32`s), the learning rate, the parameters, and
+    /// both moments (`f32`s)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = CHECKPOINT_MAGIC.to_vec();
+        bytes.extend((self.epoch as u32).to_le_bytes());
+        bytes.extend((self.adam.t as u32).to_le_bytes());
+        bytes.extend(self.adam.lr.to_le_bytes());
+        for values in [&self.net.params, &self.adam.m, &self.adam.v] {
+            bytes.extend(values.iter().flat_map(|v| v.to_le_bytes()));
+        }
+        bytes
+    }
+
+    /// the inverse of [`Checkpoint::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let Some(rest) = bytes.strip_prefix(CHECKPOINT_MAGIC) else {
+            bail!("not a checkpoint");
+        };
+        if rest.len() != 12 + 3 * 4 * PARAMS {
+            bail!("the checkpoint is for a different network");
+        }
+        let word = |i: usize| <[u8; 4]>::try_from(&rest[4 * i..4 * i + 4]).unwrap();
+        let floats = |n: usize| -> Vec<f32> {
+            rest[12 + 4 * n * PARAMS..12 + 4 * (n + 1) * PARAMS]
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+                .collect()
+        };
+        let mut adam = Adam::new(f32::from_le_bytes(word(2)));
+        adam.t = u32::from_le_bytes(word(1)) as i32;
+        adam.m = floats(1);
+        adam.v = floats(2);
+        Ok(Self {
+            epoch: u32::from_le_bytes(word(0)) as usize,
+            net: FloatNetwork { params: floats(0) },
+            adam,
+        })
+    }
+
+    /// read a checkpoint from a file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).with_context(|| format!("could not read {}", path.display()))?;
+        Self::from_bytes(&bytes).with_context(|| format!("invalid checkpoint {}", path.display()))
+    }
+
+    /// write the checkpoint to a file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, self.to_bytes())
+            .with_context(|| format!("could not write {}", path.display()))
+    }
+
+    /// train for one epoch over `samples`, in batches of `batch_size` in a
+    /// random order, and return the mean training loss
+    pub fn train_epoch(
+        &mut self,
+        samples: &[TrainingSample],
+        batch_size: usize,
+        rng: &mut Rng,
+    ) -> f32 {
+        let mut order: Vec<usize> = (0..samples.len()).collect();
+        rng.shuffle(&mut order);
+
+        let mut total = 0.0;
+        for batch in order.chunks(batch_size.max(1)) {
+            let (mut grad, loss) = batch
+                .par_chunks(batch.len().div_ceil(rayon::current_num_threads()).max(1))
+                .map(|chunk| {
+                    let mut grad = vec![0.0; PARAMS];
+                    let loss: f32 = chunk
+                        .iter()
+                        .map(|i| self.net.backward(&samples[*i], &mut grad))
+                        .sum();
+                    (grad, loss)
+                })
+                .reduce(
+                    || (vec![0.0; PARAMS], 0.0),
+                    |(mut a, la), (b, lb)| {
+                        a.iter_mut().zip(b).for_each(|(a, b)| *a += b);
+                        (a, la + lb)
+                    },
+                );
+            let scale = 1.0 / batch.len() as f32;
+            grad.iter_mut().for_each(|g| *g *= scale);
+            self.adam.step(&mut self.net, &grad);
+            total += loss as f64;
+        }
+        self.epoch += 1;
+        (total / samples.len().max(1) as f64) as f32
+    }
+}
+
+/// split `samples` into a training set and a validation set of about
+/// `fraction` of them, picked at random
+pub fn split(
+    mut samples: Vec<TrainingSample>,
+    fraction: f64,
+    rng: &mut Rng,
+) -> (Vec<TrainingSample>, Vec<TrainingSample>) {
+    rng.shuffle(&mut samples);
+    let validation =
+        ((samples.len() as f64 * fraction.clamp(0.0, 1.0)).round() as usize).min(samples.len());
+    let train = samples.split_off(validation);
+    (train, samples)
+}
+
+/// convert samples in parallel
+pub fn prepare(samples: &[Sample]) -> Vec<TrainingSample> {
+    samples.par_iter().map(TrainingSample::from).collect()
+}
+
+#[cfg(test)]
+#[path = "tests/nnue.rs"]
+mod tests;
@@ -0,0 +1,174 @@
+//! SPSA (simultaneous perturbation stochastic approximation) tuning of the
+//! search constants in [`crate::tunable`].
+//!
+//! every iteration, all the parameters are nudged at once in a random
+//! direction, by `±c_k`. an engine with the parameters moved one way plays
+//! a few games against an engine with them moved the other way, and the
+//! parameters are moved towards the winner, by `a_k * score / c_k`. both
+//! `a_k` and `c_k` shrink as the tuning goes on.
+//!
+//! the schedule is the one used by fishtest: each parameter has a
+//! perturbation `c_end` and a learning rate `r_end` for the *last* iteration,
+//! from which the initial values are derived.
+//!
+//! this module only does the bookkeeping, playing the games is up to the
+//! caller.
+//!
+//! <https://www.chessprogramming.org/SPSA>
+use crate::tunable::Tunable;
+use crate::util::Rng;
+
+/// the decay exponent of `a_k`
+const ALPHA: f64 = 0.602;
+/// the decay exponent of `c_k`
+const GAMMA: f64 = 0.101;
+/// the stability constant `A` as a fraction of the number of iterations
+const STABILITY: f64 = 0.1;
+/// the default learning rate for the last iteration
+pub const DEFAULT_R_END: f64 = 0.002;
+
+/// a parameter being tuned
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpsaParam {
+    /// the name of the UCI option
+    pub name: String,
+    /// the current estimate, which is rounded when used
+    pub value: f64,
+    /// the smallest allowed value
+    pub min: i32,
+    /// the largest allowed value
+    pub max: i32,
+    /// the perturbation in the last iteration
+    pub c_end: f64,
+    /// the learning rate in the last iteration
+    pub r_end: f64,
+}
+
+impl SpsaParam {
+    /// start tuning `tunable` from its default, perturbing it by a twentieth
+    /// of its range by the end
+    pub fn new(tunable: &Tunable) -> Self {
+        Self {
+            name: tunable.name.to_string(),
+            value: tunable.default as f64,
+            min: tunable.min,
+            max: tunable.max,
+            c_end: ((tunable.max - tunable.min) as f64 / 20.0).max(1.0),
+            r_end: DEFAULT_R_END,
+        }
+    }
+
+    /// the value to use, `offset` away from the current estimate
+    fn at(&self, offset: f64) -> i32 {
+        (self.value + offset)
+            .round()
+            .clamp(self.min as f64, self.max as f64) as i32
+    }
+}
+
+/// the engines to play against each other in an iteration
+#[derive(Debug, Clone, PartialEq)]
+pub struct Perturbation {
+    /// the direction each parameter was moved in, ±1
+    pub signs: Vec<f64>,
+    /// the parameters of the first engine, moved by `+c_k * sign`
+    pub plus: Vec<(String, i32)>,
+    /// the parameters of the second engine, moved by `-c_k * sign`
+    pub minus: Vec<(String, i32)>,
+}
+
+/// the state of an SPSA tuning run
+#[derive(Debug, Clone)]
+pub struct Spsa {
+    /// the parameters being tuned
+    pub params: Vec<SpsaParam>,
+    /// how many iterations the run lasts
+    pub iterations: usize,
+    /// how many iterations have been completed
+    pub iteration: usize,
+    /// picks the perturbations
+    rng: Rng,
+}
+
+impl Spsa {
+    /// a new run over `params`, lasting `iterations`. the `seed` picks the
+    /// sequence of perturbations.
+    pub fn new(params: Vec<SpsaParam>, iterations: usize, seed: u64) -> Self {
+        Self {
+            params,
+            iterations: iterations.max(1),
+            iteration: 0,
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// a pseudo-random bit
+    fn coin(&mut self) -> bool {
+        self.rng.next_u64() >> 63 == 1
+    }
+
+    /// the perturbation of `param` in the current iteration
+    pub fn c_k(&self, param: &SpsaParam) -> f64 {
+        let n = self.iterations as f64;
+        let k = self.iteration as f64 + 1.0;
+        param.c_end * (n / k).powf(GAMMA)
+    }
+
+    /// the step size of `param` in the current iteration
+    pub fn a_k(&self, param: &SpsaParam) -> f64 {
+        let n = self.iterations as f64;
+        let k = self.iteration as f64 + 1.0;
+        let a = STABILITY * n;
+        let a_end = param.r_end * param.c_end.powi(2);
+        a_end * ((a + n) / (a + k)).powf(ALPHA)
+    }
+
+    /// pick the engines for the next iteration
+    pub fn perturb(&mut self) -> Perturbation {
+        let signs: Vec<f64> = (0..self.params.len())
+            .map(|_| if self.coin() { 1.0 } else { -1.0 })
+            .collect();
+        let (plus, minus) = self
+            .params
+            .iter()
+            .zip(&signs)
+            .map(|(p, sign)| {
+                let c = self.c_k(p) * sign;
+                ((p.name.clone(), p.at(c)), (p.name.clone(), p.at(-c)))
+            })
+            .unzip();
+        Perturbation { signs, plus, minus }
+    }
+
+    /// move the parameters towards the winner of `perturbation`. `score` is
+    /// the number of wins minus the number of losses of the `plus` engine.
+    pub fn update(&mut self, perturbation: &Perturbation, score: f64) {
+        let steps: Vec<f64> = self
+            .params
+            .iter()
+            .zip(&perturbation.signs)
+            .map(|(p, sign)| self.a_k(p) * score * sign / self.c_k(p))
+            .collect();
+        for (p, step) in self.params.iter_mut().zip(steps) {
+            p.value = (p.value + step).clamp(p.min as f64, p.max as f64);
+        }
+        self.iteration += 1;
+    }
+
+    /// the current (rounded) values of the parameters
+    pub fn values(&self) -> Vec<(String, i32)> {
+        self.params
+            .iter()
+            .map(|p| (p.name.clone(), p.at(0.0)))
+            .collect()
+    }
+
+    /// whether every iteration has been completed
+    pub fn done(&self) -> bool {
+        self.iteration >= self.iterations
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/spsa.rs"]
+mod tests;
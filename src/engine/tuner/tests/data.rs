@@ -0,0 +1,180 @@
+use std::str::FromStr;
+
+use chess::Board;
+use chess::Piece;
+use chess::Square;
+
+use crate::tuner::data::BINARY_MAGIC;
+use crate::tuner::data::DataPoint;
+use crate::tuner::data::SKIP_OPENING_PLIES;
+use crate::tuner::data::parse_binary;
+use crate::tuner::data::parse_labelled;
+use crate::tuner::data::parse_pgn;
+use crate::tuner::data::parse_result;
+use crate::tuner::data::read_samples;
+
+#[test]
+fn results() {
+    for (token, result) in [
+        ("1-0", Some(1.0)),
+        ("\"0-1\";", Some(0.0)),
+        ("1/2-1/2", Some(0.5)),
+        ("[0.5]", Some(0.5)),
+        ("[1.0]", Some(1.0)),
+        ("0", None),
+        ("e4", None),
+    ] {
+        assert_eq!(parse_result(token), result, "{token}");
+    }
+}
+
+#[test]
+fn labelled_positions() {
+    let text = "
+        # a comment
+        rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1 [0.5]
+        4k3/8/8/8/8/8/8/3QK3 w - - c9 \"1-0\";
+        4k3/8/8/8/8/8/q7/4K3 b - - 12 40 | 0-1
+    ";
+    let samples = parse_labelled(text).unwrap();
+    assert_eq!(samples.len(), 3);
+    assert_eq!(samples[0].result, 0.5);
+    assert_eq!(samples[0].board.piece_on(Square::E4), Some(Piece::Pawn));
+    assert_eq!(samples[1].result, 1.0);
+    assert_eq!(samples[2].result, 0.0);
+
+    assert!(parse_labelled("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").is_err());
+    assert!(parse_labelled("not a fen at all 1-0").is_err());
+}
+
+#[test]
+fn pgn_games() {
+    let text = r#"
+[Event "one"]
+[Result "1-0"]
+
+1. e4 {book} e5 2. Nf3 (2. f4 exf4) Nc6 3. Bb5 a6 4. Ba4 $1 Nf6 5. O-O Be7!? 1-0
+
+[Event "two, no result"]
+[Result "*"]
+
+1. d4 d5 2. c4 e6 3. Nc3 Nf6 4. Bg5 Be7 5. e3 O-O *
+
+[Event "three"]
+[Result "0-1"]
+
+1. a4 h5 2. a5 h4 3. a6 h3 4. axb7 hxg2 5. bxa8=Q gxh1=Q 0-1
+"#;
+    let samples = parse_pgn(text).unwrap();
+    let first = 10 - SKIP_OPENING_PLIES + 1;
+    assert_eq!(samples.len(), 2 * first);
+    assert!(samples[..first].iter().all(|s| s.result == 1.0));
+    assert!(samples[first..].iter().all(|s| s.result == 0.0));
+    let last = samples.last().unwrap().board;
+    assert_eq!(last.piece_on(Square::A8), Some(Piece::Queen));
+    assert_eq!(last.piece_on(Square::H1), Some(Piece::Queen));
+    assert_ne!(last, Board::default());
+}
+
+#[test]
+fn pgn_skips_broken_games() {
+    let text = r#"[Event "one, illegal move"]
+[Result "1-0"]
+
+1. e4 e5 2. Ke3 Nc6 3. Nf3 Nf6 4. Bc4 Bc5 5. O-O O-O 1-0
+
+[Event "two"]
+[Result "0-1"]
+
+1. a4 h5 2. a5 h4 3. a6 h3 4. axb7 hxg2 5. bxa8=Q gxh1=Q 0-1
+"#;
+    let samples = parse_pgn(text).unwrap();
+    assert_eq!(samples.len(), 10 - SKIP_OPENING_PLIES + 1);
+    assert!(samples.iter().all(|s| s.result == 0.0));
+}
+
+#[test]
+fn research_games() {
+    let samples = read_samples("research/games/sandy_release_wins_with_no_tt.pgn").unwrap();
+    // the game has 44 plies, and black won
+    assert_eq!(samples.len(), 44 - SKIP_OPENING_PLIES + 1);
+    assert!(samples.iter().all(|s| s.result == 0.0));
+}
+
+/// positions with every kind of state the binary format keeps
+const DATA: &[(&str, i16, f64)] = &[
+    (
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        25,
+        0.5,
+    ),
+    ("r3k2r/8/8/8/8/8/8/R3K2R b Kq - 0 1", -310, 0.0),
+    (
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        90,
+        1.0,
+    ),
+    ("8/8/8/8/8/8/8/K6k w - - 0 1", 0, 0.5),
+];
+
+fn data_points() -> Vec<DataPoint> {
+    DATA.iter()
+        .map(|(fen, score, result)| DataPoint {
+            board: Board::from_str(fen).unwrap(),
+            score: *score,
+            result: *result,
+        })
+        .collect()
+}
+
+#[test]
+fn binary_round_trip() {
+    let points = data_points();
+    let mut bytes = BINARY_MAGIC.to_vec();
+    for point in &points {
+        bytes.extend(point.encode());
+    }
+    assert_eq!(parse_binary(&bytes).unwrap(), points);
+    assert!(
+        points[2]
+            .board
+            .en_passant()
+            .is_some_and(|sq| sq == Square::F5)
+    );
+
+    assert!(parse_binary(&bytes[..bytes.len() - 1]).is_err());
+    assert!(parse_binary(&bytes[8..]).is_err());
+    // a result that doesn't exist
+    let mut bad = bytes.clone();
+    bad[8 + 28] = 3;
+    assert!(parse_binary(&bad).is_err());
+}
+
+#[test]
+fn text_format() {
+    let points = data_points();
+    let text: String = points.iter().map(|p| p.to_text() + "\n").collect();
+    assert!(
+        text.starts_with("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 | 25 | 0.5\n")
+    );
+    let samples = parse_labelled(&text).unwrap();
+    assert_eq!(samples.len(), points.len());
+    for (sample, point) in samples.iter().zip(&points) {
+        assert_eq!(sample.board, point.board);
+        assert_eq!(sample.result, point.result);
+    }
+}
+
+#[test]
+fn binary_files() {
+    let path = std::env::temp_dir().join(format!("sandy-data-{}.bin", std::process::id()));
+    let mut bytes = BINARY_MAGIC.to_vec();
+    for point in data_points() {
+        bytes.extend(point.encode());
+    }
+    std::fs::write(&path, bytes).unwrap();
+    let samples = read_samples(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(samples.len(), DATA.len());
+    assert_eq!(samples[1].result, 0.0);
+}
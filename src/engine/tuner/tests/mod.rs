@@ -0,0 +1,95 @@
+use std::str::FromStr;
+
+use chess::Board;
+use chess::Piece;
+use chess::Square;
+
+use crate::evaluation::evaluate;
+use crate::evaluation::params::EvalParams;
+use crate::evaluation::score::Score;
+use crate::position::Position;
+use crate::setup::values::Value;
+use crate::tuner::PARAM_COUNT;
+use crate::tuner::data::Sample;
+use crate::tuner::flatten;
+use crate::tuner::frozen;
+use crate::tuner::quiesce;
+use crate::tuner::resolve;
+use crate::tuner::sigmoid;
+use crate::tuner::unflatten;
+
+#[test]
+fn flatten_round_trips() {
+    let mut params = EvalParams::DEFAULT;
+    params.tempo = 7;
+    params.eg_psqt[5][7][7] = -99;
+    params.king_safety.max_danger = 123;
+    params.pawns.passed[6] = Score::new(300, -1);
+    params.mobility[3] = Score::new(-2, 9);
+    params.threats.safe_check[0] = Score::new(40, 0);
+    params.endgame.mop_up_kings = 11;
+    let flat = flatten(&params);
+    assert_eq!(flat.len(), PARAM_COUNT);
+    assert_eq!(unflatten(&flat), params);
+}
+
+#[test]
+fn sigmoid_is_a_probability() {
+    assert_eq!(sigmoid(0.0, 1.0), 0.5);
+    assert!(sigmoid(400.0, 1.0) > 0.9);
+    assert!(sigmoid(-400.0, 1.0) < 0.1);
+    assert_eq!(sigmoid(100.0, 1.0) + sigmoid(-100.0, 1.0), 1.0);
+}
+
+#[test]
+fn quiescence_takes_hanging_pieces() {
+    // the queen on d5 is free
+    let pos = Position::from(Board::from_str("4k3/8/8/3q4/4P3/8/8/4K3 w - - 0 1").unwrap());
+    let (value, leaf) = quiesce(&pos, Value::MIN, Value::MAX);
+    assert!(value > Value(100));
+    assert!(evaluate(&pos, false) < Value(-500));
+    assert_eq!(leaf.chessboard.piece_on(Square::D5), Some(Piece::Pawn));
+
+    // nothing to take
+    let pos = Position::default();
+    let (_, leaf) = quiesce(&pos, Value::MIN, Value::MAX);
+    assert_eq!(leaf, pos);
+}
+
+#[test]
+fn resolve_drops_checks() {
+    let sample = |fen| Sample {
+        board: Board::from_str(fen).unwrap(),
+        result: 1.0,
+    };
+    let samples = [
+        sample("4k3/8/8/3q4/4P3/8/8/4K3 w - - 0 1"),
+        // in check
+        sample("4k3/8/8/8/8/8/8/3qK3 w - - 0 1"),
+        // checkmate
+        sample("4k3/8/8/8/8/8/3qq3/4K3 w - - 0 1"),
+    ];
+    let resolved = resolve(&samples);
+    assert_eq!(resolved.len(), 1);
+    assert_eq!(resolved[0].board.piece_on(Square::D5), Some(Piece::Pawn));
+    assert_eq!(resolved[0].result, 1.0);
+}
+
+#[test]
+fn frozen_pawn_bonuses_are_on_the_edge_ranks() {
+    let edge = Score::new(1, 1);
+    let mut params = EvalParams::DEFAULT;
+    for ranks in [&mut params.pawns.connected, &mut params.pawns.passed] {
+        ranks[0] = edge;
+        ranks[7] = edge;
+    }
+    let changed: Vec<usize> = flatten(&params)
+        .iter()
+        .zip(flatten(&EvalParams::DEFAULT))
+        .enumerate()
+        .filter(|(_, (a, b))| **a != *b)
+        .map(|(idx, _)| idx)
+        .collect();
+    assert_eq!(changed.len(), 8);
+    assert!(changed.into_iter().all(frozen));
+}
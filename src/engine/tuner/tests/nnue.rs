@@ -0,0 +1,129 @@
+use std::str::FromStr;
+
+use chess::Board;
+
+use crate::evaluation::nnue::QA;
+use crate::evaluation::nnue::QB;
+use crate::evaluation::nnue::accumulator::NnueAccumulator;
+use crate::tuner::data::parse_labelled;
+use crate::tuner::nnue::Checkpoint;
+use crate::tuner::nnue::FloatNetwork;
+use crate::tuner::nnue::OUTPUT_BIAS;
+use crate::tuner::nnue::PARAMS;
+use crate::tuner::nnue::TrainingSample;
+use crate::tuner::nnue::prepare;
+use crate::tuner::nnue::split;
+use crate::util::Rng;
+
+const POSITIONS: &str = "
+    4k3/8/8/8/8/8/8/3QK3 w - - 0 1 [1.0]
+    3qk3/8/8/8/8/8/8/4K3 w - - 0 1 [0.0]
+    4k3/8/8/8/8/8/8/3QK3 b - - 0 1 [1.0]
+    3qk3/8/8/8/8/8/8/4K3 b - - 0 1 [0.0]
+    4k3/pppp4/8/8/8/8/PPPP4/4K3 w - - 0 1 [0.5]
+    r3k3/8/8/8/8/8/P7/4K3 w - - 0 1 [0.0]
+";
+
+fn samples() -> Vec<TrainingSample> {
+    prepare(&parse_labelled(POSITIONS).unwrap())
+}
+
+#[test]
+fn targets_are_for_the_side_to_move() {
+    let samples = samples();
+    assert_eq!(samples[0].target, 1.0);
+    assert_eq!(samples[2].target, 0.0);
+    // the kings and the queen, from both perspectives
+    assert_eq!(samples[0].features[0].len(), 3);
+    assert_eq!(samples[0].features[1].len(), 3);
+    // the same board from the other side swaps the perspectives
+    assert_eq!(samples[0].features[0], samples[2].features[1]);
+}
+
+#[test]
+fn gradients_match_finite_differences() {
+    let sample = samples().swap_remove(4);
+    let net = FloatNetwork::random(3);
+    let mut grad = vec![0.0; PARAMS];
+    net.backward(&sample, &mut grad);
+
+    // some of the parameters that are used: output weights and bias, and the
+    // feature weights of a piece
+    let row = sample.features[0][0] as usize * crate::evaluation::nnue::HIDDEN;
+    let checked = (OUTPUT_BIAS - 20..=OUTPUT_BIAS).chain(row..row + 20);
+    let mut compared = 0;
+    for i in checked {
+        let h = 1e-3;
+        let (mut plus, mut minus) = (net.clone(), net.clone());
+        plus.params[i] += h;
+        minus.params[i] -= h;
+        let numeric = (plus.loss(&sample) - minus.loss(&sample)) / (2.0 * h);
+        assert!(
+            (numeric - grad[i]).abs() < 1e-3 + 0.05 * grad[i].abs(),
+            "parameter {i}: {numeric} vs {}",
+            grad[i]
+        );
+        compared += (grad[i] != 0.0) as usize;
+    }
+    assert!(compared > 10);
+}
+
+#[test]
+fn quantised_network_agrees() {
+    let mut net = FloatNetwork::random(5);
+    // round the weights the way quantising does, so that only the integer
+    // arithmetic of the engine differs
+    let hidden = crate::evaluation::nnue::HIDDEN;
+    for (i, w) in net.params.iter_mut().enumerate() {
+        let scale = match i {
+            OUTPUT_BIAS => (QA * QB) as f32,
+            _ if i >= OUTPUT_BIAS - 2 * hidden => QB as f32,
+            _ => QA as f32,
+        };
+        *w = (*w * scale).round() / scale;
+    }
+    let quantised = net.quantise();
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1",
+        "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+        "6k1/5ppp/8/8/8/8/5PPP/1Q4K1 w - - 0 1",
+    ] {
+        let board = Board::from_str(fen).unwrap();
+        let float = net.evaluate(&TrainingSample::new(&board, 0.5));
+        let acc = NnueAccumulator::with_network(&quantised, 1, &board);
+        let int = quantised.forward(&acc, board.side_to_move()) as f32;
+        assert!((float - int).abs() <= 1.0, "{fen}: {float} vs {int}");
+    }
+}
+
+#[test]
+fn checkpoint_round_trip() {
+    let mut checkpoint = Checkpoint::new(1, 0.01);
+    checkpoint.train_epoch(&samples(), 4, &mut Rng::new(1));
+    let bytes = checkpoint.to_bytes();
+    assert_eq!(Checkpoint::from_bytes(&bytes).unwrap(), checkpoint);
+    assert!(Checkpoint::from_bytes(&bytes[..bytes.len() - 4]).is_err());
+    assert!(Checkpoint::from_bytes(b"SANDYNN1").is_err());
+}
+
+#[test]
+fn training_lowers_the_loss() {
+    let samples = samples();
+    let mut checkpoint = Checkpoint::new(2, 0.01);
+    let before = checkpoint.net.mean_loss(&samples);
+    let mut rng = Rng::new(2);
+    for _ in 0..50 {
+        checkpoint.train_epoch(&samples, 2, &mut rng);
+    }
+    let after = checkpoint.net.mean_loss(&samples);
+    assert_eq!(checkpoint.epoch, 50);
+    assert!(after < before / 2.0, "{before} -> {after}");
+}
+
+#[test]
+fn validation_split() {
+    let (train, validation) = split(samples(), 0.34, &mut Rng::new(4));
+    assert_eq!((train.len(), validation.len()), (4, 2));
+    let (train, validation) = split(samples(), 0.0, &mut Rng::new(4));
+    assert_eq!((train.len(), validation.len()), (6, 0));
+}
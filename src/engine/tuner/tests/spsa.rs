@@ -0,0 +1,85 @@
+use crate::tunable::MAX_EXTEND;
+use crate::tunable::TUNABLES;
+use crate::tuner::spsa::Spsa;
+use crate::tuner::spsa::SpsaParam;
+
+fn run(iterations: usize) -> Spsa {
+    Spsa::new(
+        TUNABLES.iter().map(|t| SpsaParam::new(t)).collect(),
+        iterations,
+        7,
+    )
+}
+
+#[test]
+fn starts_from_defaults() {
+    let spsa = run(100);
+    let values = spsa.values();
+    assert_eq!(values.len(), TUNABLES.len());
+    for ((name, value), t) in values.iter().zip(TUNABLES) {
+        assert_eq!(name, t.name);
+        assert_eq!(*value, t.default);
+    }
+}
+
+#[test]
+fn schedule_shrinks() {
+    let mut spsa = run(100);
+    let p = spsa.params[0].clone();
+    let (c_0, a_0) = (spsa.c_k(&p), spsa.a_k(&p));
+    spsa.iteration = 99;
+    let (c_n, a_n) = (spsa.c_k(&p), spsa.a_k(&p));
+    assert!(c_0 > c_n && a_0 > a_n);
+    // the schedule ends where it is configured to
+    assert!((c_n - p.c_end).abs() < 1e-9);
+    assert!((a_n - p.r_end * p.c_end.powi(2)).abs() < 1e-9);
+}
+
+#[test]
+fn perturbations_are_symmetric() {
+    let mut spsa = run(100);
+    let mut signs = vec![];
+    for _ in 0..20 {
+        let perturbation = spsa.perturb();
+        for (i, p) in spsa.params.iter().enumerate() {
+            let (plus, minus) = (perturbation.plus[i].1, perturbation.minus[i].1);
+            assert!((p.min..=p.max).contains(&plus));
+            assert!((p.min..=p.max).contains(&minus));
+            assert_eq!(plus > minus, perturbation.signs[i] > 0.0);
+        }
+        signs.extend(perturbation.signs);
+    }
+    // both directions are tried
+    assert!(signs.contains(&1.0) && signs.contains(&-1.0));
+}
+
+#[test]
+fn moves_towards_the_winner() {
+    let mut spsa = run(10);
+    let idx = TUNABLES
+        .iter()
+        .position(|t| t.name == MAX_EXTEND.name)
+        .unwrap();
+
+    let perturbation = spsa.perturb();
+    let before = spsa.params[idx].value;
+    spsa.update(&perturbation, 2.0);
+    let after = spsa.params[idx].value;
+    assert_eq!(after > before, perturbation.signs[idx] > 0.0);
+    assert_eq!(spsa.iteration, 1);
+
+    // draws change nothing
+    let perturbation = spsa.perturb();
+    let params = spsa.params.clone();
+    spsa.update(&perturbation, 0.0);
+    assert_eq!(spsa.params, params);
+
+    while !spsa.done() {
+        let perturbation = spsa.perturb();
+        spsa.update(&perturbation, 1000.0);
+    }
+    assert_eq!(spsa.iteration, 10);
+    for p in &spsa.params {
+        assert!(p.value >= p.min as f64 && p.value <= p.max as f64);
+    }
+}
@@ -8,6 +8,7 @@ use anyhow::Result;
 use lockfree::channel::RecvErr;
 
 use crate::Engine;
+use crate::evaluation::evaluator::Evaluator;
 use crate::optlog;
 use crate::search::Message;
 use crate::search::SearchInfo;
@@ -16,7 +17,7 @@ use crate::search::exit_condition;
 /// How often to check for new uci messages from the search threads, in *ms*
 pub const UCI_LISTENING_FREQUENCY: usize = 10;
 
-impl Engine {
+impl<V: Evaluator> Engine<V> {
     /// Start the engine!!
     pub fn uci_go(&mut self) -> Result<()> {
         let mut listener = self.begin_search()?;
@@ -78,9 +79,19 @@ impl Engine {
                                     .join(" "), // Principal variation
                             );
                         }
+                        Message::TableStats(stats) => {
+                            println!("info string tt {stats}");
+                        }
+                        Message::EvalCacheStats(stats) => {
+                            println!("info string evalcache {stats}");
+                        }
                     },
                     Err(RecvErr::NoMessage) => {
                         miss += 1;
+                        // tells the search to stop once its time is up. it
+                        // still sends the stats after that, so keep reading
+                        // until it's done
+                        exit_condition();
                         thread::sleep(Duration::from_millis(UCI_LISTENING_FREQUENCY as u64));
                     }
                     Err(RecvErr::NoSender) => {
@@ -88,10 +99,6 @@ impl Engine {
                         break;
                     }
                 }
-                if exit_condition() {
-                    optlog!(comm;debug;"exit condition met, exiting listener thread");
-                    break;
-                }
             }
             if let Some(mv) = &best {
                 print!("bestmove {}", mv.0);
@@ -2,10 +2,15 @@
 #![allow(dead_code)]
 
 use std::str::FromStr;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
 use chess::Board;
 use chess::ChessMove;
+use chess::File;
 use chess::Piece;
+use chess::Rank;
+use chess::Square;
 
 use crate::position::Position;
 
@@ -125,37 +130,61 @@ impl Print for Position {
 
 /// Convert a FEN string to a visual representation of the board
 pub fn fen_to_str(fen: String) -> String {
-    let mut r: String = String::new();
-    let mut rows: Vec<&str> = fen.split("/").collect();
-    rows[7] = (rows[7].split(" ")).collect::<Vec<&str>>()[0];
-    for (i, row) in rows.iter().enumerate() {
-        r += &*(8 - i).to_string();
+    let mut squares = [None; 64];
+    let placement = fen.split(" ").next().unwrap_or_default();
+    for (i, row) in placement.split("/").take(8).enumerate() {
+        let mut file = 0;
         for c in row.chars() {
-            if c.is_numeric() {
-                r += &*" . ".repeat(c.to_digit(10).unwrap() as usize).to_string();
+            if let Some(empty) = c.to_digit(10) {
+                file += empty as usize;
             } else {
-                r += &*(" ".to_owned() + &c.to_string() + " ");
+                if let Some(sq) = squares.get_mut((7 - i) * 8 + file) {
+                    *sq = Some(c);
+                }
+                file += 1;
             }
         }
-        r += "\n";
     }
 
-    r = r.replace("p", "♟");
-    r = r.replace("P", "♙");
-    r = r.replace("r", "♜");
-    r = r.replace("R", "♖");
-    r = r.replace("n", "♞");
-    r = r.replace("N", "♘");
-    r = r.replace("b", "♝");
-    r = r.replace("B", "♗");
-    r = r.replace("k", "♚");
-    r = r.replace("K", "♔");
-    r = r.replace("q", "♛");
-    r = r.replace("Q", "♕");
-
-    r += "+ a  b  c  d  e  f  g  h";
+    print_squares(3, |sq| {
+        let piece = match squares[sq.to_index()] {
+            Some('p') => "♟",
+            Some('P') => "♙",
+            Some('r') => "♜",
+            Some('R') => "♖",
+            Some('n') => "♞",
+            Some('N') => "♘",
+            Some('b') => "♝",
+            Some('B') => "♗",
+            Some('k') => "♚",
+            Some('K') => "♔",
+            Some('q') => "♛",
+            Some('Q') => "♕",
+            Some(other) => return other.to_string(),
+            None => ".",
+        };
+        piece.to_string()
+    })
+}
 
-    r.to_string()
+/// Lay out one cell per square, the way [`Print`] draws the board: the ranks
+/// from 8 down to 1, each labelled on the left, with the files labelled
+/// underneath. every cell is centred in `width` characters.
+pub fn print_squares(width: usize, cell: impl Fn(Square) -> String) -> String {
+    let mut r = String::new();
+    for rank in (0..8).rev() {
+        r += &(rank + 1).to_string();
+        for file in 0..8 {
+            let sq = Square::make_square(Rank::from_index(rank), File::from_index(file));
+            r += &format!("{:^width$}", cell(sq));
+        }
+        r += "\n";
+    }
+    r += "+";
+    for file in 'a'..='h' {
+        r += &format!("{file:^width$}");
+    }
+    r.trim_end().to_string()
 }
 
 /// Highlight the last played move in the visual representation of the board
@@ -256,3 +285,50 @@ impl Print for Board {
         Position::from(*self).print_move(mv, capture)
     }
 }
+
+/// a pseudo-random number generator (xorshift64*), for everything that needs
+/// cheap, reproducible randomness: picking weak moves, openings, the
+/// perturbations of SPSA, and initialising and shuffling training data
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    /// a generator for `seed`
+    pub fn new(seed: u64) -> Self {
+        // the state must never be zero
+        Self(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    /// a generator seeded with the time, so that every game is different
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        Self::new(nanos)
+    }
+
+    /// the next 64 random bits
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// a random number below `n`
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n.max(1)
+    }
+
+    /// a uniform float in `[-1, 1)`
+    pub fn symmetric(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+    }
+
+    /// shuffle `items` in place (Fisher-Yates)
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i as u64 + 1) as usize);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! the `calibrate` subcommand: measuring the strength of the skill levels
+//! with self-play, for the ratings behind `UCI_Elo`
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Instant;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use log::info;
+use sandy_engine::search::skill::MAX_SKILL_LEVEL;
+use sandy_engine::search::skill::MIN_ELO;
+
+use crate::spsa::OPENINGS;
+use crate::spsa::TimeControl;
+use crate::spsa::UciEngine;
+use crate::spsa::play_game;
+
+/// where the ratings are logged by default
+pub const DEFAULT_LOG: &str = "calibrate.log";
+
+/// the rating difference that makes the stronger side score `score` (from 0
+/// to 1) over `games` games. a perfect score is counted as half a game short
+/// of one, so that the difference stays finite.
+fn elo_difference(score: f64, games: usize) -> f64 {
+    let margin = 0.5 / games.max(1) as f64;
+    let score = score.clamp(margin, 1.0 - margin);
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+/// the closest ratings to `ratings` that never go down, found by replacing
+/// every run of ratings that are out of order with their average (pool
+/// adjacent violators). neighbouring levels are close enough that their
+/// order is often within the noise of a short match.
+fn monotone(ratings: &[f64]) -> Vec<f64> {
+    // the average of each pool, and how many ratings it holds
+    let mut pools: Vec<(f64, usize)> = vec![];
+    for rating in ratings {
+        let mut pool = (*rating, 1);
+        while let Some(&(average, count)) = pools.last()
+            && average > pool.0
+        {
+            pools.pop();
+            let total = pool.1 + count;
+            pool = (
+                (pool.0 * pool.1 as f64 + average * count as f64) / total as f64,
+                total,
+            );
+        }
+        pools.push(pool);
+    }
+    pools
+        .into_iter()
+        .flat_map(|(average, count)| std::iter::repeat_n(average, count))
+        .collect()
+}
+
+/// play `pairs` pairs of games between `weaker` and `stronger`, and return
+/// the score of `stronger`, from 0 to 1
+fn play_match(
+    weaker: &mut UciEngine,
+    stronger: &mut UciEngine,
+    pairs: usize,
+    tc: TimeControl,
+) -> Result<f64> {
+    let mut score = 0.0;
+    for pair in 0..pairs {
+        let opening = OPENINGS[pair % OPENINGS.len()];
+        score += play_game(stronger, weaker, opening, tc)?;
+        score += 1.0 - play_game(weaker, stronger, opening, tc)?;
+    }
+    Ok(score / (2 * pairs.max(1)) as f64)
+}
+
+/// `calibrate [--pairs <n>] [--step <n>] [--tc <base>+<inc>] [--engine
+/// <path>] [--log <file>]`
+///
+/// plays `pairs` pairs of games (in milliseconds, `tc`) between every
+/// `step`th skill level and the next one, up to full strength, and chains
+/// the rating differences, starting from [`MIN_ELO`] at level 0. the engine
+/// defaults to this executable.
+///
+/// the rating of every level is appended to the log file as it is measured,
+/// and at the end the ratings of all the levels, made to never go down (see
+/// [`monotone`]).
+pub fn calibrate_command(args: impl IntoIterator<Item = String>) -> Result<()> {
+    let mut pairs = OPENINGS.len();
+    let mut step = 2;
+    let mut tc = TimeControl::from_str("10000+100")?;
+    let mut engine = std::env::current_exe()?;
+    let mut log = DEFAULT_LOG.to_string();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| anyhow!("{name} needs a value"));
+        match arg.as_str() {
+            "--pairs" => pairs = value("--pairs")?.parse::<usize>()?.max(1),
+            "--step" => step = value("--step")?.parse::<u8>()?.clamp(1, MAX_SKILL_LEVEL),
+            "--tc" => tc = value("--tc")?.parse()?,
+            "--engine" => engine = PathBuf::from(value("--engine")?),
+            "--log" => log = value("--log")?,
+            other => bail!("unrecognised argument {other:?}"),
+        }
+    }
+
+    let mut levels: Vec<u8> = (0..MAX_SKILL_LEVEL).step_by(step as usize).collect();
+    levels.push(MAX_SKILL_LEVEL);
+    let mut weaker = UciEngine::spawn(&engine)?;
+    let mut stronger = UciEngine::spawn(&engine)?;
+
+    let mut file = File::create(&log).with_context(|| format!("could not create {log}"))?;
+    writeln!(
+        file,
+        "# calibrate: {pairs} pairs per level at {}+{}ms",
+        tc.base.as_millis(),
+        tc.inc.as_millis()
+    )?;
+    writeln!(file, "level {}: {MIN_ELO}", levels[0])?;
+    info!("calibrating {} levels, logging to {log}", levels.len());
+
+    let start = Instant::now();
+    let mut elo = MIN_ELO as f64;
+    let mut ratings = vec![elo];
+    for pair in levels.windows(2) {
+        let (low, high) = (pair[0], pair[1]);
+        weaker.set_options(&[("Skill Level".to_string(), low as i32)])?;
+        stronger.set_options(&[("Skill Level".to_string(), high as i32)])?;
+        let score = play_match(&mut weaker, &mut stronger, pairs, tc)?;
+        let difference = elo_difference(score, 2 * pairs);
+        elo += difference;
+        ratings.push(elo);
+
+        let line = format!(
+            "level {high}: {elo:.0} ({difference:+.0} over level {low}, scoring {:.1}%, {:.0}s)",
+            100.0 * score,
+            start.elapsed().as_secs_f64()
+        );
+        info!("{line}");
+        writeln!(file, "{line}")?;
+        file.flush()?;
+    }
+    let fitted = monotone(&ratings)
+        .iter()
+        .map(|r| format!("{r:.0}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    writeln!(file, "# monotone ratings of levels {levels:?}")?;
+    writeln!(file, "{fitted}")?;
+    info!("ratings written to {log}");
+
+    Ok(())
+}
@@ -0,0 +1,327 @@
+//! the `datagen` subcommand: generating training data with self-play
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Write;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Instant;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use chess::Board;
+use chess::BoardStatus;
+use chess::ChessMove;
+use chess::Color;
+use chess::EMPTY;
+use chess::MoveGen;
+use chess::Piece;
+use log::info;
+use sandy_engine::Engine;
+use sandy_engine::evaluation::endgame::insufficient_material;
+use sandy_engine::setup::depth::Depth;
+use sandy_engine::setup::values::Value;
+use sandy_engine::tuner::data::BINARY_MAGIC;
+use sandy_engine::tuner::data::DataPoint;
+use sandy_engine::util::Rng;
+
+/// where the positions are written by default
+pub const DEFAULT_OUTPUT: &str = "datagen.txt";
+
+/// the depth of the searches, unless a depth or node limit is given
+const DEFAULT_DEPTH: u16 = 6;
+
+/// the depth of the searches when only a node limit is given
+const NODES_DEPTH: u16 = 64;
+
+/// games longer than this (in plies) are adjudicated as draws
+const MAX_PLIES: usize = 400;
+
+/// games are adjudicated as won once the score is at least this big...
+const WIN_SCORE: i16 = 1500;
+/// ...for this many plies in a row, for the same side
+const WIN_PLIES: usize = 6;
+
+/// games are adjudicated as drawn once the score is at most this big...
+const DRAW_SCORE: i16 = 10;
+/// ...for this many plies in a row...
+const DRAW_PLIES: usize = 12;
+/// ...after this many plies
+const DRAW_AFTER: usize = 80;
+
+/// the size of the transposition table of each engine, in megabytes
+const HASH_MB: usize = 16;
+
+/// how the positions are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// one [`DataPoint::to_text`] per line
+    Text,
+    /// [`DataPoint::encode`]d records after [`BINARY_MAGIC`]
+    Binary,
+}
+
+/// the limits of every search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Limits {
+    /// how deep to search
+    depth: Depth,
+    /// how many nodes to search, if limited
+    nodes: Option<u32>,
+}
+
+/// `random_plies` random moves from the starting position, that don't end
+/// the game
+fn random_opening(rng: &mut Rng, random_plies: usize) -> Vec<ChessMove> {
+    'retry: loop {
+        let mut board = Board::default();
+        let mut moves = vec![];
+        for _ in 0..random_plies {
+            let legal: Vec<ChessMove> = MoveGen::new_legal(&board).collect();
+            if legal.is_empty() {
+                continue 'retry;
+            }
+            let mv = legal[rng.below(legal.len() as u64) as usize];
+            board = board.make_move_new(mv);
+            moves.push(mv);
+        }
+        if board.status() == BoardStatus::Ongoing {
+            return moves;
+        }
+    }
+}
+
+/// whether `mv` changes the material on the board
+fn is_tactical(board: &Board, mv: ChessMove) -> bool {
+    let en_passant = board.piece_on(mv.get_source()) == Some(Piece::Pawn)
+        && mv.get_source().get_file() != mv.get_dest().get_file();
+    mv.get_promotion().is_some() || board.piece_on(mv.get_dest()).is_some() || en_passant
+}
+
+/// play a game after `opening`, with `engine` playing both sides, and return
+/// its quiet positions, labelled with the result
+fn play_game(engine: &mut Engine, opening: &[ChessMove], limits: Limits) -> Result<Vec<DataPoint>> {
+    engine.new_game()?;
+    for mv in opening {
+        engine.make_move(*mv);
+    }
+    let mut board = engine.board.chessboard;
+    let mut hashes = vec![board.get_hash()];
+    // plies since the last capture or pawn move
+    let mut quiet_plies = 0;
+    // how many plies in a row the game looked won or drawn
+    let (mut win_streak, mut draw_streak) = (0, 0);
+    let mut last_score: i16 = 0;
+    let mut positions = vec![];
+
+    let result = loop {
+        let stm = board.side_to_move();
+        let loss = if stm == Color::White { 0.0 } else { 1.0 };
+        match board.status() {
+            BoardStatus::Checkmate => break loss,
+            BoardStatus::Stalemate => break 0.5,
+            BoardStatus::Ongoing => {}
+        }
+        let repetitions = hashes.iter().filter(|h| **h == board.get_hash()).count();
+        if insufficient_material(&board)
+            || quiet_plies >= 100
+            || repetitions >= 3
+            || hashes.len() >= MAX_PLIES
+        {
+            break 0.5;
+        }
+
+        let (best, _) = engine
+            .search_fixed(limits.depth, limits.nodes)?
+            .ok_or_else(|| anyhow!("no move found in {board}"))?;
+        let (mv, value) = (best.0, best.1);
+        let score = if stm == Color::White {
+            value.0
+        } else {
+            -value.0
+        };
+
+        let is_mate = value >= Value::MATE_IN_MAX_PLY || value <= Value::MATED_IN_MAX_PLY;
+        if *board.checkers() == EMPTY && !is_tactical(&board, mv) && !is_mate {
+            positions.push(DataPoint {
+                board,
+                score,
+                result: 0.5,
+            });
+        }
+
+        // adjudicate games that are clearly decided
+        if score.abs() >= WIN_SCORE && score.signum() == last_score.signum() {
+            win_streak += 1;
+        } else {
+            win_streak = (score.abs() >= WIN_SCORE) as usize;
+        }
+        if win_streak >= WIN_PLIES {
+            break if score > 0 { 1.0 } else { 0.0 };
+        }
+        if hashes.len() >= DRAW_AFTER && score.abs() <= DRAW_SCORE {
+            draw_streak += 1;
+        } else {
+            draw_streak = 0;
+        }
+        if draw_streak >= DRAW_PLIES {
+            break 0.5;
+        }
+        last_score = score;
+
+        let resets = board.piece_on(mv.get_source()) == Some(Piece::Pawn)
+            || board.piece_on(mv.get_dest()).is_some();
+        quiet_plies = if resets { 0 } else { quiet_plies + 1 };
+        engine.make_move(mv);
+        board = engine.board.chessboard;
+        hashes.push(board.get_hash());
+    };
+
+    for point in &mut positions {
+        point.result = result;
+    }
+    Ok(positions)
+}
+
+/// open `path` to append positions to, writing the binary header to new files
+fn open_output(path: &str, format: Format) -> Result<BufWriter<File>> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .append(true)
+        .create(true)
+        .open(path)
+        .with_context(|| format!("could not open {path}"))?;
+    if format == Format::Binary {
+        if file.metadata()?.len() == 0 {
+            file.write_all(BINARY_MAGIC)?;
+        } else {
+            let mut magic = [0; 8];
+            file.read_exact(&mut magic)?;
+            if &magic != BINARY_MAGIC {
+                bail!("{path} is not a binary data file");
+            }
+        }
+    }
+    Ok(BufWriter::new(file))
+}
+
+/// `datagen [--games <n>] [--depth <n>] [--nodes <n>] [--concurrency <n>]
+/// [--out <file>] [--format text|binary] [--random-plies <n>] [--seed <n>]`
+///
+/// plays `games` games of the engine against itself, each from a different
+/// random opening of `random-plies` moves, searching every move to a fixed
+/// depth or number of nodes (see [`Engine::search_fixed`]). games are
+/// adjudicated once the score stays decisive, or drawn, for a few moves.
+///
+/// the positions that aren't in check, where the best move isn't a capture
+/// or a promotion, and that aren't mates, are appended to the output file
+/// with the score of the search and the result of the game, see
+/// [`DataPoint`]. the format defaults to binary if the file ends with `.bin`.
+pub fn datagen_command(args: impl IntoIterator<Item = String>) -> Result<()> {
+    let mut games = 100;
+    let mut depth = None;
+    let mut nodes = None;
+    let mut concurrency = 1;
+    let mut output = DEFAULT_OUTPUT.to_string();
+    let mut format = None;
+    let mut random_plies = 8;
+    let mut seed = 1;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| anyhow!("{name} needs a value"));
+        match arg.as_str() {
+            "--games" => games = value("--games")?.parse()?,
+            "--depth" => depth = Some(value("--depth")?.parse()?),
+            "--nodes" => nodes = Some(value("--nodes")?.parse()?),
+            "--concurrency" => concurrency = value("--concurrency")?.parse::<usize>()?.max(1),
+            "--out" => output = value("--out")?,
+            "--format" => {
+                format = Some(match value("--format")?.as_str() {
+                    "text" => Format::Text,
+                    "binary" => Format::Binary,
+                    other => bail!("unknown format {other:?}, expected text or binary"),
+                })
+            }
+            "--random-plies" => random_plies = value("--random-plies")?.parse()?,
+            "--seed" => seed = value("--seed")?.parse()?,
+            other => bail!("unrecognised argument {other:?}"),
+        }
+    }
+    let limits = Limits {
+        depth: Depth(depth.unwrap_or(if nodes.is_some() {
+            NODES_DEPTH
+        } else {
+            DEFAULT_DEPTH
+        })),
+        nodes,
+    };
+    let format = format.unwrap_or(if output.ends_with(".bin") {
+        Format::Binary
+    } else {
+        Format::Text
+    });
+    let mut out = open_output(&output, format)?;
+    info!(
+        "playing {games} games to depth {}{} on {concurrency} threads, writing to {output}",
+        limits.depth.0,
+        nodes.map_or(String::new(), |n| format!(" or {n} nodes"))
+    );
+
+    let next_game = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    let start = Instant::now();
+    thread::scope(|scope| -> Result<()> {
+        let handles: Vec<_> = (0..concurrency.min(games.max(1)))
+            .map(|_| {
+                let (sender, next_game) = (sender.clone(), &next_game);
+                scope.spawn(move || -> Result<()> {
+                    let mut engine = Engine::new()?;
+                    engine.resize_table(HASH_MB << 20)?;
+                    loop {
+                        let game = next_game.fetch_add(1, Ordering::Relaxed);
+                        if game >= games {
+                            return Ok(());
+                        }
+                        let mut rng = Rng::new(seed ^ (game as u64).wrapping_mul(0x1f3d_5b79));
+                        let opening = random_opening(&mut rng, random_plies);
+                        let positions = play_game(&mut engine, &opening, limits)?;
+                        // the receiver only hangs up on errors
+                        let _ = sender.send(positions);
+                    }
+                })
+            })
+            .collect();
+        drop(sender);
+
+        let (mut played, mut written) = (0, 0);
+        let every = (games / 20).max(1);
+        for positions in receiver {
+            for point in &positions {
+                match format {
+                    Format::Text => writeln!(out, "{}", point.to_text())?,
+                    Format::Binary => out.write_all(&point.encode())?,
+                }
+            }
+            played += 1;
+            written += positions.len();
+            if played % every == 0 || played == games {
+                out.flush()?;
+                info!(
+                    "{played}/{games} games, {written} positions, {:.1} games/s",
+                    played as f64 / start.elapsed().as_secs_f64()
+                );
+            }
+        }
+        out.flush()?;
+
+        handles
+            .into_iter()
+            .try_for_each(|h| h.join().map_err(|_| anyhow!("a game thread panicked"))?)
+    })
+}
@@ -4,6 +4,20 @@
 //! It can be used in two modes:
 //! * play in terminal mode
 //! * UCI mode
+//!
+//! Arguments:
+//! * `--params <file>` evaluate with the parameters in `file` (see
+//!   [`sandy_engine::evaluation::params`])
+//! * `tune <positions>...` tune the evaluation parameters and exit, see
+//!   [`tune::tune_command`]
+//! * `spsa` tune the search constants with self-play and exit, see
+//!   [`spsa::spsa_command`]
+//! * `datagen` generate training data with self-play and exit, see
+//!   [`datagen::datagen_command`]
+//! * `calibrate` measure the ratings of the skill levels with self-play and
+//!   exit, see [`calibrate::calibrate_command`]
+//! * `train <positions>...` train the network evaluation and exit, see
+//!   [`train::train_command`]
 
 #![deny(rustdoc::broken_intra_doc_links)]
 
@@ -18,14 +32,31 @@ use log::error;
 use log::info;
 use log::warn;
 use sandy_engine::Engine;
+use sandy_engine::evaluation::params::EvalParams;
+use sandy_engine::evaluation::trace::trace_board;
 use sandy_engine::util::Print;
 use sandy_engine::util::fen_to_str;
 
+use crate::calibrate::calibrate_command;
+use crate::datagen::datagen_command;
 use crate::player::terminal_loop;
+use crate::spsa::spsa_command;
+use crate::train::train_command;
+use crate::tune::tune_command;
 use crate::uci::uci_loop;
 
+/// measuring the skill levels
+mod calibrate;
+/// generating training data
+mod datagen;
 /// Interacting with human players
 pub mod player;
+/// SPSA tuning
+mod spsa;
+/// training the network evaluation
+mod train;
+/// Texel tuning
+mod tune;
 /// UCI protocol handling
 mod uci;
 
@@ -64,6 +95,25 @@ fn main() -> Result<()> {
 
     let mut engine: Engine = Engine::new()?;
 
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--params" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| anyhow!("--params needs a file"))?;
+                engine.set_params(EvalParams::load(&path)?)?;
+                info!("loaded evaluation parameters from {path}");
+            }
+            "tune" => return tune_command(args),
+            "spsa" => return spsa_command(args),
+            "train" => return train_command(args),
+            "datagen" => return datagen_command(args),
+            "calibrate" => return calibrate_command(args),
+            other => warn!("unrecognised argument {other:?}"),
+        }
+    }
+
     if cfg!(feature = "perf") {
         let x = engine.best_move(
             sandy_engine::setup::depth::Depth(9),
@@ -104,11 +154,41 @@ fn main() -> Result<()> {
                 let b = Board::from_str(cmd_body).map_err(|e| anyhow!("board error: {e}"))?;
                 info!("{}", b.print());
             }
+            ("eval", _) => {
+                info!("Evaluation breakdown");
+                let b = if cmd_body.is_empty() {
+                    Board::default()
+                } else {
+                    Board::from_str(cmd_body).map_err(|e| anyhow!("board error: {e}"))?
+                };
+                info!("{}\n{}", b.print(), trace_board(&b));
+            }
             ("display" | "fen", _) => {
                 info!("(unchecked) fen display");
                 let b = fen_to_str(cmd_body.to_string());
                 info!("{}", b);
             }
+            ("tune", _) => {
+                info!("Tuning evaluation parameters");
+                // paths are case sensitive, so use the original line
+                tune_command(cmd_body.split_whitespace().map(str::to_string))?;
+            }
+            ("spsa", _) => {
+                info!("Tuning search constants");
+                spsa_command(cmd_body.split_whitespace().map(str::to_string))?;
+            }
+            ("train", _) => {
+                info!("Training the network evaluation");
+                train_command(cmd_body.split_whitespace().map(str::to_string))?;
+            }
+            ("datagen", _) => {
+                info!("Generating training data");
+                datagen_command(cmd_body.split_whitespace().map(str::to_string))?;
+            }
+            ("calibrate", _) => {
+                info!("Calibrating the skill levels");
+                calibrate_command(cmd_body.split_whitespace().map(str::to_string))?;
+            }
             ("other", _) => {
                 // used for testing/prototyping snippets
             }
@@ -11,6 +11,12 @@ use chess::Color;
 use log::error;
 use log::info;
 use sandy_engine::Engine;
+use sandy_engine::opts::opts;
+use sandy_engine::opts::setopts;
+use sandy_engine::search::skill::DEFAULT_ELO;
+use sandy_engine::search::skill::MAX_ELO;
+use sandy_engine::search::skill::MAX_SKILL_LEVEL;
+use sandy_engine::search::skill::MIN_ELO;
 use sandy_engine::setup::depth::Depth;
 use sandy_engine::util::Print;
 
@@ -49,7 +55,7 @@ pub fn terminal_loop(mut engine: Engine) -> Result<()> {
 
     let mut search_depth = Depth::MAX;
     let mut search_time = Duration::from_secs(5);
-    if inquire::Confirm::new("Edit engine settings? (default: movetime 5 seconds)")
+    if inquire::Confirm::new("Edit engine settings? (default: movetime 5 seconds, full strength)")
         .with_default(false)
         .prompt()?
     {
@@ -63,6 +69,27 @@ pub fn terminal_loop(mut engine: Engine) -> Result<()> {
                 .with_default(search_time.as_millis() as u64)
                 .prompt()?,
         );
+
+        // how weak the engine plays, see `sandy_engine::search::skill`
+        let skill_prompt =
+            format!("Skill level (0-{MAX_SKILL_LEVEL}, {MAX_SKILL_LEVEL} is full strength)");
+        let skill_level = inquire::CustomType::<u8>::new(&skill_prompt)
+            .with_default(MAX_SKILL_LEVEL)
+            .prompt()?
+            .min(MAX_SKILL_LEVEL);
+        let elo = if inquire::Confirm::new("Limit the strength to an Elo rating instead?")
+            .with_default(false)
+            .prompt()?
+        {
+            let elo_prompt = format!("Elo rating ({MIN_ELO}-{MAX_ELO})");
+            let elo = inquire::CustomType::<u16>::new(&elo_prompt)
+                .with_default(DEFAULT_ELO)
+                .prompt()?;
+            Some(elo.clamp(MIN_ELO, MAX_ELO))
+        } else {
+            None
+        };
+        setopts(opts()?.skill_level(skill_level).limit_elo(elo))?;
     }
 
     info!("{}", engine.board.print());
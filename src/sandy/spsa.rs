@@ -0,0 +1,442 @@
+//! the `spsa` subcommand: tuning the search constants with self-play
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Child;
+use std::process::ChildStdin;
+use std::process::ChildStdout;
+use std::process::Command;
+use std::process::Stdio;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use chess::Board;
+use chess::BoardStatus;
+use chess::ChessMove;
+use chess::Color;
+use chess::Piece;
+use log::info;
+use log::warn;
+use sandy_engine::evaluation::endgame::insufficient_material;
+use sandy_engine::tunable::TUNABLES;
+use sandy_engine::tuner::spsa::Perturbation;
+use sandy_engine::tuner::spsa::Spsa;
+use sandy_engine::tuner::spsa::SpsaParam;
+
+/// where the progress is logged by default
+pub const DEFAULT_LOG: &str = "spsa.log";
+
+/// games longer than this (in plies) are adjudicated as draws
+pub(crate) const MAX_PLIES: usize = 400;
+
+/// how much an engine may overstep its clock before it loses on time, to
+/// allow for the communication overhead
+const TIME_MARGIN: Duration = Duration::from_millis(100);
+
+/// the size of the transposition table of each engine, in megabytes
+const HASH_MB: usize = 16;
+
+/// the openings the games start from, in turn. each one is played twice, with
+/// the engines swapping colours.
+pub(crate) const OPENINGS: &[&str] = &[
+    "e2e4 e7e5 g1f3 b8c6",
+    "e2e4 c7c5 g1f3 d7d6",
+    "e2e4 e7e6 d2d4 d7d5",
+    "e2e4 c7c6 d2d4 d7d5",
+    "d2d4 d7d5 c2c4 e7e6",
+    "d2d4 d7d5 c2c4 c7c6",
+    "d2d4 g8f6 c2c4 g7g6",
+    "d2d4 g8f6 c2c4 e7e6",
+    "c2c4 e7e5 b1c3 g8f6",
+    "g1f3 d7d5 g2g3 g8f6",
+    "e2e4 d7d5 e4d5 d8d5",
+    "e2e4 g7g6 d2d4 f8g7",
+];
+
+/// the clock of each engine: a base time and an increment per move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TimeControl {
+    /// the time on the clock at the start of the game
+    pub(crate) base: Duration,
+    /// the time added after every move
+    pub(crate) inc: Duration,
+}
+
+impl FromStr for TimeControl {
+    type Err = anyhow::Error;
+
+    /// `<base>+<increment>`, both in milliseconds
+    fn from_str(s: &str) -> Result<Self> {
+        let (base, inc) = s.split_once('+').unwrap_or((s, "0"));
+        let ms = |x: &str| -> Result<Duration> {
+            Ok(Duration::from_millis(x.trim().parse().with_context(
+                || format!("invalid time control {s:?}, expected <base>+<increment> in ms"),
+            )?))
+        };
+        Ok(Self {
+            base: ms(base)?,
+            inc: ms(inc)?,
+        })
+    }
+}
+
+/// an engine running in a subprocess, talking UCI
+pub(crate) struct UciEngine {
+    /// the process
+    child: Child,
+    /// where commands are written
+    stdin: ChildStdin,
+    /// where responses are read from
+    stdout: BufReader<ChildStdout>,
+    /// the names of the options the engine has
+    options: Vec<String>,
+}
+
+impl UciEngine {
+    /// start the engine at `exe` in UCI mode
+    pub(crate) fn spawn(exe: &Path) -> Result<Self> {
+        let mut child = Command::new(exe)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("could not start {}", exe.display()))?;
+        let stdin = child.stdin.take().ok_or_else(|| anyhow!("no stdin"))?;
+        let stdout = BufReader::new(child.stdout.take().ok_or_else(|| anyhow!("no stdout"))?);
+        let mut engine = Self {
+            child,
+            stdin,
+            stdout,
+            options: vec![],
+        };
+
+        engine.send("uci")?;
+        loop {
+            let line = engine.read_line()?;
+            if line == "uciok" {
+                break;
+            }
+            if let Some(option) = line.strip_prefix("option name ") {
+                engine.options.push(
+                    option
+                        .split(" type ")
+                        .next()
+                        .unwrap_or_default()
+                        .to_string(),
+                );
+            }
+        }
+
+        // one thread each, so that the engines don't compete for cores
+        engine.send("setoption name threads value 1")?;
+        engine.send(&format!("setoption name hash value {HASH_MB}"))?;
+        Ok(engine)
+    }
+
+    /// start the engine at `exe` in UCI mode, and check that it can be tuned
+    fn spawn_tunable(exe: &Path) -> Result<Self> {
+        let engine = Self::spawn(exe)?;
+        for t in TUNABLES {
+            if !engine.options.iter().any(|o| o == t.name) {
+                bail!(
+                    "{} has no option {:?}, it needs to be built with `--features tune`",
+                    exe.display(),
+                    t.name
+                );
+            }
+        }
+        Ok(engine)
+    }
+
+    /// send a command
+    fn send(&mut self, command: &str) -> Result<()> {
+        writeln!(self.stdin, "{command}")?;
+        Ok(self.stdin.flush()?)
+    }
+
+    /// the next line the engine prints
+    fn read_line(&mut self) -> Result<String> {
+        let mut line = String::new();
+        if self.stdout.read_line(&mut line)? == 0 {
+            bail!("the engine exited unexpectedly");
+        }
+        Ok(line.trim().to_string())
+    }
+
+    /// read until a line that starts with `prefix`, and return it
+    fn wait_for(&mut self, prefix: &str) -> Result<String> {
+        loop {
+            let line = self.read_line()?;
+            if line.starts_with(prefix) {
+                return Ok(line);
+            }
+        }
+    }
+
+    /// set every option in `values`
+    pub(crate) fn set_options(&mut self, values: &[(String, i32)]) -> Result<()> {
+        for (name, value) in values {
+            self.send(&format!("setoption name {name} value {value}"))?;
+        }
+        Ok(())
+    }
+
+    /// get ready for a new game
+    fn new_game(&mut self) -> Result<()> {
+        self.send("ucinewgame")?;
+        self.send("isready")?;
+        self.wait_for("readyok").map(|_| ())
+    }
+
+    /// the move the engine plays after `moves` from the starting position,
+    /// with `clocks` left for white and black, and how long it took
+    fn go(
+        &mut self,
+        moves: &[String],
+        clocks: [Duration; 2],
+        inc: Duration,
+    ) -> Result<(String, Duration)> {
+        self.send(&format!("position startpos moves {}", moves.join(" ")))?;
+        let start = Instant::now();
+        self.send(&format!(
+            "go wtime {} btime {} winc {} binc {}",
+            clocks[0].as_millis(),
+            clocks[1].as_millis(),
+            inc.as_millis(),
+            inc.as_millis()
+        ))?;
+        let line = self.wait_for("bestmove")?;
+        let elapsed = start.elapsed();
+        let mv = line
+            .split_whitespace()
+            .nth(1)
+            .ok_or_else(|| anyhow!("no move in {line:?}"))?;
+        Ok((mv.to_string(), elapsed))
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// play a game from `opening`, and return the score of white (1, 0.5 or 0)
+pub(crate) fn play_game(
+    white: &mut UciEngine,
+    black: &mut UciEngine,
+    opening: &str,
+    tc: TimeControl,
+) -> Result<f64> {
+    let mut board = Board::default();
+    let mut moves = vec![];
+    let mut hashes = vec![board.get_hash()];
+    // plies since the last capture or pawn move
+    let mut quiet_plies = 0;
+
+    let mut play = |board: &mut Board, mv: ChessMove, moves: &mut Vec<String>| {
+        let resets = board.piece_on(mv.get_source()) == Some(Piece::Pawn)
+            || board.piece_on(mv.get_dest()).is_some();
+        quiet_plies = if resets { 0 } else { quiet_plies + 1 };
+        *board = board.make_move_new(mv);
+        moves.push(mv.to_string());
+        hashes.push(board.get_hash());
+        let repetitions = hashes.iter().filter(|h| **h == board.get_hash()).count();
+        quiet_plies >= 100 || repetitions >= 3
+    };
+
+    for mv in opening.split_whitespace() {
+        let mv = ChessMove::from_str(mv).map_err(|e| anyhow!("bad opening {opening:?}: {e}"))?;
+        play(&mut board, mv, &mut moves);
+    }
+    white.new_game()?;
+    black.new_game()?;
+
+    let mut clocks = [tc.base; 2];
+    let loss = |side: Color| if side == Color::White { 0.0 } else { 1.0 };
+    loop {
+        let stm = board.side_to_move();
+        match board.status() {
+            BoardStatus::Checkmate => return Ok(loss(stm)),
+            BoardStatus::Stalemate => return Ok(0.5),
+            BoardStatus::Ongoing => {}
+        }
+        if insufficient_material(&board) || moves.len() >= MAX_PLIES {
+            return Ok(0.5);
+        }
+
+        let engine = if stm == Color::White {
+            &mut *white
+        } else {
+            &mut *black
+        };
+        let (best, elapsed) = engine.go(&moves, clocks, tc.inc)?;
+        let clock = &mut clocks[stm.to_index()];
+        if elapsed > *clock + TIME_MARGIN {
+            warn!("{stm:?} lost on time after {} plies", moves.len());
+            return Ok(loss(stm));
+        }
+        *clock = clock.saturating_sub(elapsed) + tc.inc;
+
+        match ChessMove::from_str(&best) {
+            Ok(mv) if board.legal(mv) => {
+                if play(&mut board, mv, &mut moves) {
+                    return Ok(0.5);
+                }
+            }
+            _ => {
+                warn!(
+                    "{stm:?} played the illegal move {best:?} after {}",
+                    moves.join(" ")
+                );
+                return Ok(loss(stm));
+            }
+        }
+    }
+}
+
+/// play `pairs` pairs of games between the two engines of `perturbation`,
+/// spread over `workers`, and return the number of wins minus the number of
+/// losses of the `plus` engine
+fn play_iteration(
+    workers: &mut [[UciEngine; 2]],
+    perturbation: &Perturbation,
+    pairs: usize,
+    first_opening: usize,
+    tc: TimeControl,
+) -> Result<f64> {
+    let count = workers.len();
+    thread::scope(|scope| {
+        let handles: Vec<_> = workers
+            .iter_mut()
+            .enumerate()
+            .map(|(w, [plus, minus])| {
+                scope.spawn(move || -> Result<f64> {
+                    plus.set_options(&perturbation.plus)?;
+                    minus.set_options(&perturbation.minus)?;
+                    let mut score = 0.0;
+                    for pair in (w..pairs).step_by(count) {
+                        let opening = OPENINGS[(first_opening + pair) % OPENINGS.len()];
+                        score += 2.0 * play_game(plus, minus, opening, tc)? - 1.0;
+                        score += 1.0 - 2.0 * play_game(minus, plus, opening, tc)?;
+                    }
+                    Ok(score)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().map_err(|_| anyhow!("a game thread panicked"))?)
+            .sum()
+    })
+}
+
+/// `name=value` for every parameter
+fn describe(values: &[(String, i32)]) -> String {
+    values
+        .iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `spsa [--iterations <n>] [--pairs <n>] [--tc <base>+<inc>] [--concurrency
+/// <n>] [--engine <path>] [--log <file>] [--seed <n>]`
+///
+/// tunes the search constants in [`sandy_engine::tunable`] by playing
+/// `pairs` pairs of games (in milliseconds, `tc`) per iteration between two
+/// copies of the engine, see [`sandy_engine::tuner::spsa`]. the engine
+/// defaults to this executable, and must be built with the `tune` feature.
+///
+/// the parameters after every iteration are appended to the log file, and
+/// the final ones are written as UCI commands at the end.
+pub fn spsa_command(args: impl IntoIterator<Item = String>) -> Result<()> {
+    let mut iterations = 1000;
+    let mut pairs = 2;
+    let mut tc = TimeControl::from_str("2000+20")?;
+    let mut concurrency = 1;
+    let mut engine = std::env::current_exe()?;
+    let mut log = DEFAULT_LOG.to_string();
+    let mut seed = 1;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| anyhow!("{name} needs a value"));
+        match arg.as_str() {
+            "--iterations" => iterations = value("--iterations")?.parse()?,
+            "--pairs" => pairs = value("--pairs")?.parse()?,
+            "--tc" => tc = value("--tc")?.parse()?,
+            "--concurrency" => concurrency = value("--concurrency")?.parse::<usize>()?.max(1),
+            "--engine" => engine = PathBuf::from(value("--engine")?),
+            "--log" => log = value("--log")?,
+            "--seed" => seed = value("--seed")?.parse()?,
+            other => bail!("unrecognised argument {other:?}"),
+        }
+    }
+
+    let mut workers = (0..concurrency.min(pairs.max(1)))
+        .map(|_| {
+            Ok([
+                UciEngine::spawn_tunable(&engine)?,
+                UciEngine::spawn_tunable(&engine)?,
+            ])
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut spsa = Spsa::new(
+        TUNABLES.iter().map(|t| SpsaParam::new(t)).collect(),
+        iterations,
+        seed,
+    );
+
+    let mut file = File::create(&log).with_context(|| format!("could not create {log}"))?;
+    writeln!(
+        file,
+        "# spsa: {iterations} iterations of {pairs} pairs at {}+{}ms, seed {seed}",
+        tc.base.as_millis(),
+        tc.inc.as_millis()
+    )?;
+    writeln!(file, "start: {}", describe(&spsa.values()))?;
+    info!("tuning {} constants, logging to {log}", spsa.params.len());
+
+    let start = Instant::now();
+    while !spsa.done() {
+        let perturbation = spsa.perturb();
+        let score = play_iteration(
+            &mut workers,
+            &perturbation,
+            pairs,
+            spsa.iteration * pairs,
+            tc,
+        )?;
+        spsa.update(&perturbation, score);
+
+        let line = format!(
+            "iteration {}/{iterations} ({:.0}s): score {score:+}, {}",
+            spsa.iteration,
+            start.elapsed().as_secs_f64(),
+            describe(&spsa.values())
+        );
+        info!("{line}");
+        writeln!(file, "{line}")?;
+        file.flush()?;
+    }
+
+    writeln!(file, "# final values")?;
+    for (name, value) in spsa.values() {
+        writeln!(file, "setoption name {name} value {value}")?;
+    }
+    info!("tuned constants written to {log}");
+
+    Ok(())
+}
@@ -0,0 +1,149 @@
+//! the `train` subcommand: training the network evaluation
+#[cfg(feature = "nnue")]
+pub use with_nnue::train_command;
+
+/// `train <positions>... [--out <dir>] [--epochs <n>] [--batch <n>] [--lr <f>]
+/// [--validation <f>] [--seed <n>] [--threads <n>] [--resume <checkpoint>]`
+///
+/// needs the network evaluation, which isn't built in.
+#[cfg(not(feature = "nnue"))]
+pub fn train_command(_args: impl IntoIterator<Item = String>) -> anyhow::Result<()> {
+    anyhow::bail!("training the network needs the engine to be built with `--features nnue`")
+}
+
+/// the trainer, when the network evaluation is built in
+#[cfg(feature = "nnue")]
+mod with_nnue {
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::Instant;
+
+    use anyhow::Context;
+    use anyhow::Result;
+    use anyhow::anyhow;
+    use anyhow::bail;
+    use log::info;
+    use sandy_engine::tuner::data::read_samples;
+    use sandy_engine::tuner::nnue::Checkpoint;
+    use sandy_engine::tuner::nnue::prepare;
+    use sandy_engine::tuner::nnue::split;
+    use sandy_engine::util::Rng;
+
+    /// where the checkpoints and networks are written by default
+    pub const DEFAULT_OUTPUT: &str = "training";
+    /// the learning rate of a new training run
+    const DEFAULT_LR: f32 = 0.001;
+
+    /// `train <positions>... [--out <dir>] [--epochs <n>] [--batch <n>]
+    /// [--lr <f>] [--validation <f>] [--seed <n>] [--threads <n>]
+    /// [--resume <checkpoint>]`
+    ///
+    /// trains a network on the positions in the given files (see
+    /// [`read_samples`]), holding out a fraction of them to validate it. after
+    /// every epoch, the output directory gets:
+    /// * `checkpoint.bin`, to continue training from with `--resume`
+    /// * `net.nnue`, the latest network, in the format `EvalFile` loads
+    /// * `best.nnue`, the network with the lowest validation loss so far
+    pub fn train_command(args: impl IntoIterator<Item = String>) -> Result<()> {
+        let mut inputs = vec![];
+        let mut output = PathBuf::from(DEFAULT_OUTPUT);
+        let mut epochs = 10;
+        let mut batch = 16384;
+        let mut lr = None;
+        let mut validation = 0.05;
+        let mut seed = 1;
+        let mut threads = 0;
+        let mut resume = None;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().ok_or_else(|| anyhow!("{name} needs a value"));
+            match arg.as_str() {
+                "--out" => output = value("--out")?.into(),
+                "--epochs" => epochs = value("--epochs")?.parse()?,
+                "--batch" => batch = value("--batch")?.parse()?,
+                "--lr" => lr = Some(value("--lr")?.parse()?),
+                "--validation" => validation = value("--validation")?.parse()?,
+                "--seed" => seed = value("--seed")?.parse()?,
+                "--threads" => threads = value("--threads")?.parse()?,
+                "--resume" => resume = Some(value("--resume")?),
+                _ => inputs.push(arg),
+            }
+        }
+        if inputs.is_empty() {
+            bail!(
+                "usage: train <positions>... [--out <dir>] [--epochs <n>] [--batch <n>] \
+                 [--lr <f>] [--validation <f>] [--seed <n>] [--threads <n>] \
+                 [--resume <checkpoint>]"
+            );
+        }
+        if threads > 0 {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build_global()?;
+        }
+
+        let mut samples = vec![];
+        for input in &inputs {
+            let read = read_samples(input)?;
+            info!("read {} positions from {input}", read.len());
+            samples.extend(read);
+        }
+        let mut rng = Rng::new(seed);
+        let (train, validation) = split(prepare(&samples), validation, &mut rng);
+        if train.is_empty() {
+            bail!("no positions left to train on");
+        }
+        info!(
+            "training on {} positions, validating on {}",
+            train.len(),
+            validation.len()
+        );
+
+        let mut checkpoint = match resume {
+            Some(path) => {
+                let mut checkpoint = Checkpoint::load(&path)?;
+                if let Some(lr) = lr {
+                    checkpoint.adam.lr = lr;
+                }
+                info!("resuming from {path} after epoch {}", checkpoint.epoch);
+                checkpoint
+            }
+            None => Checkpoint::new(seed, lr.unwrap_or(DEFAULT_LR)),
+        };
+        fs::create_dir_all(&output)
+            .with_context(|| format!("could not create {}", output.display()))?;
+
+        // without a validation set, the best network is the one that fits the
+        // training set best
+        let judged = if validation.is_empty() {
+            &train
+        } else {
+            &validation
+        };
+        let mut best = f32::INFINITY;
+        let start = Instant::now();
+        for _ in 0..epochs {
+            let loss = checkpoint.train_epoch(&train, batch, &mut rng);
+            let judged_loss = checkpoint.net.mean_loss(judged);
+            info!(
+                "epoch {}: training loss {loss:.6}, validation loss {judged_loss:.6} after {:.1}s",
+                checkpoint.epoch,
+                start.elapsed().as_secs_f64()
+            );
+
+            checkpoint.save(output.join("checkpoint.bin"))?;
+            let net = checkpoint.net.quantise();
+            net.save(output.join("net.nnue"))?;
+            if judged_loss < best {
+                best = judged_loss;
+                net.save(output.join("best.nnue"))?;
+            }
+        }
+        info!(
+            "done, the best network is in {}",
+            output.join("best.nnue").display()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+//! the `tune` subcommand: Texel tuning of the evaluation parameters
+use std::fs;
+use std::time::Instant;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use log::info;
+use sandy_engine::evaluation::params::params;
+use sandy_engine::tuner::TuneOptions;
+use sandy_engine::tuner::data::read_samples;
+use sandy_engine::tuner::find_k;
+use sandy_engine::tuner::resolve;
+use sandy_engine::tuner::tune;
+
+/// where the tuned parameters are written by default
+pub const DEFAULT_OUTPUT: &str = "tuned.toml";
+
+/// `tune <positions>... [--out <file>] [--passes <n>] [--step <n>]`
+///
+/// tunes the parameters currently in use (the built-in ones, or those given
+/// with `--params`) against the positions in the given files (see
+/// [`read_samples`]), and writes the result to the output file after every
+/// pass.
+pub fn tune_command(args: impl IntoIterator<Item = String>) -> Result<()> {
+    let mut inputs = vec![];
+    let mut output = DEFAULT_OUTPUT.to_string();
+    let mut opts = TuneOptions::default();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| anyhow!("{name} needs a value"));
+        match arg.as_str() {
+            "--out" => output = value("--out")?,
+            "--passes" => opts.passes = value("--passes")?.parse()?,
+            "--step" => opts.step = value("--step")?.parse()?,
+            _ => inputs.push(arg),
+        }
+    }
+    if inputs.is_empty() {
+        bail!("usage: tune <positions>... [--out <file>] [--passes <n>] [--step <n>]");
+    }
+
+    let mut samples = vec![];
+    for input in &inputs {
+        let read = read_samples(input)?;
+        info!("read {} positions from {input}", read.len());
+        samples.extend(read);
+    }
+    let samples = resolve(&samples);
+    info!("{} quiet positions after resolving captures", samples.len());
+
+    let k = find_k(&samples);
+    info!("scaling constant K = {k:.4}");
+
+    let start = Instant::now();
+    let tuned = tune(&samples, params(), k, &opts, |pass, params, error| {
+        info!(
+            "pass {pass}: error {error:.6} after {:.1}s, saving to {output}",
+            start.elapsed().as_secs_f64()
+        );
+        if let Err(e) = fs::write(&output, params.to_string()) {
+            log::error!("could not save parameters to {output}: {e}");
+        }
+    });
+    fs::write(&output, tuned.to_string())
+        .with_context(|| format!("could not save parameters to {output}"))?;
+    info!("tuned parameters written to {output}");
+
+    Ok(())
+}
@@ -8,17 +8,23 @@ use std::str::FromStr;
 use std::time::Instant;
 
 use anyhow::Result;
-use chess::Board;
 use log::info;
 use log::warn;
 use sandy_engine::Engine;
 use sandy_engine::debug::DebugLevel;
+use sandy_engine::evaluation::evaluator::Evaluator;
+#[cfg(feature = "nnue")]
+use sandy_engine::evaluation::nnue::Network;
+use sandy_engine::evaluation::params::EvalParams;
+use sandy_engine::evaluation::trace::trace;
 use sandy_engine::optlog;
 use sandy_engine::opts::Opts;
 use sandy_engine::opts::opts;
 use sandy_engine::opts::setopts;
 use sandy_engine::position::Position;
 use sandy_engine::setup::depth::Depth;
+use sandy_engine::transposition_table::persist::DEFAULT_HASH_FILE;
+use sandy_engine::tunable::find;
 use sandy_engine::util::Print;
 use vampirc_uci::Serializable;
 use vampirc_uci::UciMessage;
@@ -42,6 +48,9 @@ pub fn uci_loop(mut engine: Engine) -> Result<()> {
 
     println!("uciok");
 
+    // the file used to save and load the transposition table
+    let mut hash_file = DEFAULT_HASH_FILE.to_string();
+
     for line in std::io::stdin().lock().lines() {
         let msg: UciMessage = parse_one(&line?);
         optlog!(uci;trace;"Received message: {}", msg);
@@ -69,6 +78,75 @@ pub fn uci_loop(mut engine: Engine) -> Result<()> {
             UciMessage::SetOption { name, value } => {
                 match opts()?.receive_option(&name, value.as_deref()) {
                     Err(e) => optlog!(uci;error;"error setting option: {}", e),
+                    Ok(_) if name == "Clear Hash" => {
+                        engine.clear_table()?;
+                        println!("info string table cleared.");
+                    }
+                    Ok(_) if name == "Hash File" => {
+                        hash_file = value.unwrap_or_default();
+                    }
+                    Ok(_) if name == "Save Hash" => match engine.save_table(&hash_file) {
+                        Ok(saved) => println!("info string saved {saved} entries to {hash_file}."),
+                        Err(e) => optlog!(uci;error;"error saving table to {hash_file}: {}", e),
+                    },
+                    Ok(_) if name == "Eval Params" => {
+                        let path = value.unwrap_or_default();
+                        let params = if path.trim().is_empty() {
+                            Ok(EvalParams::DEFAULT)
+                        } else {
+                            EvalParams::load(path.trim())
+                        };
+                        match params.and_then(|p| engine.set_params(p)) {
+                            Ok(()) => println!("info string evaluation parameters set."),
+                            Err(e) => optlog!(uci;error;"error loading parameters: {:#}", e),
+                        }
+                    }
+                    // the search constants are set already, nothing to resize
+                    Ok(_) if cfg!(feature = "tune") && find(&name).is_some() => {
+                        optlog!(uci;info;"{name} set to {}.", value.unwrap_or_default());
+                    }
+                    #[cfg(feature = "nnue")]
+                    Ok(_) if name == "EvalFile" => {
+                        let path = value.unwrap_or_default();
+                        let net = if path.trim().is_empty() {
+                            Ok(None)
+                        } else {
+                            Network::load(path.trim()).map(Some)
+                        };
+                        match net.and_then(|n| engine.set_network(n)) {
+                            Ok(()) => println!("info string network set."),
+                            Err(e) => optlog!(uci;error;"error loading network: {:#}", e),
+                        }
+                    }
+                    Ok(opt) if name == "Eval Cache" => {
+                        setopts(opt)?;
+                        match engine.resize_eval_cache(opt.eval_cache_size) {
+                            0 => println!("info string evaluation cache disabled."),
+                            size => {
+                                println!("info string evaluation cache resized to {size} bytes.")
+                            }
+                        }
+                    }
+                    // the cached static evaluations are from the other evaluator
+                    Ok(opt) if name == "Use NNUE" => {
+                        setopts(opt)?;
+                        engine.clear_table()?;
+                        engine.evaluator.clear();
+                        println!(
+                            "info string evaluating with the {}.",
+                            if opt.use_nnue {
+                                "network"
+                            } else {
+                                "handcrafted evaluation"
+                            }
+                        );
+                    }
+                    Ok(_) if name == "Load Hash" => match engine.load_table(&hash_file) {
+                        Ok(loaded) => {
+                            println!("info string loaded {loaded} entries from {hash_file}.")
+                        }
+                        Err(e) => optlog!(uci;error;"error loading table from {hash_file}: {}", e),
+                    },
                     Ok(opt) => {
                         setopts(opt)?;
                         let entry_count = engine.resize_table(opt.hash_size)?;
@@ -84,7 +162,9 @@ pub fn uci_loop(mut engine: Engine) -> Result<()> {
             UciMessage::Register { .. } => {}
             UciMessage::UciNewGame => {
                 // clear any existing game state, such as transposition tables
-                // or search history ...
+                // or search history
+                engine.new_game()?;
+                optlog!(uci;info;"new game, engine state cleared");
             }
             UciMessage::Position {
                 startpos,
@@ -92,9 +172,9 @@ pub fn uci_loop(mut engine: Engine) -> Result<()> {
                 moves,
             } => {
                 if startpos {
-                    engine.board = Default::default();
+                    engine.set_position(Default::default());
                 } else if let Some(fen) = fen {
-                    engine.board = Position::from(Board::from_str(&fen.0).expect("invalid FEN"));
+                    engine.set_position(Position::from_str(&fen.0).expect("invalid FEN"));
                 }
 
                 for mv in moves {
@@ -144,6 +224,11 @@ pub fn uci_loop(mut engine: Engine) -> Result<()> {
                 if msg.trim().is_empty() {
                     continue;
                 }
+                // not part of the protocol, but handy for debugging
+                if msg.trim() == "eval" {
+                    println!("{}", trace(&engine.board));
+                    continue;
+                }
                 optlog!(uci;warn;"unrecognised message: {}", msg);
                 if let Some(err) = err {
                     if opts()?.comm.debug() {
@@ -38,10 +38,8 @@ impl TimeControl for Engine {
     }
 }
 
-/// chrono_duration_to_std_time
+/// chrono_duration_to_std_time, negative durations (eg a flagged clock) are
+/// zero
 fn cdt(d: chrono::Duration) -> std::time::Duration {
-    std::time::Duration::new(
-        d.num_seconds() as u64,
-        d.num_microseconds().unwrap_or(0) as u32,
-    )
+    d.to_std().unwrap_or_default()
 }
@@ -0,0 +1,36 @@
+//! test the `calibrate` subcommand: it plays a match between two levels and
+//! logs the rating of the stronger one
+use std::path::PathBuf;
+use std::process::Command;
+
+use colored::Colorize;
+
+#[test]
+fn main() {
+    let exec = PathBuf::from(env!("CARGO_BIN_EXE_chesseng"));
+    let log = std::env::temp_dir().join(format!("sandy-calibrate-{}.log", std::process::id()));
+    let output = Command::new(exec)
+        .args([
+            "calibrate",
+            "--pairs",
+            "1",
+            "--step",
+            "20",
+            "--tc",
+            "1000+10",
+        ])
+        .arg("--log")
+        .arg(&log)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+
+    let log = std::fs::read_to_string(&log).unwrap();
+    println!("{}", log.blue());
+    assert!(log.contains("# calibrate: 1 pairs per level at 1000+10ms"));
+    assert!(log.contains("level 0: 800"));
+    assert!(log.contains("over level 0"));
+    assert!(log.contains("level 20: "));
+    assert_eq!(log.lines().last().unwrap().split(", ").count(), 2);
+}
@@ -0,0 +1,61 @@
+//! test that the engine still searches with less than a second on its clock
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Stdio;
+use std::str::FromStr;
+
+use chess::Board;
+use chess::ChessMove;
+use chess::MoveGen;
+use colored::Colorize;
+
+/// run the engine on the starting position with `wtime` and `btime` set to
+/// `ms`, and return its best move
+fn best_move(ms: u64) -> String {
+    let exec = PathBuf::from(env!("CARGO_BIN_EXE_chesseng"));
+    let mut child = Command::new(exec)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+
+    writeln!(stdin, "uci").unwrap();
+    writeln!(stdin, "setoption name threads value 1").unwrap();
+    writeln!(stdin, "position startpos").unwrap();
+    writeln!(stdin, "go wtime {ms} btime {ms}").unwrap();
+    stdin.flush().unwrap();
+
+    let best = loop {
+        let mut line = String::new();
+        assert_ne!(
+            stdout.read_line(&mut line).unwrap(),
+            0,
+            "engine exited early"
+        );
+        let parts = line.split_whitespace().collect::<Vec<&str>>();
+        if parts.first() == Some(&"bestmove") {
+            break parts[1].to_string();
+        }
+    };
+    writeln!(stdin, "quit").unwrap();
+    child.wait().unwrap();
+    println!("{}", format!("{ms}ms: {best}").green());
+    best
+}
+
+#[test]
+fn under_a_second() {
+    // the milliseconds of the clock were once read as nanoseconds, which left
+    // no time to finish even the first iteration
+    let board = Board::default();
+    for ms in [900, 1900] {
+        let best = best_move(ms);
+        let mv = ChessMove::from_str(&best).unwrap();
+        assert!(MoveGen::new_legal(&board).any(|m| m == mv), "{best}");
+    }
+}
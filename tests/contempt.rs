@@ -0,0 +1,88 @@
+//! test that the `Contempt` option decides whether the engine repeats the
+//! position, and that `UCI_AnalyseMode` turns it off
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Stdio;
+
+use colored::Colorize;
+
+/// the knights have gone out and back twice, so taking the black knight back
+/// to g8 repeats the starting position for the third time
+const REPEATING: &str = "position startpos moves g1f3 g8f6 f3g1 f6g8 g1f3 g8f6 f3g1";
+
+/// the move that repeats the position
+const REPETITION: &str = "f6g8";
+
+/// run the engine with `options` set, search [`REPEATING`], and return its
+/// best move and the score of the repetition
+fn best_move(options: &[&str]) -> (String, Option<i64>) {
+    let exec = PathBuf::from(env!("CARGO_BIN_EXE_chesseng"));
+    let mut child = Command::new(exec)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+
+    writeln!(stdin, "uci").unwrap();
+    for option in options {
+        writeln!(stdin, "setoption name {option}").unwrap();
+    }
+    writeln!(stdin, "setoption name threads value 1").unwrap();
+    writeln!(stdin, "{REPEATING}").unwrap();
+    writeln!(stdin, "go depth 1").unwrap();
+    stdin.flush().unwrap();
+
+    let mut score = None;
+    let best = loop {
+        let mut line = String::new();
+        assert_ne!(
+            stdout.read_line(&mut line).unwrap(),
+            0,
+            "engine exited early"
+        );
+        let parts = line.split_whitespace().collect::<Vec<&str>>();
+        if parts.first() == Some(&"bestmove") {
+            break parts[1].to_string();
+        }
+        // the score of the best move, when the best move is the repetition
+        if parts.first() == Some(&"info")
+            && parts.contains(&"depth")
+            && parts.iter().skip_while(|p| **p != "pv").nth(1) == Some(&REPETITION)
+            && let Some(cp) = parts.iter().position(|p| *p == "cp")
+        {
+            score = parts[cp + 1].parse().ok();
+        }
+    };
+    writeln!(stdin, "quit").unwrap();
+    child.wait().unwrap();
+    println!(
+        "{}",
+        format!("{options:?}: {best}, repetition scored {score:?}").green()
+    );
+    (best, score)
+}
+
+#[test]
+fn contempt_avoids_repetition() {
+    let (best, _) = best_move(&["Contempt value 100"]);
+    assert_ne!(best, REPETITION);
+}
+
+#[test]
+fn negative_contempt_seeks_repetition() {
+    let (best, score) = best_move(&["Contempt value -100"]);
+    assert_eq!(best, REPETITION);
+    assert_eq!(score, Some(100));
+}
+
+#[test]
+fn analysis_ignores_contempt() {
+    let (_, score) = best_move(&["Contempt value -100", "UCI_AnalyseMode value true"]);
+    // the repetition is only the best move if nothing else beats a draw
+    assert!(score.is_none_or(|cp| cp == 0), "{score:?}");
+}
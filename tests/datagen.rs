@@ -0,0 +1,64 @@
+//! test the `datagen` subcommand: it writes quiet positions from self-play in
+//! both formats, which the tuners can read back, and appends to its output
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+use chess::EMPTY;
+use colored::Colorize;
+use sandy_engine::tuner::data::parse_binary;
+use sandy_engine::tuner::data::read_samples;
+
+/// generate a couple of quick games into `out`
+fn datagen(out: &Path) {
+    let exec = PathBuf::from(env!("CARGO_BIN_EXE_chesseng"));
+    let output = Command::new(exec)
+        .args([
+            "datagen",
+            "--games",
+            "2",
+            "--depth",
+            "2",
+            "--concurrency",
+            "2",
+        ])
+        .arg("--out")
+        .arg(out)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    println!("{}", stderr.blue());
+    assert!(output.status.success(), "{stderr}");
+    assert!(stderr.contains("2/2 games"), "{stderr}");
+}
+
+#[test]
+fn main() {
+    let dir = std::env::temp_dir().join(format!("sandy-datagen-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let binary = dir.join("data.bin");
+    datagen(&binary);
+    let points = parse_binary(&std::fs::read(&binary).unwrap()).unwrap();
+    assert!(!points.is_empty());
+    for point in &points {
+        assert_eq!(*point.board.checkers(), EMPTY, "{}", point.board);
+        assert!([0.0, 0.5, 1.0].contains(&point.result));
+    }
+    // more games are appended to the same file
+    datagen(&binary);
+    let more = parse_binary(&std::fs::read(&binary).unwrap()).unwrap();
+    assert!(more.len() > points.len());
+    assert_eq!(more[..points.len()], points[..]);
+
+    let text = dir.join("data.txt");
+    datagen(&text);
+    let lines = std::fs::read_to_string(&text).unwrap();
+    assert!(
+        lines.lines().all(|l| l.split(" | ").count() == 3),
+        "{lines}"
+    );
+    assert_eq!(read_samples(&text).unwrap().len(), lines.lines().count());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,118 @@
+//! test that `ucinewgame` and `Clear Hash` leave the engine in a clean state
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ChildStdin;
+use std::process::ChildStdout;
+use std::process::Command;
+use std::process::Stdio;
+
+use colored::Colorize;
+
+/// the (depth, nodes, hashfull) of every `info` line of a search
+type SearchTrace = Vec<(u32, u64, u64)>;
+
+/// send a sequence of commands, and collect the search info until `bestmove`
+fn search(
+    writer: &mut BufWriter<ChildStdin>,
+    reader: &mut BufReader<ChildStdout>,
+    sequence: &[&str],
+) -> SearchTrace {
+    for seq in sequence.iter() {
+        writer.write_all(seq.as_bytes()).unwrap();
+        writer.write_all(b"\n").unwrap();
+        writer.flush().unwrap();
+    }
+
+    let field = |parts: &[&str], name: &str| {
+        parts
+            .iter()
+            .position(|&x| x == name)
+            .map(|idx| parts[idx + 1].parse::<u64>().unwrap())
+    };
+
+    let mut trace = vec![];
+    loop {
+        let mut line = String::new();
+        assert_ne!(
+            reader.read_line(&mut line).unwrap(),
+            0,
+            "engine exited early"
+        );
+        let parts = line.split_whitespace().collect::<Vec<&str>>();
+        if parts.len() > 1 && parts[0] == "bestmove" {
+            println!("Best move: {}", parts[1]);
+            break;
+        } else if parts.len() > 1 && parts[0] == "info" && parts[1] == "depth" {
+            println!(
+                "{}",
+                format!("engine_info: {}", line.trim()).black().on_cyan()
+            );
+            trace.push((
+                field(&parts, "depth").unwrap() as u32,
+                field(&parts, "nodes").unwrap(),
+                field(&parts, "hashfull").unwrap(),
+            ));
+        }
+    }
+    trace
+}
+
+/// play the same opening search twice, in two different games, and make sure
+/// nothing from the first game leaks into the second one
+#[test]
+fn main() {
+    let exec = PathBuf::from(env!("CARGO_BIN_EXE_chesseng"));
+
+    let mut cmd = Command::new(exec);
+    cmd.stdin(Stdio::piped());
+    cmd.stdout(Stdio::piped());
+
+    let mut child = cmd.spawn().unwrap();
+
+    let mut writer = BufWriter::new(child.stdin.take().unwrap());
+    let mut reader = BufReader::new(child.stdout.take().unwrap());
+
+    let game = ["isready", "position startpos moves e2e4", "go depth 4"];
+
+    // single threaded, so that the search is deterministic
+    let first = search(
+        &mut writer,
+        &mut reader,
+        &[
+            &[
+                "uci",
+                "setoption name threads value 1",
+                "setoption name hash value 1",
+                "ucinewgame",
+            ][..],
+            &game[..],
+        ]
+        .concat(),
+    );
+    assert!(!first.is_empty(), "no search info received");
+
+    // without clearing, the second search reuses the transposition table
+    let reused = search(&mut writer, &mut reader, &game);
+    assert_ne!(first, reused, "search did not reuse the previous table");
+
+    let second = search(
+        &mut writer,
+        &mut reader,
+        &[&["ucinewgame"][..], &game[..]].concat(),
+    );
+    assert_eq!(first, second, "second game did not start clean");
+
+    let cleared = search(
+        &mut writer,
+        &mut reader,
+        &[&["setoption name Clear Hash"][..], &game[..]].concat(),
+    );
+    assert_eq!(first, cleared, "table was not cleared");
+
+    writer.write_all(b"quit\n").unwrap();
+    writer.flush().unwrap();
+    child.wait().unwrap();
+}
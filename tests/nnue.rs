@@ -0,0 +1,130 @@
+//! test that a network can be loaded and searched with, through the library
+//! and the UCI options. only built with the `nnue` feature.
+#![cfg(feature = "nnue")]
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Stdio;
+
+use chess::ChessMove;
+use colored::Colorize;
+use sandy_engine::evaluation::evaluate;
+use sandy_engine::evaluation::nnue::FEATURES_PER_BUCKET;
+use sandy_engine::evaluation::nnue::KING_BUCKETS;
+use sandy_engine::evaluation::nnue::Network;
+use sandy_engine::evaluation::nnue::QA;
+use sandy_engine::evaluation::nnue::QB;
+use sandy_engine::evaluation::nnue::accumulator::NnueAccumulator;
+use sandy_engine::evaluation::nnue::set_network;
+use sandy_engine::evaluation::search_eval;
+use sandy_engine::opts::Opts;
+use sandy_engine::position::Position;
+use sandy_engine::setup::values::Value;
+
+/// a network that only counts pawns: a pawn is worth a centipawn for each of
+/// its side's neurons
+fn pawn_counter() -> Network {
+    let mut net = Network::zeroed();
+    for bucket in 0..KING_BUCKETS {
+        for sq in 8..56 {
+            let ours = bucket * FEATURES_PER_BUCKET + sq;
+            net.feature_weights[ours][0] = QA as i16 / 8;
+            net.feature_weights[ours + 6 * 64][1] = QA as i16 / 8;
+        }
+    }
+    // own pawns count for the side to move, the opponent's against it
+    net.output_weights[0][0] = QB as i16;
+    net.output_weights[0][1] = -QB as i16;
+    net.output_weights[1][0] = -QB as i16;
+    net.output_weights[1][1] = QB as i16;
+    net
+}
+
+/// run the engine, send it `search`, wait for the best move, then send it
+/// `after`, and return everything it printed
+fn run(search: &[&str], after: &[&str]) -> String {
+    let exec = PathBuf::from(env!("CARGO_BIN_EXE_chesseng"));
+    let mut child = Command::new(exec)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    for seq in search {
+        writeln!(stdin, "{seq}").unwrap();
+    }
+
+    let mut output = String::new();
+    while !output.contains("bestmove") {
+        assert_ne!(stdout.read_line(&mut output).unwrap(), 0, "{output}");
+    }
+    for seq in after.iter().chain(&["quit"]) {
+        writeln!(stdin, "{seq}").unwrap();
+    }
+    drop(stdin);
+    stdout.read_to_string(&mut output).unwrap();
+    child.wait().unwrap();
+    output
+}
+
+#[test]
+fn library() {
+    let net = pawn_counter();
+    let opts = Opts::new().nnue(true);
+
+    let start = Position::default();
+    assert_eq!(start.nnue, NnueAccumulator::default());
+    assert_eq!(search_eval(&start, false, &opts), evaluate(&start, false));
+
+    set_network(Some(net.clone()));
+    // stale accumulators are recomputed
+    assert_eq!(search_eval(&start, false, &opts), Value(0));
+
+    let mut pos = Position::default();
+    for mv in ["e2e4", "d7d5", "e4d5", "g8f6", "e1e2"] {
+        pos = pos.make_move(mv.parse::<ChessMove>().unwrap());
+    }
+    // black to move, a pawn down, and each pawn is worth 400 * 2 * 31 * 64 /
+    // (255 * 64) ~ 97 centipawns (for both neurons)
+    let value = search_eval(&pos, false, &opts);
+    println!("{}", format!("network: {value:?}").blue());
+    assert_eq!(value, Value(-97));
+    assert_eq!(pos.nnue, NnueAccumulator::new(&pos.chessboard));
+    assert_eq!(
+        search_eval(&pos, false, &Opts::new()),
+        evaluate(&pos, false)
+    );
+
+    set_network(None);
+    assert_eq!(search_eval(&pos, false, &opts), evaluate(&pos, false));
+}
+
+#[test]
+fn uci() {
+    let path = std::env::temp_dir().join(format!("sandy-net-{}.nnue", std::process::id()));
+    pawn_counter().save(&path).unwrap();
+    let path = path.to_str().unwrap();
+
+    let output = run(
+        &[
+            "uci",
+            &format!("setoption name EvalFile value {path}"),
+            "setoption name Use NNUE value on",
+            "position startpos moves e2e4 d7d5 e4d5",
+            "go depth 1",
+        ],
+        &["setoption name EvalFile value /no/such/file"],
+    );
+    println!("{}", output.blue());
+    assert!(output.contains("option name Use NNUE type check default false"));
+    assert!(output.contains("option name EvalFile type string"));
+    assert!(output.contains("info string network set."));
+    assert!(output.contains("info string evaluating with the network."));
+    // recapturing is the only way to get the pawn back
+    assert!(output.contains("bestmove d8d5") || output.contains("bestmove g8f6"));
+    assert!(output.contains("score cp 0"));
+}
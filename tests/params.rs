@@ -0,0 +1,79 @@
+//! test that evaluation parameters can be loaded from a file, with the CLI
+//! flag and the UCI option
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Stdio;
+
+use colored::Colorize;
+
+/// run the engine with `args`, send it `sequence`, and return everything it
+/// printed
+fn run(args: &[&str], sequence: &[&str]) -> String {
+    let exec = PathBuf::from(env!("CARGO_BIN_EXE_chesseng"));
+    let mut child = Command::new(exec)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+    for seq in sequence.iter().chain(&["quit"]) {
+        writeln!(stdin, "{seq}").unwrap();
+    }
+    drop(stdin);
+
+    String::from_utf8(child.wait_with_output().unwrap().stdout).unwrap()
+}
+
+/// the evaluations printed by the `eval` command, in order
+fn evaluations(output: &str) -> Vec<i32> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix("evaluation: "))
+        .map(|rest| rest.split_whitespace().next().unwrap().parse().unwrap())
+        .collect()
+}
+
+#[test]
+fn main() {
+    let path = std::env::temp_dir().join(format!("sandy-params-{}.toml", std::process::id()));
+    std::fs::write(&path, "# a big tempo\ntempo = 100\n").unwrap();
+    let path = path.to_str().unwrap();
+
+    // the startpos is symmetric, only the tempo counts
+    let output = run(
+        &["--params", path],
+        &[
+            "uci",
+            "position startpos",
+            "eval",
+            "setoption name Eval Params value",
+            "eval",
+            &format!("setoption name Eval Params value {path}"),
+            "position startpos moves e2e4 e7e5",
+            "eval",
+        ],
+    );
+    println!(
+        "{}",
+        format!("evaluations: {:?}", evaluations(&output)).blue()
+    );
+    assert_eq!(evaluations(&output), [100, 25, 100]);
+
+    // a broken file is reported, and the built-in parameters stay
+    std::fs::write(path, "tempo = [1, 2]\n").unwrap();
+    let output = run(
+        &[],
+        &[
+            "uci",
+            &format!("setoption name Eval Params value {path}"),
+            "position startpos",
+            "eval",
+        ],
+    );
+    assert_eq!(evaluations(&output), [25]);
+
+    std::fs::remove_file(path).unwrap();
+}
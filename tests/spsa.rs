@@ -0,0 +1,39 @@
+//! test the `spsa` subcommand: it refuses engines that can't be tuned, and
+//! with the `tune` feature it plays an iteration and logs the result
+use std::path::PathBuf;
+use std::process::Command;
+
+use colored::Colorize;
+
+#[test]
+fn main() {
+    let exec = PathBuf::from(env!("CARGO_BIN_EXE_chesseng"));
+    let log = std::env::temp_dir().join(format!("sandy-spsa-{}.log", std::process::id()));
+    let output = Command::new(exec)
+        .args([
+            "spsa",
+            "--iterations",
+            "1",
+            "--pairs",
+            "1",
+            "--tc",
+            "1000+10",
+        ])
+        .arg("--log")
+        .arg(&log)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    if cfg!(feature = "tune") {
+        assert!(output.status.success(), "{stderr}");
+        let log = std::fs::read_to_string(&log).unwrap();
+        println!("{}", log.blue());
+        assert!(log.contains("start: max_extend=16"));
+        assert!(log.contains("iteration 1/1"));
+        assert!(log.contains("setoption name max_extend value"));
+    } else {
+        assert!(!output.status.success());
+        assert!(stderr.contains("--features tune"), "{stderr}");
+    }
+}
@@ -0,0 +1,58 @@
+//! test that the table and eval cache counters are reported at the end of
+//! every search, including the ones the clock stops halfway through
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Stdio;
+
+use colored::Colorize;
+
+/// run the engine on the starting position with the `go` command `go`, and
+/// return everything it printed up to its best move
+fn search(go: &str) -> Vec<String> {
+    let exec = PathBuf::from(env!("CARGO_BIN_EXE_chesseng"));
+    let mut child = Command::new(exec)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+
+    writeln!(stdin, "uci").unwrap();
+    writeln!(stdin, "position startpos").unwrap();
+    writeln!(stdin, "{go}").unwrap();
+    stdin.flush().unwrap();
+
+    let mut lines = vec![];
+    loop {
+        let mut line = String::new();
+        assert_ne!(
+            stdout.read_line(&mut line).unwrap(),
+            0,
+            "engine exited early"
+        );
+        lines.push(line.trim().to_string());
+        if line.starts_with("bestmove") {
+            break;
+        }
+    }
+    writeln!(stdin, "quit").unwrap();
+    child.wait().unwrap();
+    println!("{}", format!("{go}: {}", lines.join("\n")).green());
+    lines
+}
+
+#[test]
+fn reported_on_every_stop() {
+    for go in ["go depth 3", "go movetime 500", "go wtime 2000 btime 2000"] {
+        let lines = search(go);
+        assert!(
+            lines.iter().any(|l| l.starts_with("info string tt ")),
+            "{go}"
+        );
+        assert!(lines.last().unwrap().starts_with("bestmove "), "{go}");
+    }
+}
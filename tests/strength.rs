@@ -0,0 +1,114 @@
+//! test that `Skill Level` and `UCI_LimitStrength` limit how deep the engine
+//! searches, and that it still plays legal moves
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Stdio;
+use std::str::FromStr;
+
+use chess::Board;
+use chess::ChessMove;
+use chess::MoveGen;
+use colored::Colorize;
+
+/// a middlegame position, with plenty of moves to choose from
+const FEN: &str = "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4";
+
+/// four pawns that can take seven hanging pieces between them, so that the
+/// best few moves are captures at any depth
+const CAPTURES_FEN: &str = "7k/8/8/1n1b1r1q/P1P1P1P1/8/8/6K1 w - - 0 1";
+
+/// run the engine with `options` set, search `fen` with the `go` command
+/// `go`, and return the deepest depth it reported and its best move
+fn search_with(fen: &str, options: &[&str], go: &str) -> (u32, ChessMove) {
+    let exec = PathBuf::from(env!("CARGO_BIN_EXE_chesseng"));
+    let mut child = Command::new(exec)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+
+    writeln!(stdin, "uci").unwrap();
+    for option in options {
+        writeln!(stdin, "setoption name {option}").unwrap();
+    }
+    writeln!(stdin, "setoption name threads value 1").unwrap();
+    writeln!(stdin, "position fen {fen}").unwrap();
+    writeln!(stdin, "{go}").unwrap();
+    stdin.flush().unwrap();
+
+    let mut depth = 0;
+    let best = loop {
+        let mut line = String::new();
+        assert_ne!(
+            stdout.read_line(&mut line).unwrap(),
+            0,
+            "engine exited early"
+        );
+        let parts = line.split_whitespace().collect::<Vec<&str>>();
+        if parts.first() == Some(&"bestmove") {
+            break ChessMove::from_str(parts[1]).unwrap();
+        }
+        if parts.first() == Some(&"info") && parts.get(1) == Some(&"depth") {
+            depth = depth.max(parts[2].parse().unwrap());
+        }
+    };
+    writeln!(stdin, "quit").unwrap();
+    child.wait().unwrap();
+    println!("{}", format!("{options:?}: depth {depth}, {best}").green());
+    (depth, best)
+}
+
+/// [`search_with`] [`FEN`] to depth 6
+fn search(options: &[&str]) -> (u32, ChessMove) {
+    search_with(FEN, options, "go depth 6 movetime 10000")
+}
+
+/// whether `mv` can be played in [`FEN`]
+fn is_legal(mv: ChessMove) -> bool {
+    MoveGen::new_legal(&Board::from_str(FEN).unwrap()).any(|m| m == mv)
+}
+
+#[test]
+fn skill_level() {
+    let (depth, best) = search(&["Skill Level value 0"]);
+    assert_eq!(depth, 1);
+    assert!(is_legal(best), "{best}");
+
+    // 4 plies at most, fewer if the node limit is reached first
+    let (depth, best) = search(&["Skill Level value 6"]);
+    assert!((2..=4).contains(&depth), "{depth}");
+    assert!(is_legal(best), "{best}");
+}
+
+#[test]
+fn limit_strength() {
+    // the rating wins over the level
+    let (depth, best) = search(&[
+        "Skill Level value 6",
+        "UCI_LimitStrength value true",
+        "UCI_Elo value 800",
+    ]);
+    assert_eq!(depth, 1);
+    assert!(is_legal(best), "{best}");
+
+    // and is ignored without the limit
+    let (depth, _) = search(&["UCI_Elo value 800"]);
+    assert!(depth > 1, "{depth}");
+}
+
+#[test]
+fn skill_under_time_control() {
+    // the time runs out long before the depth or node limits of the level are
+    // reached, and the move must still be picked from the candidates, which
+    // are all captures here
+    let board = Board::from_str(CAPTURES_FEN).unwrap();
+    for _ in 0..5 {
+        let (_, mv) = search_with(CAPTURES_FEN, &["Skill Level value 10"], "go movetime 200");
+        assert!(board.piece_on(mv.get_dest()).is_some(), "{mv}");
+    }
+}
@@ -0,0 +1,34 @@
+//! test that the evaluation is left-right symmetric once the piece-square
+//! tables are, since the default ones aren't
+use colored::Colorize;
+use sandy_engine::evaluation::params::EvalParams;
+use sandy_engine::evaluation::params::set_params;
+use sandy_engine::evaluation::symmetry::Asymmetry;
+use sandy_engine::evaluation::symmetry::check_all;
+use sandy_engine::evaluation::symmetry::epd_positions;
+use sandy_engine::evaluation::symmetry::mirror;
+use sandy_engine::evaluation::symmetry::mirrored_params;
+use sandy_engine::util::bench_positions;
+
+#[test]
+fn mirrored_evaluation() {
+    // the parameters are global, but every test binary is its own process
+    set_params(mirrored_params(&EvalParams::DEFAULT));
+
+    let mut positions = bench_positions();
+    positions.extend(epd_positions());
+    let mirrored = positions.iter().filter_map(mirror).count();
+    let found: Vec<Asymmetry> = check_all(&positions);
+    for asymmetry in &found {
+        println!("{}", asymmetry.to_string().red());
+    }
+    assert!(found.is_empty(), "{} asymmetric positions", found.len());
+    println!(
+        "{}",
+        format!(
+            "{} positions symmetric, {mirrored} of them mirrored",
+            positions.len()
+        )
+        .green()
+    );
+}
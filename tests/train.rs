@@ -0,0 +1,74 @@
+//! test the `train` subcommand: it needs the `nnue` feature, and with it it
+//! writes checkpoints and networks the engine can load, and resumes from them
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Output;
+
+use colored::Colorize;
+
+/// positions labelled with the results of their games
+const POSITIONS: &str = "
+    4k3/8/8/8/8/8/8/3QK3 w - - 0 1 [1.0]
+    3qk3/8/8/8/8/8/8/4K3 w - - 0 1 [0.0]
+    4k3/8/8/8/8/8/8/3QK3 b - - 0 1 [1.0]
+    3qk3/8/8/8/8/8/8/4K3 b - - 0 1 [0.0]
+    4k3/pppp4/8/8/8/8/PPPP4/4K3 w - - 0 1 [0.5]
+    r3k3/8/8/8/8/8/P7/4K3 w - - 0 1 [0.0]
+    4k3/p7/8/8/8/8/8/R3K3 b - - 0 1 [1.0]
+    4k3/8/8/8/8/8/8/1N2K3 w - - 0 1 [0.5]
+";
+
+/// run `train` on `data`, writing to `out`
+fn train(data: &Path, out: &Path, extra: &[&str]) -> Output {
+    let exec = PathBuf::from(env!("CARGO_BIN_EXE_chesseng"));
+    Command::new(exec)
+        .arg("train")
+        .arg(data)
+        .arg("--out")
+        .arg(out)
+        .args(["--epochs", "2", "--batch", "4", "--validation", "0.25"])
+        .args(extra)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn main() {
+    let dir = std::env::temp_dir().join(format!("sandy-train-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let data = dir.join("positions.epd");
+    std::fs::write(&data, POSITIONS).unwrap();
+    let out = dir.join("out");
+
+    let output = train(&data, &out, &[]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    println!("{}", stderr.blue());
+
+    if !cfg!(feature = "nnue") {
+        assert!(!output.status.success());
+        assert!(stderr.contains("--features nnue"), "{stderr}");
+        return;
+    }
+    assert!(output.status.success(), "{stderr}");
+    assert!(stderr.contains("training on 6 positions, validating on 2"));
+    assert!(stderr.contains("epoch 2: training loss"));
+
+    #[cfg(feature = "nnue")]
+    {
+        use sandy_engine::evaluation::nnue::Network;
+        let best = Network::load(out.join("best.nnue")).unwrap();
+        let latest = Network::load(out.join("net.nnue")).unwrap();
+        assert_ne!(latest, Network::zeroed());
+        assert_ne!(best, Network::zeroed());
+    }
+
+    let checkpoint = out.join("checkpoint.bin");
+    let output = train(&data, &out, &["--resume", checkpoint.to_str().unwrap()]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(stderr.contains("after epoch 2"), "{stderr}");
+    assert!(stderr.contains("epoch 4: training loss"), "{stderr}");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,58 @@
+//! test that tuning lowers the error of the evaluation over some labelled
+//! positions, and leaves the parameters in use untouched
+use std::str::FromStr;
+
+use colored::Colorize;
+use sandy_engine::evaluation::params::EvalParams;
+use sandy_engine::evaluation::params::params;
+use sandy_engine::evaluation::params::set_params;
+use sandy_engine::tuner::TuneOptions;
+use sandy_engine::tuner::data::parse_labelled;
+use sandy_engine::tuner::error;
+use sandy_engine::tuner::find_k;
+use sandy_engine::tuner::resolve;
+use sandy_engine::tuner::tune;
+
+/// positions whose results the default evaluation doesn't quite agree with
+const POSITIONS: &str = "
+    4k3/8/8/8/8/8/8/1N2K3 w - - 0 1 [0.5]
+    4k3/8/8/8/8/8/8/2B1K3 b - - 0 1 [0.5]
+    1n2k3/8/8/8/8/8/8/4K3 w - - 0 1 [0.5]
+    4k3/pppp4/8/8/8/8/PPPP4/3QK3 w - - 0 1 [1.0]
+    3qk3/pppp4/8/8/8/8/PPPP4/4K3 b - - 0 1 [0.0]
+    4k3/pppp4/8/8/8/8/PPPP4/4K3 w - - 0 1 [0.5]
+    r3k3/8/8/8/8/8/P7/4K3 w - - 0 1 [0.0]
+    4k3/p7/8/8/8/8/8/R3K3 b - - 0 1 [1.0]
+";
+
+#[test]
+fn main() {
+    let samples = resolve(&parse_labelled(POSITIONS).unwrap());
+    assert_eq!(samples.len(), 8);
+
+    let k = find_k(&samples);
+    let before = error(&samples, k);
+    let mut passes = vec![];
+    let opts = TuneOptions { passes: 1, step: 5 };
+    let tuned = tune(&samples, EvalParams::DEFAULT, k, &opts, |pass, _, error| {
+        passes.push((pass, error))
+    });
+    println!(
+        "{}",
+        format!("K = {k:.4}, error {before:.6} -> {:?}", passes).blue()
+    );
+
+    // the parameters in use are restored
+    assert_eq!(params(), EvalParams::DEFAULT);
+    assert_eq!(error(&samples, k), before);
+
+    assert_eq!(passes.len(), 1);
+    assert_ne!(tuned, EvalParams::DEFAULT);
+    set_params(tuned);
+    let after = error(&samples, k);
+    assert!(after < before, "{after} >= {before}");
+    assert_eq!(passes[0], (1, after));
+
+    // and can be saved and loaded back
+    assert_eq!(EvalParams::from_str(&tuned.to_string()).unwrap(), tuned);
+}